@@ -0,0 +1,84 @@
+#![deny(missing_docs)]
+
+//! The `#[derive(FromPattern)]` macro of the [fontconfig] crate, which is re-exported there when
+//! its `derive` feature is enabled. See the documentation of `fontconfig::FromPattern` for the
+//! attributes it accepts.
+//!
+//! [fontconfig]: https://crates.io/crates/fontconfig
+
+use std::ffi::CString;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt, LitStr};
+
+/// Derive `fontconfig::FromPattern` for a struct with named fields.
+///
+/// Each field is read from the pattern element named after it, with underscores removed, so
+/// `postscript_name` reads "postscriptname". The attribute `#[fc(rename = "...")]` reads another
+/// element and `#[fc(index = n)]` reads the value at position `n` rather than the first.
+#[proc_macro_derive(FromPattern, attributes(fc))]
+pub fn derive_from_pattern(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "FromPattern can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromPattern can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut reads = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut name = ident.to_string().trim_start_matches("r#").replace('_', "");
+        let mut index = 0;
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("fc")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("index") {
+                    index = meta.value()?.parse::<LitInt>()?.base10_parse::<i32>()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename` or `index`"))
+                }
+            })?;
+        }
+        let name = CString::new(name)
+            .map_err(|_| Error::new_spanned(ident, "element names cannot contain NUL"))?;
+        let name = Literal::c_string(&name);
+        reads.push(quote! {
+            #ident: ::fontconfig::FromPatternValue::from_pattern_value(pattern, #name, #index)?
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::fontconfig::FromPattern for #ident #ty_generics #where_clause {
+            fn from_pattern(pattern: &::fontconfig::Pattern<'_>) -> ::fontconfig::Result<Self> {
+                ::std::result::Result::Ok(#ident {
+                    #(#reads,)*
+                })
+            }
+        }
+    })
+}
@@ -12,9 +12,88 @@ use std::os::raw::{c_char, c_double, c_int, c_uchar, c_uint, c_ushort, c_void};
 
 pub use dlib::ffi_dispatch;
 
+/// Call a function declared with `optional_functions!`, like `ffi_dispatch!` does for the
+/// others.
+///
+/// With the `dlopen` feature this panics if the loaded library lacks the function, so callers
+/// check [`available`] first.
+#[macro_export]
+macro_rules! ffi_dispatch_optional(
+    ($func: ident, $($arg: expr),*) => ({
+        #[cfg(feature = "dlopen")]
+        let func = $crate::statics::OPTIONAL.$func.expect(concat!(
+            stringify!($func),
+            " is missing from the loaded Fontconfig"
+        ));
+        #[cfg(not(feature = "dlopen"))]
+        let func = $func;
+        func($($arg),*)
+    })
+);
+
+/// Declare functions added in later versions of Fontconfig.
+///
+/// When linking they are declared like the others, as the linker checks that they exist. With
+/// the `dlopen` feature each one is looked up on its own, so that an older library still loads,
+/// and [`available`] tells which were found.
+macro_rules! optional_functions(
+    ($(fn $name: ident($($arg: ty),*) -> $ret: ty,)+) => (
+        #[cfg(not(feature = "dlopen"))]
+        extern "C" {
+            $(pub fn $name($(_: $arg),*) -> $ret;)+
+        }
+
+        /// The functions added in later versions of Fontconfig, `None` where the library lacks them.
+        #[cfg(feature = "dlopen")]
+        pub struct FcOptional {
+            // Keeps the functions loaded.
+            _lib: Option<dlib::Library>,
+            $(pub $name: Option<unsafe extern "C" fn($($arg),*) -> $ret>,)+
+        }
+
+        #[cfg(feature = "dlopen")]
+        impl FcOptional {
+            /// # Safety
+            ///
+            /// As for `dlib::Library::new`, loading the library runs its initialisers.
+            pub unsafe fn open(name: &str) -> FcOptional {
+                let lib = dlib::Library::new(name).ok();
+                FcOptional {
+                    $($name: lib.as_ref().and_then(|lib| {
+                        lib.get::<unsafe extern "C" fn($($arg),*) -> $ret>(
+                            concat!(stringify!($name), "\0").as_bytes()
+                        )
+                        .ok()
+                        .map(|func| *func)
+                    }),)+
+                    _lib: lib,
+                }
+            }
+        }
+
+        /// Checks for the functions that older versions of Fontconfig lack.
+        ///
+        /// Each returns `true` if the function can be called. Without the `dlopen` feature this
+        /// is always the case, as the linker made sure of it.
+        pub mod available {
+            $(
+                #[cfg(feature = "dlopen")]
+                pub fn $name() -> bool {
+                    super::statics::OPTIONAL.$name.is_some()
+                }
+
+                #[cfg(not(feature = "dlopen"))]
+                pub fn $name() -> bool {
+                    true
+                }
+            )+
+        }
+    )
+);
+
 #[cfg(feature = "dlopen")]
 pub mod statics {
-    use super::Fc;
+    use super::{Fc, FcOptional};
     use once_cell::sync::Lazy;
 
     static SONAME: &str = if cfg!(windows) {
@@ -29,6 +108,10 @@ pub mod statics {
         Lazy::new(|| unsafe { Fc::open(SONAME) });
 
     pub static LIB: Lazy<&'static Fc> = Lazy::new(|| LIB_RESULT.as_ref().unwrap());
+
+    /// The functions of the loaded library that older versions of Fontconfig lack, see
+    /// [`available`](super::available).
+    pub static OPTIONAL: Lazy<FcOptional> = Lazy::new(|| unsafe { FcOptional::open(SONAME) });
 }
 
 pub type FcChar8 = c_uchar;
@@ -46,6 +129,7 @@ pub const FcTypeMatrix: u32 = 5_u32;
 pub const FcTypeCharSet: u32 = 6_u32;
 pub const FcTypeFTFace: u32 = 7_u32;
 pub const FcTypeLangSet: u32 = 8_u32;
+pub const FcTypeRange: u32 = 9_u32;
 
 pub type FcType = enum__FcType;
 
@@ -136,6 +220,7 @@ pub mod constants {
     pub const FC_SCALABLE: &CStr = c"scalable";
     pub const FC_COLOR: &CStr = c"color";
     pub const FC_VARIABLE: &CStr = c"variable";
+    pub const FC_NAMED_INSTANCE: &CStr = c"namedinstance";
     pub const FC_SCALE: &CStr = c"scale";
     pub const FC_SYMBOL: &CStr = c"symbol";
     pub const FC_DPI: &CStr = c"dpi";
@@ -224,7 +309,7 @@ pub type struct__FcLangSet = c_void;
 pub type FcLangSet = struct__FcLangSet;
 
 #[repr(C)]
-#[allow(missing_copy_implementations)]
+#[derive(Clone, Copy)]
 pub struct struct__FcValue {
     pub _type: FcType,
     pub u: union_unnamed1,
@@ -259,6 +344,32 @@ pub const FcMatchScan: u32 = 2_u32;
 
 pub type FcMatchKind = enum__FcMatchKind;
 
+pub type enum__FcValueBinding = c_uint;
+pub const FcValueBindingWeak: u32 = 0_u32;
+pub const FcValueBindingStrong: u32 = 1_u32;
+pub const FcValueBindingSame: u32 = 2_u32;
+
+pub type FcValueBinding = enum__FcValueBinding;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct struct__FcPatternIter {
+    pub dummy1: *mut c_void,
+    pub dummy2: *mut c_void,
+}
+
+pub type FcPatternIter = struct__FcPatternIter;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct struct__FcConfigFileInfoIter {
+    pub dummy1: *mut c_void,
+    pub dummy2: *mut c_void,
+    pub dummy3: *mut c_void,
+}
+
+pub type FcConfigFileInfoIter = struct__FcConfigFileInfoIter;
+
 pub type enum__FcLangResult = c_uint;
 pub const FcLangEqual: u32 = 0_u32;
 pub const FcLangDifferentCountry: u32 = 1_u32;
@@ -305,7 +416,27 @@ pub type struct__FcCache = c_void;
 
 pub type FcCache = struct__FcCache;
 
-pub type union_unnamed1 = c_void;
+pub type struct__FcRange = c_void;
+
+pub type FcRange = struct__FcRange;
+
+/// The value held by an [`FcValue`], selected by its `_type`.
+///
+/// This was an opaque `c_void` before version 7, which gave `FcValue` the wrong size and
+/// alignment, so values could not be passed to or read from Fontconfig.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union union_unnamed1 {
+    pub s: *const FcChar8,
+    pub i: c_int,
+    pub b: FcBool,
+    pub d: c_double,
+    pub m: *const FcMatrix,
+    pub c: *const FcCharSet,
+    pub f: *mut c_void,
+    pub l: *const FcLangSet,
+    pub r: *const FcRange,
+}
 
 dlib::external_library!(Fc, "fontconfig",
     functions:
@@ -491,6 +622,22 @@ dlib::external_library!(Fc, "fontconfig",
             *mut c_int
         ) -> *mut FcPattern,
 
+        // The first argument is a FreeType Face object (`FT_Face`)
+        //
+        // <https://freetype.org/freetype2/docs/reference/ft2-base_interface.html#ft_face>
+        fn FcFreeTypeQueryFace(
+            *mut c_void,
+            *const FcChar8,
+            c_int,
+            *mut FcBlanks
+        ) -> *mut FcPattern,
+
+        // The first argument of these is also an `FT_Face`, and `FcFreeTypeCharIndex` returns
+        // an `FT_UInt` glyph index.
+        fn FcFreeTypeCharSet(*mut c_void, *mut FcBlanks) -> *mut FcCharSet,
+
+        fn FcFreeTypeCharIndex(*mut c_void, FcChar32) -> c_uint,
+
         fn FcFontSetCreate() -> *mut FcFontSet,
 
         fn FcFontSetDestroy(*mut FcFontSet) -> (),
@@ -724,6 +871,11 @@ dlib::external_library!(Fc, "fontconfig",
             *const FcLangSet
         ) -> FcBool,
 
+        // The last argument is a FreeType Face object (`FT_Face`)
+        //
+        // <https://freetype.org/freetype2/docs/reference/ft2-base_interface.html#ft_face>
+        fn FcPatternAddFTFace(*mut FcPattern, *const c_char, *const c_void) -> FcBool,
+
         fn FcPatternGetInteger(
             *mut FcPattern,
             *const c_char,
@@ -860,3 +1012,79 @@ dlib::external_library!(Fc, "fontconfig",
         fn FcPatternBuild(*mut FcPattern) -> *mut FcPattern,
         fn FcObjectSetBuild(*mut c_char) -> *mut FcObjectSet,
 );
+
+optional_functions!(
+    fn FcConfigGetSysRoot(*const FcConfig) -> *const FcChar8,
+
+    fn FcConfigSetSysRoot(*mut FcConfig, *const FcChar8) -> (),
+
+    fn FcLangSetDel(*mut FcLangSet, *const FcChar8) -> FcBool,
+
+    fn FcLangSetUnion(*const FcLangSet, *const FcLangSet) -> *mut FcLangSet,
+
+    fn FcLangSetSubtract(*const FcLangSet, *const FcLangSet) -> *mut FcLangSet,
+
+    fn FcRangeCreateDouble(c_double, c_double) -> *mut FcRange,
+
+    fn FcRangeCreateInteger(FcChar32, FcChar32) -> *mut FcRange,
+
+    fn FcRangeDestroy(*mut FcRange) -> (),
+
+    fn FcRangeCopy(*const FcRange) -> *mut FcRange,
+
+    fn FcRangeGetDouble(*const FcRange, *mut c_double, *mut c_double) -> FcBool,
+
+    fn FcPatternAddRange(*mut FcPattern, *const c_char, *const FcRange) -> FcBool,
+
+    fn FcPatternGetRange(*mut FcPattern, *const c_char, c_int, *mut *mut FcRange) -> FcResult,
+
+    fn FcConfigParseAndLoadFromMemory(
+        *mut FcConfig,
+        *const FcChar8,
+        FcBool
+    ) -> FcBool,
+
+    fn FcFreeTypeQueryAll(
+        *const FcChar8,
+        c_int,
+        *mut FcBlanks,
+        *mut c_int,
+        *mut FcFontSet
+    ) -> c_uint,
+
+    fn FcDirCacheRescan(*const FcChar8, *mut FcConfig) -> *mut FcCache,
+
+    fn FcWeightFromOpenTypeDouble(c_double) -> c_double,
+
+    fn FcWeightToOpenTypeDouble(c_double) -> c_double,
+
+    fn FcPatternIterStart(*const FcPattern, *mut FcPatternIter) -> (),
+
+    fn FcPatternIterNext(*const FcPattern, *mut FcPatternIter) -> FcBool,
+
+    fn FcPatternIterIsValid(*const FcPattern, *mut FcPatternIter) -> FcBool,
+
+    fn FcPatternIterGetObject(*const FcPattern, *mut FcPatternIter) -> *const c_char,
+
+    fn FcPatternIterValueCount(*const FcPattern, *mut FcPatternIter) -> c_int,
+
+    fn FcPatternIterGetValue(
+        *const FcPattern,
+        *mut FcPatternIter,
+        c_int,
+        *mut FcValue,
+        *mut FcValueBinding
+    ) -> FcResult,
+
+    fn FcConfigFileInfoIterInit(*mut FcConfig, *mut FcConfigFileInfoIter) -> (),
+
+    fn FcConfigFileInfoIterNext(*mut FcConfig, *mut FcConfigFileInfoIter) -> FcBool,
+
+    fn FcConfigFileInfoIterGet(
+        *mut FcConfig,
+        *mut FcConfigFileInfoIter,
+        *mut *mut FcChar8,
+        *mut *mut FcChar8,
+        *mut FcBool
+    ) -> FcBool,
+);
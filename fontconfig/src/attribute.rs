@@ -0,0 +1,132 @@
+//! Typed names of pattern elements, for elements this crate has no accessor for.
+
+use std::ffi::CStr;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::{FromPatternValue, IntoPatternValue, Pattern, Result};
+
+/// The name of a pattern element together with the type of its values.
+///
+/// This crate names elements with plain `&CStr` constants such as [`FC_FAMILY`], leaving the
+/// type of their values to each accessor. An `Attribute` lets other crates declare the type of
+/// niche or custom elements once, as a constant, and then read and add their values without
+/// repeating it.
+///
+/// [`FC_FAMILY`]: crate::FC_FAMILY
+///
+/// ```
+/// use fontconfig::{Attribute, Fontconfig, Pattern};
+///
+/// const PIXEL_SIZE_FIXUP: Attribute<f64> = Attribute::new(c"pixelsizefixupfactor");
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let mut pat = Pattern::new(&fc);
+/// pat.add_attribute(PIXEL_SIZE_FIXUP, 1.5);
+/// assert_eq!(pat.get_attribute(PIXEL_SIZE_FIXUP).unwrap(), 1.5);
+/// ```
+pub struct Attribute<T> {
+    name: &'static CStr,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Attribute<T> {
+    /// Declare the element `name` as holding values of type `T`.
+    pub const fn new(name: &'static CStr) -> Attribute<T> {
+        Attribute {
+            name,
+            _type: PhantomData,
+        }
+    }
+
+    /// The name of the element.
+    pub const fn name(&self) -> &'static CStr {
+        self.name
+    }
+}
+
+impl<T> Clone for Attribute<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Attribute<T> {}
+
+impl<T> fmt::Debug for Attribute<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Attribute").field(&self.name).finish()
+    }
+}
+
+impl Pattern<'_> {
+    /// Get the first value of the element `attr`.
+    pub fn get_attribute<T: FromPatternValue>(&self, attr: Attribute<T>) -> Result<T> {
+        T::from_pattern_value(self, attr.name, 0)
+    }
+
+    /// Iterate over the values of the element `attr`, in order.
+    ///
+    /// The iterator stops at the first value that is not of type `T`, so it is empty if the
+    /// element is missing.
+    ///
+    /// ```
+    /// use fontconfig::{Attribute, Fontconfig, Pattern};
+    ///
+    /// const FAMILY: Attribute<String> = Attribute::new(fontconfig::FC_FAMILY);
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_attribute(FAMILY, "DejaVu Sans".to_owned());
+    /// pat.add_attribute(FAMILY, "sans-serif".to_owned());
+    /// assert!(pat.get_all(FAMILY).eq(["DejaVu Sans", "sans-serif"]));
+    /// ```
+    pub fn get_all<T: FromPatternValue>(&self, attr: Attribute<T>) -> impl Iterator<Item = T> + '_ {
+        (0..).map_while(move |n| T::from_pattern_value(self, attr.name, n).ok())
+    }
+
+    /// Add a value to the element `attr`.
+    pub fn add_attribute<T: IntoPatternValue>(&mut self, attr: Attribute<T>, value: T) {
+        value.add_to(self, attr.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Fontconfig};
+
+    #[test]
+    fn typed_elements() {
+        const FAMILY: Attribute<String> = Attribute::new(crate::FC_FAMILY);
+        const HINTING: Attribute<bool> = Attribute::new(crate::FC_HINTING);
+
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        assert!(matches!(pat.get_attribute(FAMILY), Err(Error::NoMatch)));
+        pat.add_attribute(FAMILY, "Foo".to_owned());
+        pat.add_attribute(HINTING, false);
+        assert_eq!(pat.get_attribute(FAMILY).unwrap(), "Foo");
+        assert_eq!(pat.get_string(FAMILY.name()), Some("Foo"));
+        assert!(!pat.get_attribute(HINTING).unwrap());
+    }
+
+    #[test]
+    fn all_values() {
+        const LANG: Attribute<String> = Attribute::new(crate::FC_LANG);
+        const FACTOR: Attribute<f64> = Attribute::new(c"myfactor");
+
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        assert_eq!(pat.get_all(LANG).count(), 0);
+        for lang in ["en", "de", "fr"] {
+            pat.add_attribute(LANG, lang.to_owned());
+        }
+        assert_eq!(pat.get_all(LANG).collect::<Vec<_>>(), ["en", "de", "fr"]);
+
+        pat.add_attribute(FACTOR, 1.5);
+        pat.add(FACTOR.name(), "large");
+        pat.add_attribute(FACTOR, 2.0);
+        assert_eq!(pat.get_all(FACTOR).collect::<Vec<_>>(), [1.5]);
+    }
+}
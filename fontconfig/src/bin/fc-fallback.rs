@@ -0,0 +1,82 @@
+//! Print the runs that a string is split into for rendering, with the script and font of each.
+//!
+//! ```text
+//! fc-fallback [-l LANG] [-f FAMILY] TEXT
+//! ```
+//!
+//! This shows how [`Pattern::itemize`] falls back from the preferred font to others that cover
+//! the text, e.g. to check what an application will render a string with.
+
+use std::env;
+use std::process::ExitCode;
+
+use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_LANG};
+
+const USAGE: &str = "usage: fc-fallback [-l LANG] [-f FAMILY] TEXT";
+
+fn main() -> ExitCode {
+    let mut lang = None;
+    let mut family = None;
+    let mut text = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "-l" | "--lang" => &mut lang,
+            "-f" | "--family" => &mut family,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if text.is_none() => {
+                text = Some(arg);
+                continue;
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        };
+        match args.next() {
+            Some(arg) => *value = Some(arg),
+            None => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some(text) = text else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let Some(fc) = Fontconfig::new() else {
+        eprintln!("fc-fallback: cannot initialise Fontconfig");
+        return ExitCode::FAILURE;
+    };
+    let mut pat = Pattern::new(&fc);
+    pat.add(FC_FAMILY, family.as_deref().unwrap_or("sans-serif"));
+    if let Some(lang) = &lang {
+        pat.add(FC_LANG, lang.as_str());
+    }
+
+    let runs = pat.itemize(&text);
+    if runs.is_empty() && !text.is_empty() {
+        eprintln!("fc-fallback: no fonts found");
+        return ExitCode::FAILURE;
+    }
+    for run in runs {
+        let index = run.font.index.unwrap_or(0);
+        let script = run.script.map(|script| format!("{:?}", script));
+        println!(
+            "{}..{}\t{:?}\t{}\t{}\t{}:{}",
+            run.range.start,
+            run.range.end,
+            &text[run.range.clone()],
+            script.as_deref().unwrap_or("Common"),
+            run.font.name,
+            run.font.path.display(),
+            index
+        );
+    }
+    ExitCode::SUCCESS
+}
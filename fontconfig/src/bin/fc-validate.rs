@@ -0,0 +1,133 @@
+//! Report the characters a font lacks to support a language.
+//!
+//! ```text
+//! fc-validate [-i INDEX] -l LANG... FILE
+//! ```
+//!
+//! The orthography of each language comes from [`LangSet::charset`], and is compared with the
+//! characters the font covers, as Fontconfig does when deciding which languages a font supports.
+//! The exit status is non-zero if any language is not fully covered.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use fontconfig::{CharSet, Fontconfig, LangSet, Pattern};
+
+const USAGE: &str = "usage: fc-validate [-i INDEX] -l LANG... FILE";
+
+fn main() -> ExitCode {
+    let mut index = 0;
+    let mut langs = Vec::new();
+    let mut file = None;
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("-i" | "--index") => {
+                match args.next().and_then(|arg| arg.to_str()?.parse().ok()) {
+                    Some(arg) => index = arg,
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            Some("-l" | "--lang") => match args.next().and_then(|arg| arg.into_string().ok()) {
+                Some(arg) => langs.push(arg),
+                None => {
+                    eprintln!("{}", USAGE);
+                    return ExitCode::FAILURE;
+                }
+            },
+            Some("-h" | "--help") => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if file.is_none() => file = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some(file) = file.filter(|_| !langs.is_empty()) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let Some(fc) = Fontconfig::new() else {
+        eprintln!("fc-validate: cannot initialise Fontconfig");
+        return ExitCode::FAILURE;
+    };
+    let Some(chars) = Pattern::from_file(&fc, &file, index).and_then(|font| font.char_set()) else {
+        eprintln!(
+            "fc-validate: cannot read a font at index {} of {}",
+            index,
+            file.display()
+        );
+        return ExitCode::FAILURE;
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for lang in &langs {
+        let Some(orthography) = LangSet::charset(&fc, lang) else {
+            eprintln!("fc-validate: no orthography for {}", lang);
+            status = ExitCode::FAILURE;
+            continue;
+        };
+        let missing = missing_chars(&orthography, &chars);
+        if missing.is_empty() {
+            println!(
+                "{}:{} Satisfies the coverage for {}",
+                file.display(),
+                index,
+                lang
+            );
+            continue;
+        }
+        status = ExitCode::FAILURE;
+        println!(
+            "{}:{} Missing {} of {} characters for {}",
+            file.display(),
+            index,
+            missing.len(),
+            orthography.len(),
+            lang
+        );
+        for c in missing {
+            println!("\tU+{:04X}\t{}", c as u32, c);
+        }
+    }
+    status
+}
+
+/// The characters of `orthography` that are not in `chars`, in order.
+fn missing_chars(orthography: &CharSet, chars: &CharSet) -> Vec<char> {
+    orthography
+        .chars()
+        .filter(|&c| !chars.contains(c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_fontconfig() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("DejaVu Sans", None).unwrap();
+        let font = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+        let chars = font.char_set().unwrap();
+        let langs = font.get_lang_set().unwrap();
+        // Fontconfig treats the languages of a font as those whose orthography it covers fully,
+        // except for the CJK ones, which are left out of this comparison.
+        for lang in ["en", "ru", "el", "he", "ar", "vi", "hi", "th", "ka"] {
+            let missing = missing_chars(&LangSet::charset(&fc, lang).unwrap(), &chars);
+            assert_eq!(missing.is_empty(), langs.contains(lang), "{}", lang);
+        }
+        let mut partial = CharSet::from_text(&fc, "abc");
+        partial.add_char('\u{0B95}');
+        assert_eq!(missing_chars(&partial, &chars), ['\u{0B95}']);
+    }
+}
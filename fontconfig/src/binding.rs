@@ -0,0 +1,170 @@
+//! Iterating the values of a pattern together with how strongly they are bound.
+
+use std::ffi::{c_int, CStr};
+use std::iter;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+use crate::{ffi_dispatch_optional, sys, FcResultExt, FcTrue, Pattern, ValueType};
+
+/// How strongly a value is bound to its element, which decides how much it counts when matching.
+///
+/// Values added by applications are strong. Configuration files choose the binding of the
+/// values they add, e.g. aliases usually append weak or "same" families.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// The value counts for less than the languages of the pattern when matching.
+    Weak,
+    /// The value counts fully when matching.
+    Strong,
+    /// The value has the same binding as the value it was added next to.
+    Same,
+}
+
+impl Binding {
+    fn from_raw(binding: sys::FcValueBinding) -> Binding {
+        match binding {
+            sys::FcValueBindingWeak => Binding::Weak,
+            sys::FcValueBindingSame => Binding::Same,
+            _ => Binding::Strong,
+        }
+    }
+}
+
+/// A value of a pattern element, as seen when iterating the pattern.
+///
+/// The value itself can be read with the getter for its type, e.g.
+/// `pattern.try_get_string(value.name, value.index)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundValue<'a> {
+    /// The name of the element.
+    pub name: &'a CStr,
+    /// The position of the value among the values of the element.
+    pub index: c_int,
+    /// The type of the value, or `None` if it has none.
+    pub value_type: Option<ValueType>,
+    /// How strongly the value is bound.
+    pub binding: Binding,
+}
+
+fn value_type(value_type: sys::FcType) -> Option<ValueType> {
+    match value_type {
+        sys::FcTypeInteger => Some(ValueType::Integer),
+        sys::FcTypeDouble => Some(ValueType::Double),
+        sys::FcTypeString => Some(ValueType::String),
+        sys::FcTypeBool => Some(ValueType::Bool),
+        sys::FcTypeMatrix => Some(ValueType::Matrix),
+        sys::FcTypeCharSet => Some(ValueType::CharSet),
+        sys::FcTypeFTFace => Some(ValueType::FtFace),
+        sys::FcTypeLangSet => Some(ValueType::LangSet),
+        sys::FcTypeRange => Some(ValueType::Range),
+        _ => None,
+    }
+}
+
+impl Pattern<'_> {
+    /// Iterate every value of every element of this pattern, in order, with its binding.
+    ///
+    /// This shows how substitution changed a pattern, e.g. which families the configuration
+    /// appended and how strongly, which helps when debugging configuration files.
+    ///
+    /// Nothing is iterated if the loaded Fontconfig is older than 2.13.1, which added the
+    /// functions this relies on.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add(FC_FAMILY, "sans-serif");
+    /// let font = pat.font_match();
+    /// for value in font.bound_values() {
+    ///     println!("{:?}[{}]: {:?}", value.name, value.index, value.binding);
+    /// }
+    /// ```
+    pub fn bound_values(&self) -> impl Iterator<Item = BoundValue<'_>> + '_ {
+        let mut fc_iter = sys::FcPatternIter {
+            dummy1: ptr::null_mut(),
+            dummy2: ptr::null_mut(),
+        };
+        let mut valid = sys::available::FcPatternIterStart()
+            && unsafe {
+                ffi_dispatch_optional!(FcPatternIterStart, self.pat, &mut fc_iter);
+                ffi_dispatch_optional!(FcPatternIterIsValid, self.pat, &mut fc_iter) == FcTrue
+            };
+        let mut index = 0;
+        iter::from_fn(move || unsafe {
+            while valid {
+                if index < ffi_dispatch_optional!(FcPatternIterValueCount, self.pat, &mut fc_iter) {
+                    let mut value = MaybeUninit::<sys::FcValue>::uninit();
+                    let mut binding = sys::FcValueBindingStrong;
+                    ffi_dispatch_optional!(
+                        FcPatternIterGetValue,
+                        self.pat,
+                        &mut fc_iter,
+                        index,
+                        value.as_mut_ptr(),
+                        &mut binding
+                    )
+                    .to_result()
+                    .ok()?;
+                    let name =
+                        ffi_dispatch_optional!(FcPatternIterGetObject, self.pat, &mut fc_iter);
+                    let bound = BoundValue {
+                        name: CStr::from_ptr(name),
+                        index,
+                        value_type: value_type(value.assume_init()._type),
+                        binding: Binding::from_raw(binding),
+                    };
+                    index += 1;
+                    return Some(bound);
+                }
+                index = 0;
+                // The iterator is left on the last element at the end, so only the result of
+                // advancing tells whether there are more.
+                valid = ffi_dispatch_optional!(FcPatternIterNext, self.pat, &mut fc_iter) == FcTrue;
+            }
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fontconfig, FC_FAMILY, FC_WEIGHT, FC_WEIGHT_BOLD};
+
+    #[test]
+    fn iterate_bindings() {
+        assert!(sys::available::FcPatternIterStart());
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        assert_eq!(pat.bound_values().count(), 0);
+        pat.add(FC_FAMILY, "Foo");
+        pat.add_weak(FC_FAMILY, "Bar");
+        pat.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+
+        let values: Vec<_> = pat
+            .bound_values()
+            .map(|v| (v.name, v.index, v.value_type, v.binding))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (FC_FAMILY, 0, Some(ValueType::String), Binding::Strong),
+                (FC_FAMILY, 1, Some(ValueType::String), Binding::Weak),
+                (FC_WEIGHT, 0, Some(ValueType::Integer), Binding::Strong),
+            ]
+        );
+
+        // The configuration appends families to generic names.
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "sans-serif");
+        pat.substitute();
+        assert!(pat
+            .bound_values()
+            .any(|v| v.name == FC_FAMILY && v.binding != Binding::Strong));
+    }
+}
@@ -0,0 +1,186 @@
+//! Inspecting and removing the caches Fontconfig keeps for font directories.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    cstr_to_path, ffi_dispatch, path_to_cstring, sys, FcFalse, FcTrue, FontSet, Fontconfig,
+};
+
+/// The cache of the fonts in one directory, as written by `fc-cache`.
+///
+/// The cache is unloaded when dropped.
+///
+/// ```
+/// use fontconfig::{DirCache, Fontconfig};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let cache = DirCache::load(&fc, "/usr/share/fonts");
+/// if let Some(cache) = &cache {
+///     println!("{} fonts, {} subdirectories", cache.font_count(), cache.subdirs().len());
+/// }
+/// ```
+pub struct DirCache<'fc> {
+    cache: *mut sys::FcCache,
+    fc: &'fc Fontconfig,
+}
+
+impl<'fc> DirCache<'fc> {
+    /// Load the cache of `dir` from the cache directories of `fc`.
+    ///
+    /// Returns `None` if there is no cache for `dir` that is up to date. The directory is not
+    /// scanned.
+    pub fn load(fc: &'fc Fontconfig, dir: impl AsRef<Path>) -> Option<DirCache<'fc>> {
+        let dir = path_to_cstring(dir.as_ref())?;
+        let cache = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcDirCacheLoad,
+                dir.as_ptr() as *const u8,
+                fc.raw_config(),
+                ptr::null_mut()
+            )
+        };
+        DirCache::from_raw(fc, cache)
+    }
+
+    /// Load the cache of `dir`, scanning the directory and writing a new cache if it has none
+    /// that is up to date, or if `force` is `true`.
+    ///
+    /// Returns `None` if `dir` cannot be scanned. The new cache is written to the first
+    /// writable cache directory of `fc`, if it has one.
+    pub fn read(fc: &'fc Fontconfig, dir: impl AsRef<Path>, force: bool) -> Option<DirCache<'fc>> {
+        let dir = path_to_cstring(dir.as_ref())?;
+        let force = if force { FcTrue } else { FcFalse };
+        let cache = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcDirCacheRead,
+                dir.as_ptr() as *const u8,
+                force,
+                fc.raw_config()
+            )
+        };
+        DirCache::from_raw(fc, cache)
+    }
+
+    /// Returns `true` if the cache directories of `fc` hold a cache for `dir` that is up to
+    /// date.
+    pub fn is_valid(fc: &Fontconfig, dir: impl AsRef<Path>) -> bool {
+        // `FcDirCacheValid` only checks the current configuration, so load the cache through
+        // that of `fc` instead.
+        DirCache::load(fc, dir).is_some()
+    }
+
+    /// Remove the caches of `dir` from every cache directory of `fc`, so that the directory
+    /// is scanned again when next needed.
+    ///
+    /// Returns `false` if a cache file exists but could not be removed.
+    pub fn unlink(fc: &Fontconfig, dir: impl AsRef<Path>) -> bool {
+        path_to_cstring(dir.as_ref()).is_some_and(|dir| unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcDirCacheUnlink,
+                dir.as_ptr() as *const u8,
+                fc.raw_config()
+            ) == FcTrue
+        })
+    }
+
+    fn from_raw(fc: &'fc Fontconfig, cache: *mut sys::FcCache) -> Option<DirCache<'fc>> {
+        if cache.is_null() {
+            None
+        } else {
+            Some(DirCache { cache, fc })
+        }
+    }
+
+    /// The directory this cache is for.
+    pub fn dir(&self) -> PathBuf {
+        unsafe {
+            let dir = ffi_dispatch!(LIB, FcCacheDir, self.cache);
+            cstr_to_path(CStr::from_ptr(dir as *const c_char))
+        }
+    }
+
+    /// The number of fonts in the directory, not counting its subdirectories.
+    pub fn font_count(&self) -> usize {
+        unsafe { ffi_dispatch!(LIB, FcCacheNumFont, self.cache).max(0) as usize }
+    }
+
+    /// The subdirectories of the directory, which have caches of their own.
+    pub fn subdirs(&self) -> Vec<PathBuf> {
+        unsafe {
+            let count = ffi_dispatch!(LIB, FcCacheNumSubdir, self.cache);
+            (0..count)
+                .map(|n| {
+                    let dir = ffi_dispatch!(LIB, FcCacheSubdir, self.cache, n);
+                    cstr_to_path(CStr::from_ptr(dir as *const c_char))
+                })
+                .collect()
+        }
+    }
+
+    /// Copy the patterns of the fonts in the directory.
+    pub fn fonts(&self) -> FontSet<'fc> {
+        unsafe {
+            let set = ffi_dispatch!(LIB, FcCacheCopySet, self.cache);
+            assert!(!set.is_null());
+            FontSet::from_raw(self.fc, set)
+        }
+    }
+}
+
+impl Drop for DirCache<'_> {
+    fn drop(&mut self) {
+        unsafe { ffi_dispatch!(LIB, FcDirCacheUnload, self.cache) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::dejavu_file;
+    use crate::SandboxFonts;
+    use std::{env, fs};
+
+    #[test]
+    fn read_and_unlink() {
+        let tmp = env::temp_dir().join(format!("fontconfig-rs-dir-cache-{}", std::process::id()));
+        let dir = tmp.join("fonts");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::copy(dejavu_file("DejaVu Sans"), dir.join("DejaVuSans.ttf")).unwrap();
+        let fc = SandboxFonts {
+            cache_dir: Some(tmp.join("cache")),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        }
+        .build()
+        .unwrap();
+
+        assert!(DirCache::load(&fc, &dir).is_none());
+        assert!(!DirCache::is_valid(&fc, &dir));
+        let cache = DirCache::read(&fc, &dir, false).unwrap();
+        assert_eq!(cache.dir(), dir);
+        assert_eq!(cache.font_count(), 1);
+        assert_eq!(cache.subdirs(), [dir.join("sub")]);
+        assert_eq!(cache.fonts().iter().count(), 1);
+        drop(cache);
+
+        assert_eq!(DirCache::load(&fc, &dir).unwrap().font_count(), 1);
+        assert!(DirCache::is_valid(&fc, &dir));
+        // The cache is in the cache directory of `fc`, not in those of the current configuration.
+        assert!(!DirCache::is_valid(&Fontconfig::new().unwrap(), &dir));
+        assert!(DirCache::unlink(&fc, &dir));
+        assert!(DirCache::load(&fc, &dir).is_none());
+        assert!(!DirCache::is_valid(&fc, "/nonexistent"));
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+}
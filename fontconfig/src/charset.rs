@@ -0,0 +1,479 @@
+//! Sets of Unicode characters, used to describe the coverage of fonts.
+
+use std::ffi::{CStr, CString};
+use std::ops::RangeInclusive;
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    ffi_dispatch, list_fonts, sort_fonts, sys, FcTrue, Font, Fontconfig, ObjectSet, Pattern,
+};
+use crate::{FC_CHARSET, FC_CHARSET_DONE, FC_CHARSET_MAP_SIZE, FC_FILE, FC_FULLNAME, FC_INDEX};
+
+/// A safe wrapper around fontconfig's `FcCharSet`.
+pub struct CharSet {
+    fcset: *mut sys::FcCharSet,
+}
+
+impl CharSet {
+    /// Create a new, empty `CharSet`.
+    pub fn new(_: &Fontconfig) -> CharSet {
+        CharSet::empty()
+    }
+
+    /// Create an empty set, for constructors and `collect` that do not take a `Fontconfig`
+    /// handle as Fontconfig needs no configuration to hold characters.
+    pub(crate) fn empty() -> CharSet {
+        let fcset = unsafe { ffi_dispatch!(LIB, FcCharSetCreate,) };
+        assert!(!fcset.is_null());
+        CharSet { fcset }
+    }
+
+    /// Create a set of the characters in `text`, e.g. to find fonts that can render all of it.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let chars = CharSet::from_text(&fc, "Grüße");
+    /// assert_eq!(chars.len(), 5);
+    /// assert!(chars.contains('ß'));
+    /// ```
+    pub fn from_text(fc: &Fontconfig, text: &str) -> CharSet {
+        CharSet::from_chars(fc, text.chars())
+    }
+
+    /// Create a set of the characters yielded by `chars`.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let digits = CharSet::from_chars(&fc, '0'..='9');
+    /// assert_eq!(digits.len(), 10);
+    /// ```
+    pub fn from_chars(_: &Fontconfig, chars: impl IntoIterator<Item = char>) -> CharSet {
+        chars.into_iter().collect()
+    }
+
+    /// Wrap an existing `FcCharSet`.
+    ///
+    /// The returned wrapper assumes ownership of the `FcCharSet`.
+    ///
+    /// # Safety
+    ///
+    /// The char set pointer must be valid/non-null.
+    pub unsafe fn from_raw(_: &Fontconfig, raw_set: *mut sys::FcCharSet) -> CharSet {
+        CharSet { fcset: raw_set }
+    }
+
+    /// Copy a char set that is owned elsewhere, such as one held by a pattern.
+    ///
+    /// Fontconfig's own `FcCharSetCopy` only takes a reference, which would let changes to the
+    /// copy show through in the original.
+    pub(crate) unsafe fn copy_raw(raw_set: *const sys::FcCharSet) -> CharSet {
+        let fcset = ffi_dispatch!(LIB, FcCharSetUnion, raw_set, raw_set);
+        assert!(!fcset.is_null());
+        CharSet { fcset }
+    }
+
+    /// Add a character to this set.
+    pub fn add_char(&mut self, c: char) {
+        unsafe {
+            ffi_dispatch!(LIB, FcCharSetAddChar, self.fcset, c as u32);
+        }
+    }
+
+    /// Add every character in `range` to this set.
+    ///
+    /// Fontconfig has no call to add more than one character, but it parses the ranges of a
+    /// char set in a pattern name in one call, so this adds a whole block, such as Cyrillic,
+    /// without crossing into Fontconfig for each character.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut chars = CharSet::new(&fc);
+    /// chars.add_range('\u{0400}'..='\u{04FF}');
+    /// assert_eq!(chars.len(), 256);
+    /// ```
+    pub fn add_range(&mut self, range: RangeInclusive<char>) {
+        let (start, end) = (*range.start() as u32, *range.end() as u32);
+        if start > end {
+            return;
+        }
+        // The range of code points may span the surrogates, which are not characters.
+        let ranges = if start < 0xD800 && end > 0xDFFF {
+            format!("{:x}-d7ff e000-{:x}", start, end)
+        } else {
+            format!("{:x}-{:x}", start, end)
+        };
+        let name = CString::new(format!(":charset={}", ranges)).unwrap();
+        unsafe {
+            let pat = ffi_dispatch!(LIB, FcNameParse, name.as_ptr() as *const u8);
+            assert!(!pat.is_null());
+            let mut chars = ptr::null_mut();
+            if ffi_dispatch!(
+                LIB,
+                FcPatternGetCharSet,
+                pat,
+                FC_CHARSET.as_ptr(),
+                0,
+                &mut chars
+            ) == sys::FcResultMatch
+            {
+                ffi_dispatch!(LIB, FcCharSetMerge, self.fcset, chars, ptr::null_mut());
+            }
+            ffi_dispatch!(LIB, FcPatternDestroy, pat);
+        }
+    }
+
+    /// Add the characters of `other` to this set, returning `true` if any of them were new.
+    pub(crate) fn merge(&mut self, other: &CharSet) -> bool {
+        let mut changed = 0;
+        unsafe { ffi_dispatch!(LIB, FcCharSetMerge, self.fcset, other.fcset, &mut changed) };
+        changed == FcTrue
+    }
+
+    /// Returns `true` if this set contains `c`.
+    pub fn contains(&self, c: char) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcCharSetHasChar, self.fcset, c as u32) == FcTrue }
+    }
+
+    /// Returns the number of characters in this set.
+    pub fn len(&self) -> usize {
+        unsafe { ffi_dispatch!(LIB, FcCharSetCount, self.fcset) as usize }
+    }
+
+    /// Returns `true` if every character in this set is also in `other`.
+    pub fn is_subset(&self, other: &CharSet) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcCharSetIsSubset, self.fcset, other.fcset) == FcTrue }
+    }
+
+    /// Returns the pages of this set in order, each being the first character of a run of 256
+    /// and a bitmap of the characters of the run that are in the set.
+    pub(crate) fn pages(
+        &self,
+    ) -> impl Iterator<Item = (u32, [u32; FC_CHARSET_MAP_SIZE as usize])> + '_ {
+        let mut map = [0; FC_CHARSET_MAP_SIZE as usize];
+        let mut next = 0;
+        let mut base = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcCharSetFirstPage,
+                self.fcset,
+                map.as_mut_ptr(),
+                &mut next
+            )
+        };
+        std::iter::from_fn(move || {
+            if base == FC_CHARSET_DONE {
+                return None;
+            }
+            let page = (base, map);
+            base = unsafe {
+                ffi_dispatch!(
+                    LIB,
+                    FcCharSetNextPage,
+                    self.fcset,
+                    map.as_mut_ptr(),
+                    &mut next
+                )
+            };
+            Some(page)
+        })
+    }
+
+    /// Returns the characters of this set in order.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let set = CharSet::from_text(&fc, "cab");
+    /// assert!(set.chars().eq(['a', 'b', 'c']));
+    /// ```
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.pages().flat_map(|(base, map)| {
+            (0..256).filter_map(move |i| {
+                let bit = map[i as usize / 32] & (1 << (i % 32));
+                if bit == 0 {
+                    None
+                } else {
+                    char::from_u32(base + i)
+                }
+            })
+        })
+    }
+
+    /// Returns `true` if this set contains no characters.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a raw pointer to underlying `FcCharSet`.
+    pub fn as_ptr(&self) -> *const sys::FcCharSet {
+        self.fcset
+    }
+
+    /// Returns an unsafe mutable pointer to the underlying `FcCharSet`.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::FcCharSet {
+        self.fcset
+    }
+}
+
+// SAFETY: A `CharSet` owns its `FcCharSet`, which is only modified through `&mut self`, and the
+// Fontconfig functions that take `&self` only read it. No other owner shares the `FcCharSet`:
+// patterns are only ever given copies, and sets read back from them are copied too, since
+// `FcCharSetCopy` would only add a reference that another thread could modify through.
+unsafe impl Send for CharSet {}
+unsafe impl Sync for CharSet {}
+
+impl Clone for CharSet {
+    fn clone(&self) -> Self {
+        unsafe { CharSet::copy_raw(self.fcset) }
+    }
+}
+
+impl PartialEq for CharSet {
+    fn eq(&self, other: &CharSet) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcCharSetEqual, self.fcset, other.fcset) == FcTrue }
+    }
+}
+
+impl Eq for CharSet {}
+
+/// Collect characters into a set, e.g. those of a string with `text.chars().collect()`.
+impl FromIterator<char> for CharSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> CharSet {
+        let mut set = CharSet::empty();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<char> for CharSet {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.add_char(c);
+        }
+    }
+}
+
+impl std::fmt::Debug for CharSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CharSet").field("len", &self.len()).finish()
+    }
+}
+
+impl Drop for CharSet {
+    fn drop(&mut self) {
+        unsafe { ffi_dispatch!(LIB, FcCharSetDestroy, self.fcset) }
+    }
+}
+
+impl Pattern<'_> {
+    /// Get a copy of the "charset" element of this pattern: the characters the font covers.
+    pub fn char_set(&self) -> Option<CharSet> {
+        let mut fcset = ptr::null_mut();
+        unsafe {
+            if ffi_dispatch!(
+                LIB,
+                FcPatternGetCharSet,
+                self.pat,
+                FC_CHARSET.as_ptr(),
+                0,
+                &mut fcset
+            ) == sys::FcResultMatch
+            {
+                Some(CharSet::copy_raw(fcset))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Add a copy of a char set to the "charset" element of this pattern, e.g. to match fonts
+    /// covering it.
+    pub fn add_char_set(&mut self, char_set: &CharSet) {
+        self.add_char_set_to(FC_CHARSET, char_set);
+    }
+
+    /// Add a copy of a char set to the element `name` of this pattern.
+    pub(crate) fn add_char_set_to(&mut self, name: &CStr, char_set: &CharSet) {
+        // Fontconfig only references the set it is given, so hand it a copy that later changes
+        // to `char_set` cannot reach.
+        let copy = char_set.clone();
+        unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcPatternAddCharSet,
+                self.pat,
+                name.as_ptr(),
+                copy.fcset
+            );
+        }
+    }
+}
+
+impl Fontconfig {
+    /// Find the installed fonts that cover every character in `chars`, in no particular order.
+    ///
+    /// This checks coverage up front, for example to make sure a single font can render all
+    /// the text of a subtitle track or a language pack, rather than relying on fallback.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let chars = CharSet::from_text(&fc, "Grüße, Ελλάδα");
+    /// for font in fc.fonts_covering(&chars) {
+    ///     println!("{}", font.name);
+    /// }
+    /// ```
+    pub fn fonts_covering(&self, chars: &CharSet) -> Vec<Font> {
+        let mut pat = Pattern::new(self);
+        pat.add_char_set(chars);
+        let mut objects = ObjectSet::new(self);
+        for object in [FC_FULLNAME, FC_FILE, FC_INDEX] {
+            objects.add(object);
+        }
+        // Listing matches fonts whose char set contains the one in the pattern.
+        list_fonts(&pat, Some(&objects))
+            .iter()
+            .filter_map(|pattern| Font::from_pattern(&pattern))
+            .collect()
+    }
+
+    /// Find the installed fonts that have a glyph for `c`, best first, e.g. to pick a fallback
+    /// font for a character the primary font of a terminal or editor lacks.
+    ///
+    /// The fonts are ranked as Fontconfig ranks them for a pattern asking for `c` after the
+    /// configuration has been applied, so the default families and languages decide between
+    /// fonts covering it.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// if let Some(font) = fc.fonts_for_char('∰').first() {
+    ///     println!("{}", font.name);
+    /// }
+    /// ```
+    pub fn fonts_for_char(&self, c: char) -> Vec<Font> {
+        let mut chars = CharSet::empty();
+        chars.add_char(c);
+        let mut pat = Pattern::new(self);
+        pat.add_char_set(&chars);
+        pat.substitute();
+        sort_fonts(&pat, false)
+            .iter()
+            .filter(|pattern| pattern.char_set().is_some_and(|set| set.contains(c)))
+            .filter_map(|pattern| Font::from_pattern(&pattern))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_copy() {
+        let fc = Fontconfig::new().unwrap();
+        let mut set = CharSet::new(&fc);
+        assert!(set.is_empty());
+        set.add_char('a');
+        set.add_char('ß');
+        set.add_char('a');
+        assert_eq!(set.len(), 2);
+        assert!(set.contains('ß'));
+        assert!(!set.contains('b'));
+
+        let mut copy = set.clone();
+        assert_eq!(copy, set);
+        copy.add_char('b');
+        assert!(!set.contains('b'));
+        assert!(set.is_subset(&copy));
+        assert!(!copy.is_subset(&set));
+
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_char_set(&set);
+        assert_eq!(pattern.char_set().as_ref(), Some(&set));
+        set.add_char('c');
+        assert!(!pattern.char_set().unwrap().contains('c'));
+    }
+
+    #[test]
+    fn collect_chars() {
+        let fc = Fontconfig::new().unwrap();
+        let mut set: CharSet = "abba".chars().collect();
+        assert_eq!(set.len(), 2);
+        assert_eq!(CharSet::from_chars(&fc, ['b', 'a']), set);
+        set.extend(['c', 'ж', 'a']);
+        assert_eq!(set.len(), 4);
+        assert!(set.contains('ж'));
+        assert!(set.chars().eq(['a', 'b', 'c', 'ж']));
+        set.add_char(' ');
+        assert_eq!(CharSet::from_text(&fc, "cabж cab"), set);
+        assert!(CharSet::from_text(&fc, "").is_empty());
+    }
+
+    #[test]
+    fn add_ranges() {
+        let mut set = CharSet::empty();
+        set.add_range('a'..='z');
+        set.add_range('\u{0400}'..='\u{04FF}');
+        set.add_range('z'..='a');
+        assert_eq!(set.len(), 26 + 256);
+        let one_by_one: CharSet = ('a'..='z').chain('\u{0400}'..='\u{04FF}').collect();
+        assert_eq!(set, one_by_one);
+
+        // The surrogates are skipped.
+        let mut set = CharSet::empty();
+        set.add_range('\u{D7FF}'..='\u{E000}');
+        assert_eq!(set.len(), 2);
+        let mut set = CharSet::empty();
+        set.add_range('\0'..=char::MAX);
+        assert_eq!(set.len(), 0x110000 - 0x800);
+    }
+
+    #[test]
+    fn find_covering_fonts() {
+        let fc = Fontconfig::new().unwrap();
+        let mut chars = CharSet::new(&fc);
+        chars.add_char('a');
+        chars.add_char('Ω');
+        let fonts = fc.fonts_covering(&chars);
+        assert!(fonts.iter().any(|font| font.name == "DejaVu Sans"));
+        for font in &fonts {
+            let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+            assert!(chars.is_subset(&pattern.char_set().unwrap()));
+        }
+
+        chars.add_char('\u{10FFFD}');
+        assert!(fc.fonts_covering(&chars).is_empty());
+    }
+
+    #[test]
+    fn fonts_for_char() {
+        let fc = Fontconfig::new().unwrap();
+        let fonts = fc.fonts_for_char('∰');
+        // The configured default family comes first.
+        assert_eq!(fonts[0].name, "DejaVu Sans");
+        let file = |font: &Font| font.path.file_name().unwrap().to_owned();
+        assert!(fonts
+            .iter()
+            .any(|font| file(font) == "DejaVuMathTeXGyre.ttf"));
+        assert!(!fonts.iter().any(|font| file(font) == "DejaVuSansMono.ttf"));
+        for font in &fonts {
+            let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+            assert!(pattern.char_set().unwrap().contains('∰'));
+        }
+        assert!(fc.fonts_for_char('\u{10FFFD}').is_empty());
+    }
+}
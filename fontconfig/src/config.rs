@@ -0,0 +1,456 @@
+//! Changing the configuration of a handle at runtime.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use crate::reload::next_generation;
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    cstr_to_path, ffi_dispatch, ffi_dispatch_optional, path_to_cstring, sys, Error, FcBool, FcTrue,
+    Fontconfig, Generation, Result,
+};
+
+impl Fontconfig {
+    /// Add the font file at `path` to the fonts of this handle, such as a font shipped inside
+    /// the application bundle.
+    ///
+    /// Application fonts are matched like installed fonts, but are not cached. A handle sharing
+    /// the current configuration is given one of its own first, as described under
+    /// [Changing the configuration](Fontconfig#changing-the-configuration). Returns
+    /// [`Error::ConfigFailed`] if the file cannot be read or contains no fonts, or if the
+    /// configuration cannot be loaded.
+    ///
+    /// ```no_run
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let mut fc = Fontconfig::new().unwrap();
+    /// fc.app_font_add_file("/opt/myapp/fonts/Brand-Regular.otf").unwrap();
+    /// ```
+    pub fn app_font_add_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path_to_cstring(path.as_ref()).ok_or(Error::ConfigFailed)?;
+        let config = self.private_config()?;
+        let added = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcConfigAppFontAddFile,
+                config,
+                path.as_ptr() as *const u8
+            )
+        };
+        if added == FcTrue {
+            Ok(())
+        } else {
+            Err(Error::ConfigFailed)
+        }
+    }
+
+    /// Add the fonts in `dir` and its subdirectories to the fonts of this handle.
+    ///
+    /// Like [`Fontconfig::app_font_add_file`], this first gives a handle sharing the current
+    /// configuration one of its own. Returns [`Error::ConfigFailed`] if `dir` is not a
+    /// directory. See [`Fontconfig::add_app_fonts`] to also decide which fonts win when the
+    /// directory holds families that are installed as well.
+    pub fn app_font_add_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        // Fontconfig skips directories that do not exist without failing.
+        if !dir.is_dir() {
+            return Err(Error::ConfigFailed);
+        }
+        let dir = path_to_cstring(dir).ok_or(Error::ConfigFailed)?;
+        let config = self.private_config()?;
+        let added = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcConfigAppFontAddDir,
+                config,
+                dir.as_ptr() as *const u8
+            )
+        };
+        if added == FcTrue {
+            Ok(())
+        } else {
+            Err(Error::ConfigFailed)
+        }
+    }
+
+    /// Remove every font added with [`Fontconfig::app_font_add_file`] or
+    /// [`Fontconfig::app_font_add_dir`] from this handle.
+    ///
+    /// A handle that still shares the current configuration has added no fonts, so this leaves
+    /// the application fonts of other code in place.
+    pub fn app_font_clear(&mut self) {
+        if self.owns_config {
+            unsafe { ffi_dispatch!(LIB, FcConfigAppFontClear, self.config) }
+        }
+    }
+
+    /// Load the configuration file at `path` on top of the configuration of this handle, e.g.
+    /// to add font aliases for a project without setting `FONTCONFIG_FILE`.
+    ///
+    /// The file is read like one included with `<include>`, so a directory loads the `.conf`
+    /// files in it. Font directories and font selection rules it adds take effect straight
+    /// away, as the fonts are scanned again. Like the other methods changing the configuration,
+    /// this first gives a handle sharing the current configuration one of its own.
+    ///
+    /// If `complain` is `false` a file that does not exist is skipped without error, and with
+    /// `true` it fails and Fontconfig prints a warning. Returns [`Error::ConfigFailed`] if the
+    /// file cannot be loaded.
+    ///
+    /// ```no_run
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let mut fc = Fontconfig::new().unwrap();
+    /// fc.load_config_file("fonts.conf", true).unwrap();
+    /// ```
+    pub fn load_config_file(&mut self, path: impl AsRef<Path>, complain: bool) -> Result<()> {
+        let path = path_to_cstring(path.as_ref()).ok_or(Error::ConfigFailed)?;
+        let config = self.private_config()?;
+        unsafe {
+            let loaded = ffi_dispatch!(
+                LIB,
+                FcConfigParseAndLoad,
+                config,
+                path.as_ptr() as *const u8,
+                complain as FcBool
+            );
+            rebuild_after(config, loaded)
+        }
+    }
+
+    /// Load a configuration from the XML in `xml` on top of the configuration of this handle,
+    /// e.g. to embed font aliases or rejection rules in the application.
+    ///
+    /// This behaves like [`Fontconfig::load_config_file`], without writing the XML to a file
+    /// first. Returns [`Error::ConfigFailed`] if the XML
+    /// cannot be loaded or contains a NUL character, or if the loaded Fontconfig is too old to
+    /// load a configuration from memory.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let mut fc = Fontconfig::new().unwrap();
+    /// fc.load_config_str(
+    ///     r#"<fontconfig>
+    ///          <alias>
+    ///            <family>Brand Sans</family>
+    ///            <prefer><family>DejaVu Sans</family></prefer>
+    ///          </alias>
+    ///        </fontconfig>"#,
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn load_config_str(&mut self, xml: &str) -> Result<()> {
+        if !sys::available::FcConfigParseAndLoadFromMemory() {
+            return Err(Error::ConfigFailed);
+        }
+        let xml = CString::new(xml).map_err(|_| Error::ConfigFailed)?;
+        let config = self.private_config()?;
+        unsafe {
+            let loaded = ffi_dispatch_optional!(
+                FcConfigParseAndLoadFromMemory,
+                config,
+                xml.as_ptr() as *const u8,
+                FcTrue
+            );
+            rebuild_after(config, loaded)
+        }
+    }
+
+    /// Scan the font directories of this handle again, picking up fonts installed or removed
+    /// since they were last scanned.
+    ///
+    /// Application fonts are kept. Only a configuration that belongs to this handle is scanned
+    /// again: a handle sharing the current configuration is instead given a configuration of its
+    /// own, whose fonts are scanned as it is loaded, and the current configuration is left to
+    /// Fontconfig's own rescans. Returns [`Error::ConfigFailed`] if the fonts cannot be scanned.
+    pub fn build_fonts(&mut self) -> Result<()> {
+        let owned = self.owns_config;
+        let config = self.private_config()?;
+        if owned {
+            unsafe { rebuild_after(config, FcTrue) }
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Use the configuration of the system rooted at `root`, such as a chroot or the image of
+    /// another target, instead of the configuration of this handle.
+    ///
+    /// The configuration files, font directories and cache directories are all looked up
+    /// within `root`, so that caches can be built for a system without running inside it. The
+    /// files of the fonts found are named relative to `root`. The fonts are scanned straight
+    /// away, and the previous configuration is kept if this fails.
+    ///
+    /// Returns [`Error::ConfigFailed`] if `root` is not a directory, its configuration cannot
+    /// be loaded, or the loaded Fontconfig is too old to support a root.
+    ///
+    /// ```no_run
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let mut fc = Fontconfig::new().unwrap();
+    /// fc.set_sysroot("/srv/images/target").unwrap();
+    /// assert!(fc.sysroot().is_some());
+    /// ```
+    pub fn set_sysroot(&mut self, root: impl AsRef<Path>) -> Result<()> {
+        let root = root.as_ref();
+        if !sys::available::FcConfigSetSysRoot() || !root.is_dir() {
+            return Err(Error::ConfigFailed);
+        }
+        let root = path_to_cstring(root).ok_or(Error::ConfigFailed)?;
+        unsafe {
+            let config = ffi_dispatch!(LIB, FcConfigCreate,);
+            if config.is_null() {
+                return Err(Error::OutOfMemory);
+            }
+            // The handle releases the configuration if it cannot be loaded.
+            let fc = Fontconfig {
+                config,
+                owns_config: true,
+                generation: next_generation(),
+            };
+            ffi_dispatch_optional!(FcConfigSetSysRoot, config, root.as_ptr() as *const u8);
+            let loaded = ffi_dispatch!(LIB, FcConfigParseAndLoad, config, ptr::null(), FcTrue);
+            rebuild_after(config, loaded)?;
+            *self = fc;
+        }
+        Ok(())
+    }
+
+    /// Returns the root that the configuration of this handle was loaded from, if it is not
+    /// the root of the file system.
+    pub fn sysroot(&self) -> Option<PathBuf> {
+        if !sys::available::FcConfigGetSysRoot() {
+            return None;
+        }
+        unsafe {
+            let root = ffi_dispatch_optional!(FcConfigGetSysRoot, self.raw_config());
+            if root.is_null() {
+                None
+            } else {
+                Some(cstr_to_path(CStr::from_ptr(root as *const c_char)))
+            }
+        }
+    }
+
+    /// The configuration of this handle, resolving the current configuration if it has none.
+    pub(crate) fn raw_config(&self) -> *mut sys::FcConfig {
+        if self.config.is_null() {
+            unsafe { ffi_dispatch!(LIB, FcConfigGetCurrent,) }
+        } else {
+            self.config
+        }
+    }
+
+    /// The configuration of this handle, for changing it.
+    ///
+    /// A handle sharing the current configuration is first given a configuration of its own,
+    /// loaded from the default configuration files, so that the change only affects this handle.
+    /// The handle starts a new generation, as its fonts may change.
+    pub(crate) fn private_config(&mut self) -> Result<*mut sys::FcConfig> {
+        if !self.owns_config {
+            let config = unsafe { ffi_dispatch!(LIB, FcInitLoadConfigAndFonts,) };
+            if config.is_null() {
+                return Err(Error::ConfigFailed);
+            }
+            // Release the reference to the shared configuration, if any.
+            *self = Fontconfig {
+                config,
+                owns_config: true,
+                generation: Generation::default(),
+            };
+        }
+        self.generation = next_generation();
+        Ok(self.config)
+    }
+}
+
+/// Scan the fonts of `config` again if loading more of it succeeded.
+unsafe fn rebuild_after(config: *mut sys::FcConfig, loaded: FcBool) -> Result<()> {
+    if loaded == FcTrue && ffi_dispatch!(LIB, FcConfigBuildFonts, config) == FcTrue {
+        Ok(())
+    } else {
+        Err(Error::ConfigFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dejavu_dir, dejavu_file};
+    use crate::{list_fonts, Pattern, SandboxFonts, FC_FAMILY};
+
+    /// A handle without any fonts.
+    fn empty(cache: &str) -> Fontconfig {
+        SandboxFonts {
+            cache_dir: Some(std::env::temp_dir().join(cache)),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        }
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn app_fonts() {
+        let mut fc = empty("fontconfig-rs-config-test");
+        let count = |fc: &Fontconfig| list_fonts(&Pattern::new(fc), None).iter().count();
+        assert_eq!(count(&fc), 0);
+
+        fc.app_font_add_file(dejavu_file("DejaVu Sans")).unwrap();
+        assert_eq!(count(&fc), 1);
+        assert!(fc.app_font_add_file("/nonexistent.ttf").is_err());
+
+        fc.app_font_add_dir(dejavu_dir()).unwrap();
+        assert!(count(&fc) > 1);
+        assert!(fc.app_font_add_dir("/nonexistent").is_err());
+
+        fc.app_font_clear();
+        assert_eq!(count(&fc), 0);
+    }
+
+    #[test]
+    fn app_fonts_of_shared_handle() {
+        let mut fc = Fontconfig::new().unwrap();
+        let other = Fontconfig::new().unwrap();
+        let font = other.find("DejaVu Sans", None).unwrap();
+        let count = |fc: &Fontconfig| unsafe {
+            let set = ffi_dispatch!(
+                LIB,
+                FcConfigGetFonts,
+                fc.raw_config(),
+                sys::FcSetApplication
+            );
+            if set.is_null() {
+                0
+            } else {
+                (*set).nfont
+            }
+        };
+        let before = count(&other);
+
+        fc.app_font_add_file(&font.path).unwrap();
+        assert_ne!(fc.raw_config(), other.raw_config());
+        assert_eq!(count(&fc), 1);
+        assert_eq!(count(&other), before);
+
+        fc.app_font_clear();
+        assert_eq!(count(&fc), 0);
+    }
+
+    #[test]
+    fn empty_config() {
+        let mut fc = Fontconfig::empty().unwrap();
+        let count = |fc: &Fontconfig| list_fonts(&Pattern::new(fc), None).iter().count();
+        assert_eq!(count(&fc), 0);
+        match fc.ensure_fonts() {
+            Err(Error::NoFonts(err)) => assert!(err.config_files.is_empty()),
+            other => panic!("unexpected {:?}", other),
+        }
+        fc.app_font_add_file(dejavu_file("DejaVu Sans")).unwrap();
+        fc.build_fonts().unwrap();
+        assert_eq!(count(&fc), 1);
+    }
+
+    #[test]
+    fn load_file() {
+        let mut fc = empty("fontconfig-rs-config-file-test");
+        let path =
+            std::env::temp_dir().join(format!("fontconfig-rs-config-{}.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            format!(
+                "<fontconfig><dir>{}</dir><alias><family>Project Serif</family>\
+                 <prefer><family>DejaVu Serif</family></prefer></alias></fontconfig>",
+                dejavu_dir().display()
+            ),
+        )
+        .unwrap();
+        fc.load_config_file(&path, true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        {
+            let mut pat = Pattern::new(&fc);
+            pat.add(FC_FAMILY, "Project Serif");
+            assert_eq!(pat.font_match().name(), Some("DejaVu Serif"));
+        }
+
+        // Without `complain` a missing file is skipped; with it Fontconfig would print a warning.
+        fc.load_config_file("/nonexistent.conf", false).unwrap();
+    }
+
+    #[test]
+    fn load_str() {
+        let mut fc = empty("fontconfig-rs-config-str-test");
+        fc.load_config_str(&format!(
+            "<fontconfig><dir>{}</dir><selectfont><rejectfont><glob>*/DejaVuSans.ttf</glob>\
+             </rejectfont></selectfont></fontconfig>",
+            dejavu_dir().display()
+        ))
+        .unwrap();
+        let fonts = list_fonts(&Pattern::new(&fc), None);
+        assert!(fonts.iter().count() > 0);
+        assert!(fonts
+            .iter()
+            .all(|font| !font.filename().unwrap().ends_with("/DejaVuSans.ttf")));
+        drop(fonts);
+
+        assert!(fc.load_config_str("<fontconfig>\0</fontconfig>").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn sysroot() {
+        let root = std::env::temp_dir().join(format!("fontconfig-rs-root-{}", std::process::id()));
+        let fonts = root.join("fonts");
+        std::fs::create_dir_all(root.join("etc/fonts")).unwrap();
+        std::fs::create_dir_all(&fonts).unwrap();
+        std::fs::copy(dejavu_file("DejaVu Serif"), fonts.join("Serif.ttf")).unwrap();
+        // Fontconfig stores the files of a root relative to it, and some releases, such as
+        // 2.14.1, then query the font by that relative name. Listing the directory by its host
+        // path, and linking that path within the root, finds the font either way.
+        let linked = root.join(fonts.strip_prefix("/").unwrap());
+        std::fs::create_dir_all(linked.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&fonts, &linked).unwrap();
+        std::fs::write(
+            root.join("etc/fonts/fonts.conf"),
+            format!(
+                "<fontconfig><dir>{}</dir><cachedir>/cache</cachedir></fontconfig>",
+                fonts.display()
+            ),
+        )
+        .unwrap();
+
+        let mut fc = Fontconfig::new().unwrap();
+        assert_eq!(fc.sysroot(), None);
+        assert!(fc.set_sysroot(root.join("nonexistent")).is_err());
+        fc.set_sysroot(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(fc.sysroot(), Some(root.clone()));
+        // The configuration file, font directory and cache directory of the root are used,
+        // rather than those of the host.
+        fc.ensure_fonts().unwrap();
+        {
+            let mut pat = Pattern::new(&fc);
+            pat.add(FC_FAMILY, "DejaVu Serif");
+            let font = pat.font_match();
+            assert_eq!(font.name(), Some("DejaVu Serif"));
+            // The file is named relative to the root.
+            let file = font.file_path().unwrap();
+            let file = root.join(file.strip_prefix("/").unwrap());
+            assert_eq!(
+                file.canonicalize().unwrap(),
+                root.join("fonts/Serif.ttf").canonicalize().unwrap()
+            );
+        }
+        assert_eq!(list_fonts(&Pattern::new(&fc), None).iter().count(), 1);
+        assert!(root.join("cache").is_dir());
+
+        drop(fc);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+//! Finding the languages that no installed font supports.
+
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, list_fonts, sys, Font, Fontconfig, LangSet, ObjectSet, Pattern};
+use crate::{FC_CHARSET, FC_FILE, FC_FULLNAME, FC_INDEX};
+
+/// The most partial matches reported for a language.
+const MAX_CLOSEST: usize = 3;
+
+/// A language that no installed font fully supports.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LangGap {
+    /// The language, as it was given.
+    pub lang: String,
+    /// The number of characters in the orthography of the language.
+    pub required: usize,
+    /// The fonts covering the most of the orthography, best first, at most three.
+    ///
+    /// This is empty if no installed font covers any of it.
+    pub closest: Vec<PartialCoverage>,
+}
+
+/// A font covering part of the orthography of a language.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialCoverage {
+    /// The font.
+    pub font: Font,
+    /// The number of characters of the orthography the font does not cover.
+    pub missing: usize,
+}
+
+impl Fontconfig {
+    /// Report which of `langs` no installed font supports on its own, with the fonts that come
+    /// closest, for example to offer installing language support.
+    ///
+    /// A font supports a language if it covers every character of the orthography Fontconfig
+    /// has for it, see [`LangSet::charset`]. Languages Fontconfig has no orthography for are
+    /// left out, as their support cannot be judged. The gaps are in the order of `langs`.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// for gap in fc.language_gaps(&["en", "ja", "km"]) {
+    ///     println!("no font for {}", gap.lang);
+    ///     for partial in &gap.closest {
+    ///         println!("  {} lacks {} characters", partial.font.name, partial.missing);
+    ///     }
+    /// }
+    /// ```
+    pub fn language_gaps(&self, langs: &[&str]) -> Vec<LangGap> {
+        let mut objects = ObjectSet::new(self);
+        for object in [FC_FULLNAME, FC_FILE, FC_INDEX, FC_CHARSET] {
+            objects.add(object);
+        }
+        // The char sets are borrowed from the listed patterns, which live as long as `listed`.
+        let listed = list_fonts(&Pattern::new(self), Some(&objects));
+        let fonts: Vec<_> = listed
+            .iter()
+            .filter_map(|pattern| {
+                let chars = char_set_ptr(&pattern)?;
+                Some((Font::from_pattern(&pattern)?, chars))
+            })
+            .collect();
+
+        let mut gaps = Vec::new();
+        for &lang in langs {
+            let Some(orthography) = LangSet::charset(self, lang) else {
+                continue;
+            };
+            let mut coverage: Vec<_> = fonts
+                .iter()
+                .map(|(font, chars)| {
+                    let missing = unsafe {
+                        ffi_dispatch!(LIB, FcCharSetSubtractCount, orthography.as_ptr(), *chars)
+                    };
+                    (font, missing as usize)
+                })
+                .collect();
+            if coverage.iter().any(|&(_, missing)| missing == 0) {
+                continue;
+            }
+            let required = orthography.len();
+            coverage.retain(|&(_, missing)| missing < required);
+            coverage.sort_by_key(|&(_, missing)| missing);
+            gaps.push(LangGap {
+                lang: lang.to_owned(),
+                required,
+                closest: coverage
+                    .into_iter()
+                    .take(MAX_CLOSEST)
+                    .map(|(font, missing)| PartialCoverage {
+                        font: font.clone(),
+                        missing,
+                    })
+                    .collect(),
+            });
+        }
+        gaps
+    }
+}
+
+/// Returns the char set of a font pattern without copying it.
+///
+/// The char set belongs to the pattern, so it must not outlive it.
+fn char_set_ptr(pattern: &Pattern) -> Option<*const sys::FcCharSet> {
+    let mut chars = ptr::null_mut();
+    let result = unsafe {
+        ffi_dispatch!(
+            LIB,
+            FcPatternGetCharSet,
+            pattern.pat,
+            FC_CHARSET.as_ptr(),
+            0,
+            &mut chars
+        )
+    };
+    (result == sys::FcResultMatch).then_some(chars as *const _)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_gaps() {
+        let fc = Fontconfig::new().unwrap();
+        // DejaVu covers Latin and Cyrillic but no Japanese.
+        let gaps = fc.language_gaps(&["en", "ru", "ja", "xx-unknown"]);
+        assert_eq!(gaps.len(), 1);
+        let ja = &gaps[0];
+        assert_eq!(ja.lang, "ja");
+        assert!(ja.required > 1000);
+        assert!(ja.closest.len() <= MAX_CLOSEST);
+        assert!(ja
+            .closest
+            .windows(2)
+            .all(|pair| pair[0].missing <= pair[1].missing));
+        assert!(ja.closest.iter().all(|p| p.missing < ja.required));
+    }
+}
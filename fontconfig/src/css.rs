@@ -0,0 +1,441 @@
+//! Parsing of the CSS `font` shorthand property and `unicode-range` descriptor.
+
+use std::ffi::CString;
+
+use crate::{CharSet, Fontconfig, Pattern, Weight};
+use crate::{
+    FC_FAMILY, FC_FONT_FEATURES, FC_PIXEL_SIZE, FC_SLANT, FC_SLANT_ITALIC, FC_SLANT_OBLIQUE,
+    FC_WEIGHT, FC_WIDTH, FC_WIDTH_CONDENSED, FC_WIDTH_EXPANDED, FC_WIDTH_EXTRACONDENSED,
+    FC_WIDTH_EXTRAEXPANDED, FC_WIDTH_SEMICONDENSED, FC_WIDTH_SEMIEXPANDED, FC_WIDTH_ULTRACONDENSED,
+    FC_WIDTH_ULTRAEXPANDED,
+};
+
+/// The computed size of the `medium` keyword, which relative sizes are resolved against.
+const MEDIUM_PX: f64 = 16.0;
+
+impl<'fc> Pattern<'fc> {
+    /// Create a pattern from the value of a CSS `font` shorthand property, such as
+    /// `italic bold 14px/1.2 'Fira Sans', sans-serif`.
+    ///
+    /// The style, weight, stretch and `small-caps` variant are mapped to the corresponding
+    /// Fontconfig elements, and the size is converted to CSS pixels and stored as the pixel size.
+    /// Relative sizes (`em`, `rem`, `%`, `larger` and `smaller`) are resolved against the
+    /// default `medium` size of 16px, and `bolder` and `lighter` against the normal weight. The
+    /// line height is accepted but ignored. The weight is left out if the loaded Fontconfig is too
+    /// old to convert it.
+    ///
+    /// Returns `None` if the value is not a valid `font` shorthand. System font keywords such as
+    /// `caption` are not supported.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let pat = Pattern::from_css(&fc, "italic bold 14px/1.2 'DejaVu Sans', sans-serif").unwrap();
+    /// assert_eq!(pat.get_string(FC_FAMILY), Some("DejaVu Sans"));
+    /// let font = pat.font_match();
+    /// ```
+    pub fn from_css(fc: &'fc Fontconfig, font: &str) -> Option<Pattern<'fc>> {
+        let font = CssFont::parse(font)?;
+
+        let mut pat = Pattern::new(fc);
+        for family in &font.families {
+            pat.add_string(FC_FAMILY, &CString::new(family.as_str()).ok()?);
+        }
+        if let Some(slant) = font.slant {
+            pat.add_integer(FC_SLANT, slant);
+        }
+        if let Some(Weight(weight)) = font.weight.and_then(Weight::from_opentype) {
+            pat.add_integer(FC_WEIGHT, weight.round() as i32);
+        }
+        if let Some(width) = font.width {
+            pat.add_integer(FC_WIDTH, width);
+        }
+        if font.small_caps {
+            pat.add_string(FC_FONT_FEATURES, c"smcp");
+        }
+        pat.add_double(FC_PIXEL_SIZE, font.pixel_size);
+        Some(pat)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct CssFont {
+    families: Vec<String>,
+    slant: Option<i32>,
+    /// The weight on the CSS (OpenType) scale.
+    weight: Option<f64>,
+    width: Option<i32>,
+    small_caps: bool,
+    pixel_size: f64,
+}
+
+impl CssFont {
+    fn parse(s: &str) -> Option<CssFont> {
+        let mut font = CssFont::default();
+        let mut rest = s.trim_start();
+
+        // Style, variant, weight and stretch may appear in any order before the size.
+        let size = loop {
+            let (token, after) = next_token(rest)?;
+            rest = after;
+            match token {
+                "normal" => {}
+                "italic" => font.slant = Some(FC_SLANT_ITALIC),
+                "oblique" => {
+                    font.slant = Some(FC_SLANT_OBLIQUE);
+                    // An optional angle may follow, which Fontconfig has no way to express.
+                    if let Some((angle, after)) = next_token(rest) {
+                        if is_angle(angle) {
+                            rest = after;
+                        }
+                    }
+                }
+                "small-caps" => font.small_caps = true,
+                "bold" | "bolder" => font.weight = Some(700.0),
+                "lighter" => font.weight = Some(100.0),
+                _ => {
+                    if let Some(width) = parse_stretch(token) {
+                        font.width = Some(width);
+                    } else if let Some(weight) = parse_weight(token) {
+                        font.weight = Some(weight);
+                    } else {
+                        break token;
+                    }
+                }
+            }
+        };
+
+        // The line height may be attached to the size or separated from it by whitespace.
+        let (size, line_height) = match size.split_once('/') {
+            Some((size, line_height)) => (size, Some(line_height)),
+            None => (size, None),
+        };
+        font.pixel_size = parse_size(size)?;
+        match line_height {
+            Some("") => rest = next_token(rest)?.1,
+            Some(_) => {}
+            None => {
+                if let Some(after) = rest.strip_prefix('/') {
+                    let after = after.trim_start();
+                    rest = match after.split_once(char::is_whitespace) {
+                        Some((_, after)) => after,
+                        None => "",
+                    };
+                    if after.is_empty() {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        font.families = parse_families(rest)?;
+        Some(font)
+    }
+}
+
+/// Split the next whitespace-delimited token from `s`.
+fn next_token(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if s.is_empty() || s.starts_with(['"', '\'']) {
+        return None;
+    }
+    match s.split_once(char::is_whitespace) {
+        Some((token, rest)) => Some((token, rest.trim_start())),
+        None => Some((s, "")),
+    }
+}
+
+fn is_angle(token: &str) -> bool {
+    ["deg", "grad", "rad", "turn"].iter().any(|unit| {
+        token
+            .strip_suffix(unit)
+            .is_some_and(|n| n.parse::<f64>().is_ok())
+    })
+}
+
+fn parse_weight(token: &str) -> Option<f64> {
+    let weight: f64 = token.parse().ok()?;
+    (1.0..=1000.0).contains(&weight).then_some(weight)
+}
+
+fn parse_stretch(token: &str) -> Option<i32> {
+    match token {
+        "ultra-condensed" => Some(FC_WIDTH_ULTRACONDENSED),
+        "extra-condensed" => Some(FC_WIDTH_EXTRACONDENSED),
+        "condensed" => Some(FC_WIDTH_CONDENSED),
+        "semi-condensed" => Some(FC_WIDTH_SEMICONDENSED),
+        "semi-expanded" => Some(FC_WIDTH_SEMIEXPANDED),
+        "expanded" => Some(FC_WIDTH_EXPANDED),
+        "extra-expanded" => Some(FC_WIDTH_EXTRAEXPANDED),
+        "ultra-expanded" => Some(FC_WIDTH_ULTRAEXPANDED),
+        _ => None,
+    }
+}
+
+/// Parse a font size, returning it in CSS pixels.
+fn parse_size(token: &str) -> Option<f64> {
+    let keyword = match token {
+        "xx-small" => Some(9.0),
+        "x-small" => Some(10.0),
+        "small" => Some(13.0),
+        "medium" => Some(MEDIUM_PX),
+        "large" => Some(18.0),
+        "x-large" => Some(24.0),
+        "xx-large" => Some(32.0),
+        "xxx-large" => Some(48.0),
+        "larger" => Some(MEDIUM_PX * 1.2),
+        "smaller" => Some(MEDIUM_PX / 1.2),
+        _ => None,
+    };
+    if keyword.is_some() {
+        return keyword;
+    }
+
+    let split = token
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '+' || c == '-'))
+        .unwrap_or(token.len());
+    let (value, unit) = token.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let px_per_unit = match unit.to_ascii_lowercase().as_str() {
+        "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "em" | "rem" => MEDIUM_PX,
+        "%" => MEDIUM_PX / 100.0,
+        "" if value == 0.0 => 0.0,
+        _ => return None,
+    };
+    let px = value * px_per_unit;
+    (px >= 0.0).then_some(px)
+}
+
+/// Parse a comma-separated list of quoted or unquoted family names.
+fn parse_families(s: &str) -> Option<Vec<String>> {
+    let mut families = Vec::new();
+    let mut chars = s.trim().chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let family = match chars.peek()? {
+            &quote @ ('"' | '\'') => {
+                chars.next();
+                let mut family = String::new();
+                loop {
+                    match chars.next()? {
+                        c if c == quote => break,
+                        '\\' => family.push(chars.next()?),
+                        c => family.push(c),
+                    }
+                }
+                family
+            }
+            _ => {
+                let mut family = String::new();
+                while let Some(c) = chars.next_if(|&c| c != ',') {
+                    family.push(c);
+                }
+                let family = family.split_whitespace().collect::<Vec<_>>().join(" ");
+                generic_family(&family).unwrap_or(&family).to_string()
+            }
+        };
+        if family.is_empty() {
+            return None;
+        }
+        families.push(family);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => {}
+            None => return Some(families),
+            Some(_) => return None,
+        }
+    }
+}
+
+impl CharSet {
+    /// Create a set from the value of a CSS `unicode-range` descriptor of `@font-face`, such as
+    /// `U+0000-00FF, U+0131, U+2000-206F`.
+    ///
+    /// Each comma-separated item is a code point, a range of code points, or a code point with
+    /// trailing `?` wildcards such as `U+4??`, which stands for U+400 to U+4FF. Ends beyond
+    /// U+10FFFF are clamped to it, and surrogates are left out as they are not characters.
+    ///
+    /// Returns `None` if an item is not valid, including a range that ends before it starts.
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let latin = CharSet::from_unicode_range(&fc, "U+0000-00FF, U+0131, U+2000-206F").unwrap();
+    /// assert!(latin.contains('é'));
+    /// assert!(latin.contains('ı'));
+    /// assert!(!latin.contains('Ā'));
+    /// ```
+    pub fn from_unicode_range(fc: &Fontconfig, value: &str) -> Option<CharSet> {
+        let mut set = CharSet::new(fc);
+        for item in value.split(',') {
+            let (start, end) = parse_unicode_range(item.trim())?;
+            // Move ends that are surrogates to the nearest character within the range.
+            let start = if (0xD800..=0xDFFF).contains(&start) {
+                0xE000
+            } else {
+                start
+            };
+            let end = if (0xD800..=0xDFFF).contains(&end) {
+                0xD7FF
+            } else {
+                end
+            };
+            if let (Some(start), Some(end)) = (char::from_u32(start), char::from_u32(end)) {
+                set.add_range(start..=end);
+            }
+        }
+        Some(set)
+    }
+}
+
+/// Parse one item of a `unicode-range`, returning its first and last code points.
+fn parse_unicode_range(item: &str) -> Option<(u32, u32)> {
+    let hex = |digits: &str| {
+        if digits.is_empty() || digits.len() > 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u32::from_str_radix(digits, 16).ok()
+    };
+    let item = item.strip_prefix(['U', 'u'])?.strip_prefix('+')?;
+    let (start, end) = if let Some((start, end)) = item.split_once('-') {
+        (hex(start)?, hex(end)?)
+    } else if item.ends_with('?') {
+        if item.len() > 6 || item.trim_end_matches('?').contains('?') {
+            return None;
+        }
+        (hex(&item.replace('?', "0"))?, hex(&item.replace('?', "F"))?)
+    } else {
+        let code_point = hex(item)?;
+        (code_point, code_point)
+    };
+    if start > end || start > 0x10FFFF {
+        return None;
+    }
+    Some((start, end.min(0x10FFFF)))
+}
+
+/// Map the CSS generic families that Fontconfig does not know to ones it does.
+fn generic_family(family: &str) -> Option<&'static str> {
+    match family {
+        "ui-serif" => Some("serif"),
+        "ui-sans-serif" => Some("sans-serif"),
+        "ui-monospace" => Some("monospace"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shorthand() {
+        let font = CssFont::parse("italic bold 14px/1.2 'Fira Sans', sans-serif").unwrap();
+        assert_eq!(
+            font,
+            CssFont {
+                families: vec!["Fira Sans".into(), "sans-serif".into()],
+                slant: Some(FC_SLANT_ITALIC),
+                weight: Some(700.0),
+                width: None,
+                small_caps: false,
+                pixel_size: 14.0,
+            }
+        );
+
+        let font = CssFont::parse("small-caps 600 condensed 12pt / 1.5 Noto  Sans,serif").unwrap();
+        assert_eq!(font.families, ["Noto Sans", "serif"]);
+        assert_eq!(font.weight, Some(600.0));
+        assert_eq!(font.width, Some(FC_WIDTH_CONDENSED));
+        assert!(font.small_caps);
+        assert_eq!(font.pixel_size, 16.0);
+
+        let font = CssFont::parse("oblique 10deg 1.5em \"A \\\"quoted\\\" name\"").unwrap();
+        assert_eq!(font.slant, Some(FC_SLANT_OBLIQUE));
+        assert_eq!(font.pixel_size, 24.0);
+        assert_eq!(font.families, ["A \"quoted\" name"]);
+
+        assert_eq!(
+            CssFont::parse("large ui-monospace").unwrap().pixel_size,
+            18.0
+        );
+    }
+
+    #[test]
+    fn reject_invalid() {
+        assert_eq!(CssFont::parse("bold sans-serif"), None);
+        assert_eq!(CssFont::parse("12px"), None);
+        assert_eq!(CssFont::parse("12px 'Unterminated"), None);
+        assert_eq!(CssFont::parse("12furlongs serif"), None);
+        assert_eq!(CssFont::parse("12px serif,"), None);
+    }
+
+    #[test]
+    fn pattern_from_css() {
+        let fc = Fontconfig::new().unwrap();
+        let pat = Pattern::from_css(&fc, "italic bold 14px/1.2 'Fira Sans', sans-serif").unwrap();
+        assert_eq!(pat.get_string_at(FC_FAMILY, 0), Some("Fira Sans"));
+        assert_eq!(pat.get_string_at(FC_FAMILY, 1), Some("sans-serif"));
+        assert_eq!(pat.slant(), Some(FC_SLANT_ITALIC));
+        assert_eq!(pat.weight(), Some(crate::FC_WEIGHT_BOLD));
+        assert_eq!(pat.get_double(FC_PIXEL_SIZE), Some(14.0));
+    }
+
+    #[test]
+    fn unicode_range() {
+        assert_eq!(parse_unicode_range("U+0131"), Some((0x131, 0x131)));
+        assert_eq!(parse_unicode_range("u+2000-206f"), Some((0x2000, 0x206F)));
+        assert_eq!(parse_unicode_range("U+4??"), Some((0x400, 0x4FF)));
+        assert_eq!(parse_unicode_range("U+??????"), Some((0, 0x10FFFF)));
+        assert_eq!(
+            parse_unicode_range("U+10FFFF-1FFFFF"),
+            Some((0x10FFFF, 0x10FFFF))
+        );
+        assert_eq!(parse_unicode_range("U+00FF-0000"), None);
+        assert_eq!(parse_unicode_range("U+110000"), None);
+        assert_eq!(parse_unicode_range("U+4?4"), None);
+        assert_eq!(parse_unicode_range("U+1234567"), None);
+        assert_eq!(parse_unicode_range("0041"), None);
+        assert_eq!(parse_unicode_range("U+"), None);
+
+        let fc = Fontconfig::new().unwrap();
+        let set = CharSet::from_unicode_range(&fc, " U+0041-005A ,U+61 ").unwrap();
+        assert_eq!(set.len(), 27);
+        assert!(set.contains('A') && set.contains('Z') && set.contains('a'));
+        // Surrogates are not characters.
+        assert_eq!(
+            CharSet::from_unicode_range(&fc, "U+D7FF-E000")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            CharSet::from_unicode_range(&fc, "U+D800-DFFF")
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_eq!(
+            CharSet::from_unicode_range(&fc, "U+DC00-E001")
+                .unwrap()
+                .len(),
+            2
+        );
+        let all = CharSet::from_unicode_range(&fc, "U+??????").unwrap();
+        assert_eq!(all.len(), 0x110000 - 0x800);
+        assert!(CharSet::from_unicode_range(&fc, "U+0041,").is_none());
+        assert!(CharSet::from_unicode_range(&fc, "").is_none());
+    }
+}
@@ -0,0 +1,107 @@
+//! Reading the contents of matched font files.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{Font, Pattern};
+
+impl Font {
+    /// Read the file containing this font.
+    ///
+    /// The whole file is returned, so for a collection the index must still be used to select
+    /// the face. Returns an error of kind `InvalidData` if the file has no face at the index.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let font = fc.find("sans-serif", None).unwrap();
+    /// let data = font.data().unwrap();
+    /// println!("{} bytes", data.len());
+    /// ```
+    pub fn data(&self) -> io::Result<Vec<u8>> {
+        read_font_file(&self.path, self.index.unwrap_or(0))
+    }
+}
+
+impl Pattern<'_> {
+    /// Read the file of this font pattern.
+    ///
+    /// Returns an error of kind `NotFound` if the pattern has no file, and of kind
+    /// `InvalidData` if the file has no face at the index of the pattern.
+    pub fn read_file(&self) -> io::Result<Vec<u8>> {
+        read_font_file(&self.existing_file_path()?, self.face_index().unwrap_or(0))
+    }
+
+    /// Get the "file" of this font pattern, or an error of kind `NotFound` if it has none, for
+    /// the methods that open the file.
+    pub(crate) fn existing_file_path(&self) -> io::Result<PathBuf> {
+        self.file_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "pattern has no file"))
+    }
+}
+
+fn read_font_file(path: &Path, index: i32) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    check_face_index(&data, index)?;
+    Ok(data)
+}
+
+/// Returns an error of kind `InvalidData` if the font file `data` has no face at `index`.
+pub(crate) fn check_face_index(data: &[u8], index: i32) -> io::Result<()> {
+    // The upper 16 bits hold the named instance of a variable font.
+    let face = index as u32 & 0xFFFF;
+    if face < face_count(data) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("font file has no face at index {}", face),
+        ))
+    }
+}
+
+/// Returns the number of faces in a font file, which is only greater than one for collections.
+fn face_count(data: &[u8]) -> u32 {
+    match data.get(..12) {
+        Some([b't', b't', b'c', b'f', _, _, _, _, count @ ..]) => {
+            u32::from_be_bytes(count.try_into().unwrap())
+        }
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fontconfig;
+
+    #[test]
+    fn read_matched_font() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let data = font.data().unwrap();
+        assert_eq!(data, fs::read(&font.path).unwrap());
+
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(crate::FC_FAMILY, c"DejaVu Sans");
+        assert_eq!(pat.font_match().read_file().unwrap(), data);
+        assert!(Pattern::new(&fc).read_file().is_err());
+
+        let missing = Font {
+            index: Some(1),
+            ..font
+        };
+        assert_eq!(
+            missing.data().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn count_collection_faces() {
+        assert_eq!(face_count(b"ttcf\0\x02\0\0\0\0\0\x03"), 3);
+        assert_eq!(face_count(b"\0\x01\0\0"), 1);
+    }
+}
@@ -0,0 +1,251 @@
+//! A compact snapshot of the installed fonts that can be queried without Fontconfig.
+
+#[cfg(unix)]
+use std::ffi::OsStr;
+use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::{list_fonts, Fontconfig, Generation, ObjectSet, Pattern, Spacing};
+use crate::{FC_FAMILY, FC_FILE, FC_INDEX, FC_SLANT, FC_SPACING, FC_STYLE, FC_WEIGHT, FC_WIDTH};
+use crate::{FC_SLANT_ROMAN, FC_WEIGHT_REGULAR, FC_WIDTH_NORMAL};
+
+/// The location of a string in the arena of a `FontDatabase`.
+#[derive(Clone, Copy, Debug)]
+struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    fn range(self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
+
+/// A fixed-size record describing one font.
+#[derive(Clone, Copy, Debug)]
+struct Record {
+    /// The case-folded family name, which records are sorted by.
+    key: Span,
+    family: Span,
+    style: Span,
+    /// The location of the path in the path arena.
+    path: Span,
+    index: i32,
+    weight: i32,
+    slant: i32,
+    width: i32,
+    spacing: Option<Spacing>,
+}
+
+/// Every installed font, enumerated once into a contiguous, cache-friendly structure.
+///
+/// All strings live in two arenas, one for names and one for paths, and each font is a fixed-size record, so the database
+/// makes a handful of allocations however many fonts are installed. Once built it never calls
+/// into Fontconfig, and it is `Send` and `Sync`, so game engines and other applications can
+/// query it from worker threads without touching libfontconfig after startup.
+///
+/// Each font is recorded under its first (usually English) family name. Use [`FontIndex`] to
+/// look fonts up by any of their family names.
+///
+/// [`FontIndex`]: crate::FontIndex
+///
+/// ```
+/// use fontconfig::{FontDatabase, Fontconfig};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let db = FontDatabase::new(&fc);
+/// std::thread::spawn(move || {
+///     for font in db.family("DejaVu Sans") {
+///         println!("{} {}", font.style, font.path.display());
+///     }
+/// });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FontDatabase {
+    strings: String,
+    /// The paths of the fonts, which need not be valid UTF-8.
+    paths: Vec<u8>,
+    records: Vec<Record>,
+    /// The generation of the configuration this database was built from.
+    pub(crate) generation: Generation,
+}
+
+/// A font in a [`FontDatabase`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DatabaseFont<'a> {
+    /// The family name of this font.
+    pub family: &'a str,
+    /// The style name of this font, e.g. "Bold Italic", or an empty string if it has none.
+    pub style: &'a str,
+    /// The location of this font on the filesystem.
+    pub path: &'a Path,
+    /// The index of the font within the file.
+    pub index: i32,
+    /// The weight of this font, on Fontconfig's scale (e.g. `FC_WEIGHT_BOLD`).
+    pub weight: i32,
+    /// The slant of this font (e.g. `FC_SLANT_ITALIC`).
+    pub slant: i32,
+    /// The width of this font (e.g. `FC_WIDTH_CONDENSED`).
+    pub width: i32,
+    /// The spacing of this font, if it declares one.
+    pub spacing: Option<Spacing>,
+}
+
+impl FontDatabase {
+    /// Enumerate all fonts known to Fontconfig into a new database.
+    pub fn new(fc: &Fontconfig) -> FontDatabase {
+        let mut objects = ObjectSet::new(fc);
+        for object in [
+            FC_FAMILY, FC_STYLE, FC_FILE, FC_INDEX, FC_WEIGHT, FC_SLANT, FC_WIDTH, FC_SPACING,
+        ] {
+            objects.add(object);
+        }
+
+        let mut db = FontDatabase {
+            generation: fc.generation(),
+            ..FontDatabase::default()
+        };
+        for pattern in list_fonts(&Pattern::new(fc), Some(&objects)).iter() {
+            db.push(&pattern);
+        }
+        let strings = &db.strings;
+        db.records
+            .sort_by(|a, b| strings[a.key.range()].cmp(&strings[b.key.range()]));
+        db.strings.shrink_to_fit();
+        db.paths.shrink_to_fit();
+        db.records.shrink_to_fit();
+        db
+    }
+
+    fn push(&mut self, pattern: &Pattern) {
+        let (Some(family), Some(path)) = (pattern.get_string(FC_FAMILY), pattern.file_path())
+        else {
+            return;
+        };
+        let record = Record {
+            key: self.intern(&family.to_lowercase()),
+            family: self.intern(family),
+            style: self.intern(pattern.get_string(FC_STYLE).unwrap_or("")),
+            path: self.intern_path(&path),
+            index: pattern.face_index().unwrap_or(0),
+            weight: pattern.weight().unwrap_or(FC_WEIGHT_REGULAR),
+            slant: pattern.slant().unwrap_or(FC_SLANT_ROMAN),
+            width: pattern.width().unwrap_or(FC_WIDTH_NORMAL),
+            spacing: pattern.spacing(),
+        };
+        self.records.push(record);
+    }
+
+    fn intern(&mut self, s: &str) -> Span {
+        let start = self.strings.len() as u32;
+        self.strings.push_str(s);
+        Span {
+            start,
+            end: self.strings.len() as u32,
+        }
+    }
+
+    fn intern_path(&mut self, path: &Path) -> Span {
+        let start = self.paths.len() as u32;
+        #[cfg(unix)]
+        self.paths.extend_from_slice(path.as_os_str().as_bytes());
+        // Elsewhere paths from Fontconfig are UTF-8, and `file_path` has made them so.
+        #[cfg(not(unix))]
+        self.paths
+            .extend_from_slice(path.to_string_lossy().as_bytes());
+        Span {
+            start,
+            end: self.paths.len() as u32,
+        }
+    }
+
+    fn path(&self, span: Span) -> &Path {
+        let bytes = &self.paths[span.range()];
+        #[cfg(unix)]
+        return Path::new(OsStr::from_bytes(bytes));
+        #[cfg(not(unix))]
+        return Path::new(std::str::from_utf8(bytes).unwrap_or_default());
+    }
+
+    fn font(&self, record: &Record) -> DatabaseFont<'_> {
+        DatabaseFont {
+            family: &self.strings[record.family.range()],
+            style: &self.strings[record.style.range()],
+            path: self.path(record.path),
+            index: record.index,
+            weight: record.weight,
+            slant: record.slant,
+            width: record.width,
+            spacing: record.spacing,
+        }
+    }
+
+    /// Iterate all fonts in this database, sorted by family name.
+    pub fn iter(&self) -> impl Iterator<Item = DatabaseFont<'_>> {
+        self.records.iter().map(|record| self.font(record))
+    }
+
+    /// Iterate the fonts of the family `name`, ignoring case.
+    pub fn family(&self, name: &str) -> impl Iterator<Item = DatabaseFont<'_>> {
+        let name = name.to_lowercase();
+        let key = |record: &Record| &self.strings[record.key.range()];
+        let start = self
+            .records
+            .partition_point(|record| key(record) < name.as_str());
+        let end = self
+            .records
+            .partition_point(|record| key(record) <= name.as_str());
+        self.records[start..end]
+            .iter()
+            .map(|record| self.font(record))
+    }
+
+    /// Returns the number of fonts in this database.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if this database contains no fonts.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_family() {
+        let fc = Fontconfig::new().unwrap();
+        let db = FontDatabase::new(&fc);
+        assert!(!db.is_empty());
+        assert_eq!(db.iter().count(), db.len());
+
+        let fonts: Vec<_> = db.family("dejavu SANS").collect();
+        assert!(!fonts.is_empty());
+        assert!(fonts.iter().all(|font| font.family == "DejaVu Sans"));
+        assert!(fonts
+            .iter()
+            .any(|font| font.weight == crate::FC_WEIGHT_BOLD && font.style == "Bold"));
+        assert_eq!(db.family("no such family").count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"Caf\xc3\xa9");
+        pat.add_string(FC_FILE, c"/fonts/caf\xe9.ttf");
+
+        let mut db = FontDatabase::default();
+        db.push(&pat);
+        let font = db.iter().next().unwrap();
+        assert_eq!(font.family, "Caf\u{e9}");
+        assert_eq!(font.path, OsStr::from_bytes(b"/fonts/caf\xe9.ttf"));
+    }
+}
@@ -0,0 +1,184 @@
+//! The directories and files a configuration is made of.
+
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::c_char;
+use std::path::PathBuf;
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    cstr_to_path, ffi_dispatch, ffi_dispatch_optional, sys, FcFalse, FcTrue, Fontconfig, StrList,
+};
+
+/// A configuration file that was loaded, as listed by `fc-conflist`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigFileInfo {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The `<description>` of the file, if it has one.
+    pub description: Option<String>,
+    /// Whether the rules of the file are applied, which they are unless it was loaded with
+    /// them disabled.
+    pub enabled: bool,
+}
+
+impl Fontconfig {
+    /// The font directories of the configuration of this handle, including the
+    /// subdirectories found while scanning them.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// for dir in fc.font_dirs() {
+    ///     println!("{}", dir.display());
+    /// }
+    /// ```
+    pub fn font_dirs(&self) -> Vec<PathBuf> {
+        unsafe { paths(ffi_dispatch!(LIB, FcConfigGetFontDirs, self.raw_config())) }
+    }
+
+    /// The directories of configuration files that were included, such as `/etc/fonts/conf.d`.
+    ///
+    /// Fontconfig checks these for changes when deciding whether the configuration is up to
+    /// date.
+    pub fn config_dirs(&self) -> Vec<PathBuf> {
+        unsafe { paths(ffi_dispatch!(LIB, FcConfigGetConfigDirs, self.raw_config())) }
+    }
+
+    /// The directories Fontconfig reads font caches from, in order of preference.
+    ///
+    /// The first one that is writable is where new caches are written.
+    pub fn cache_dirs(&self) -> Vec<PathBuf> {
+        unsafe { paths(ffi_dispatch!(LIB, FcConfigGetCacheDirs, self.raw_config())) }
+    }
+
+    /// The configuration files that were loaded, in the order they were read.
+    pub fn config_files(&self) -> Vec<PathBuf> {
+        unsafe {
+            paths(ffi_dispatch!(
+                LIB,
+                FcConfigGetConfigFiles,
+                self.raw_config()
+            ))
+        }
+    }
+
+    /// The configuration files that were loaded, with their descriptions and whether their
+    /// rules are applied, in the order they were read.
+    ///
+    /// This is empty if the loaded Fontconfig is older than 2.13.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// for file in fc.config_file_info() {
+    ///     let mark = if file.enabled { '+' } else { '-' };
+    ///     println!("{} {}: {}", mark, file.path.display(), file.description.unwrap_or_default());
+    /// }
+    /// ```
+    pub fn config_file_info(&self) -> Vec<ConfigFileInfo> {
+        let mut files = Vec::new();
+        if !sys::available::FcConfigFileInfoIterInit() {
+            return files;
+        }
+        let config = self.raw_config();
+        let mut iter = sys::FcConfigFileInfoIter {
+            dummy1: ptr::null_mut(),
+            dummy2: ptr::null_mut(),
+            dummy3: ptr::null_mut(),
+        };
+        unsafe {
+            ffi_dispatch_optional!(FcConfigFileInfoIterInit, config, &mut iter);
+            loop {
+                let mut name = ptr::null_mut();
+                let mut description = ptr::null_mut();
+                let mut enabled = FcFalse;
+                if ffi_dispatch_optional!(
+                    FcConfigFileInfoIterGet,
+                    config,
+                    &mut iter,
+                    &mut name,
+                    &mut description,
+                    &mut enabled
+                ) == FcTrue
+                {
+                    files.push(ConfigFileInfo {
+                        path: take_string(name, cstr_to_path).unwrap_or_default(),
+                        description: take_string(description, |s| s.to_string_lossy().into_owned()),
+                        enabled: enabled == FcTrue,
+                    });
+                }
+                if ffi_dispatch_optional!(FcConfigFileInfoIterNext, config, &mut iter) != FcTrue {
+                    break;
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Convert a string allocated by Fontconfig with `convert`, freeing the string.
+unsafe fn take_string<T>(s: *mut sys::FcChar8, convert: impl FnOnce(&CStr) -> T) -> Option<T> {
+    if s.is_null() {
+        return None;
+    }
+    let value = convert(CStr::from_ptr(s as *const c_char));
+    ffi_dispatch!(LIB, FcStrFree, s);
+    Some(value)
+}
+
+/// Collect the paths in a list returned by Fontconfig.
+pub(crate) unsafe fn paths(list: *mut sys::FcStrList) -> Vec<PathBuf> {
+    if list.is_null() {
+        return Vec::new();
+    }
+    let list = StrList {
+        list,
+        _life: PhantomData,
+    };
+    list.cstrs().map(cstr_to_path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::dejavu_dir;
+    use crate::{HostFontDir, SandboxFonts};
+
+    #[test]
+    fn configured_paths() {
+        let cache = std::env::temp_dir().join("fontconfig-rs-dirs-test");
+        let dejavu = dejavu_dir();
+        let fc = SandboxFonts {
+            host_dirs: vec![HostFontDir {
+                path: dejavu.clone(),
+                host_path: None,
+            }],
+            cache_dir: Some(cache.clone()),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        }
+        .build()
+        .unwrap();
+        assert!(fc.config_dirs().is_empty());
+        assert!(fc.font_dirs().contains(&dejavu));
+        assert_eq!(fc.cache_dirs(), [cache]);
+        assert!(fc.config_files().is_empty());
+
+        let fc = Fontconfig::new().unwrap();
+        assert!(!fc.config_files().is_empty());
+        assert!(!fc.config_dirs().is_empty());
+        let info = fc.config_file_info();
+        assert!(!info.is_empty());
+        assert!(info
+            .iter()
+            .any(|file| fc.config_files().contains(&file.path) && file.enabled));
+        assert!(!fc.font_dirs().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+//! Gathering what PDF and EPUB writers need to embed a subset of a font.
+
+use std::ops::RangeInclusive;
+
+use crate::{FontFormat, Handle, Pattern};
+
+/// The information needed to embed the part of a font used by some text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Embedding {
+    /// The font file and the index of the face within it.
+    pub handle: Handle,
+    /// The format of the font, if Fontconfig recognises it.
+    pub format: Option<FontFormat>,
+    /// The characters of the text that the font covers, as sorted, non-overlapping ranges.
+    pub ranges: Vec<RangeInclusive<char>>,
+    /// The characters of the text that the font does not cover, sorted and without duplicates.
+    ///
+    /// These need a fallback font. Control characters are never included.
+    pub missing: Vec<char>,
+}
+
+impl Pattern<'_> {
+    /// Work out how to embed this matched font for rendering `text`, e.g. in a PDF.
+    ///
+    /// The result gives the file to read, the format to embed it as, and the minimal set of
+    /// characters to keep when subsetting it. Returns `None` if this pattern has no file or no
+    /// "charset" element, as is the case for patterns that have not been matched.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_string(FC_FAMILY, c"sans-serif");
+    /// let embedding = pat.font_match().embedding("Hello, world").unwrap();
+    /// println!("{} {:?}", embedding.handle.path.display(), embedding.ranges);
+    /// ```
+    pub fn embedding(&self, text: &str) -> Option<Embedding> {
+        let handle = self.handle()?;
+        let char_set = self.char_set()?;
+
+        let mut chars: Vec<char> = text.chars().filter(|c| !c.is_control()).collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut ranges: Vec<RangeInclusive<char>> = Vec::new();
+        let mut missing = Vec::new();
+        for c in chars {
+            if !char_set.contains(c) {
+                missing.push(c);
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if char::from_u32(*range.end() as u32 + 1) == Some(c) => {
+                    *range = *range.start()..=c;
+                }
+                _ => ranges.push(c..=c),
+            }
+        }
+
+        Some(Embedding {
+            handle,
+            format: self.format().ok(),
+            ranges,
+            missing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FontFormat, Fontconfig, Pattern};
+
+    #[test]
+    fn embed_text() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let pattern = Pattern::from_file(&fc, &font.path, 0).unwrap();
+
+        let embedding = pattern.embedding("cabbage\u{10FFFD}\n").unwrap();
+        assert_eq!(embedding.handle, font.handle());
+        assert_eq!(embedding.format, Some(FontFormat::TrueType));
+        assert_eq!(embedding.ranges, ['a'..='c', 'e'..='e', 'g'..='g']);
+        assert_eq!(embedding.missing, ['\u{10FFFD}']);
+
+        assert!(Pattern::new(&fc).embedding("text").is_none());
+    }
+}
@@ -0,0 +1,213 @@
+//! Choosing fonts for text or emoji presentation of a character.
+
+use crate::{sort_fonts, CharSet, Font, Fontconfig, Pattern};
+use crate::{FC_COLOR, FC_FAMILY, FC_LANG, FC_SYMBOL};
+
+/// Variation selector 15, which requests text presentation.
+pub const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+
+/// Variation selector 16, which requests emoji presentation.
+pub const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+/// How a character that has both text and emoji forms should be displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Presentation {
+    /// A monochrome glyph that takes on the color of the text.
+    Text,
+    /// A color emoji glyph.
+    Emoji,
+}
+
+impl Presentation {
+    /// Work out the presentation of `c` when followed by `selector`.
+    ///
+    /// Variation selector 15 requests text presentation and variation selector 16 emoji
+    /// presentation. Without either, characters with the Unicode Emoji_Presentation property,
+    /// such as U+231A WATCH, default to emoji presentation and all others, such as U+2764 HEAVY
+    /// BLACK HEART, default to text presentation.
+    pub fn select(c: char, selector: Option<char>) -> Presentation {
+        match selector {
+            Some(TEXT_PRESENTATION_SELECTOR) => Presentation::Text,
+            Some(EMOJI_PRESENTATION_SELECTOR) => Presentation::Emoji,
+            _ if has_emoji_presentation(c) => Presentation::Emoji,
+            _ => Presentation::Text,
+        }
+    }
+}
+
+fn has_emoji_presentation(c: char) -> bool {
+    let c = c as u32;
+    let i = EMOJI_PRESENTATION.partition_point(|&(_, end)| end < c);
+    EMOJI_PRESENTATION
+        .get(i)
+        .is_some_and(|&(start, _)| start <= c)
+}
+
+/// The ranges of characters with the Emoji_Presentation property, from the emoji-data.txt file
+/// of Unicode 15.1.
+static EMOJI_PRESENTATION: &[(u32, u32)] = &[
+    (0x231A, 0x231B),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA),
+    (0x26F2, 0x26F3),
+    (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x274C, 0x274C),
+    (0x274E, 0x274E),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F1E6, 0x1F1FF),
+    (0x1F201, 0x1F201),
+    (0x1F21A, 0x1F21A),
+    (0x1F22F, 0x1F22F),
+    (0x1F232, 0x1F236),
+    (0x1F238, 0x1F23A),
+    (0x1F250, 0x1F251),
+    (0x1F300, 0x1F320),
+    (0x1F32D, 0x1F335),
+    (0x1F337, 0x1F37C),
+    (0x1F37E, 0x1F393),
+    (0x1F3A0, 0x1F3CA),
+    (0x1F3CF, 0x1F3D3),
+    (0x1F3E0, 0x1F3F0),
+    (0x1F3F4, 0x1F3F4),
+    (0x1F3F8, 0x1F43E),
+    (0x1F440, 0x1F440),
+    (0x1F442, 0x1F4FC),
+    (0x1F4FF, 0x1F53D),
+    (0x1F54B, 0x1F54E),
+    (0x1F550, 0x1F567),
+    (0x1F57A, 0x1F57A),
+    (0x1F595, 0x1F596),
+    (0x1F5A4, 0x1F5A4),
+    (0x1F5FB, 0x1F64F),
+    (0x1F680, 0x1F6C5),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F6D0, 0x1F6D2),
+    (0x1F6D5, 0x1F6D7),
+    (0x1F6DC, 0x1F6DF),
+    (0x1F6EB, 0x1F6EC),
+    (0x1F6F4, 0x1F6FC),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F7F0, 0x1F7F0),
+    (0x1F90C, 0x1F93A),
+    (0x1F93C, 0x1F945),
+    (0x1F947, 0x1F9FF),
+    (0x1FA70, 0x1FA7C),
+    (0x1FA80, 0x1FA88),
+    (0x1FA90, 0x1FABD),
+    (0x1FABF, 0x1FAC5),
+    (0x1FACE, 0x1FADB),
+    (0x1FAE0, 0x1FAE8),
+    (0x1FAF0, 0x1FAF8),
+];
+
+impl Fontconfig {
+    /// Find a font to display `c` with the given presentation.
+    ///
+    /// For emoji presentation color fonts are required and the "emoji" family is preferred; for
+    /// text presentation color fonts are excluded, unless they are symbol fonts, whose glyphs
+    /// are meant to be set as text. The fonts are tried in the order Fontconfig ranks them, and
+    /// `None` is returned if none with the requested presentation covers `c`.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Presentation};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let heart = '\u{2764}';
+    /// let presentation = Presentation::select(heart, Some('\u{FE0F}'));
+    /// if let Some(font) = fc.font_for_presentation(heart, presentation) {
+    ///     println!("{}", font.name);
+    /// }
+    /// ```
+    pub fn font_for_presentation(&self, c: char, presentation: Presentation) -> Option<Font> {
+        let mut char_set = CharSet::new(self);
+        char_set.add_char(c);
+
+        let mut pat = Pattern::new(self);
+        pat.add_char_set(&char_set);
+        match presentation {
+            Presentation::Emoji => {
+                pat.add_string(FC_FAMILY, c"emoji");
+                pat.add_string(FC_LANG, c"und-zsye");
+                pat.add_bool(FC_COLOR, true);
+            }
+            Presentation::Text => pat.add_bool(FC_COLOR, false),
+        }
+
+        pat.substitute();
+
+        sort_fonts(&pat, false)
+            .iter()
+            .filter(|font| {
+                let color = font.get_bool(FC_COLOR).unwrap_or(false);
+                match presentation {
+                    Presentation::Emoji => color,
+                    Presentation::Text => !color || font.get_bool(FC_SYMBOL).unwrap_or(false),
+                }
+            })
+            .filter(|font| font.char_set().is_some_and(|set| set.contains(c)))
+            .find_map(|font| Font::from_pattern(&font))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_presentation() {
+        assert_eq!(Presentation::select('❤', None), Presentation::Text);
+        assert_eq!(
+            Presentation::select('❤', Some(EMOJI_PRESENTATION_SELECTOR)),
+            Presentation::Emoji
+        );
+        assert_eq!(Presentation::select('😀', None), Presentation::Emoji);
+        assert_eq!(Presentation::select('\u{231A}', None), Presentation::Emoji);
+        assert_eq!(Presentation::select('\u{2614}', None), Presentation::Emoji);
+        // Enclosed alphanumerics and many pictographs are text by default.
+        assert_eq!(Presentation::select('\u{1F100}', None), Presentation::Text);
+        assert_eq!(Presentation::select('\u{1F321}', None), Presentation::Text);
+        assert_eq!(Presentation::select('\u{1FAF9}', None), Presentation::Text);
+        assert_eq!(
+            Presentation::select('😀', Some(TEXT_PRESENTATION_SELECTOR)),
+            Presentation::Text
+        );
+    }
+
+    #[test]
+    fn text_presentation_font() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.font_for_presentation('❤', Presentation::Text).unwrap();
+        let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+        assert!(pattern.char_set().unwrap().contains('❤'));
+        assert_ne!(pattern.get_bool(FC_COLOR), Some(true));
+    }
+}
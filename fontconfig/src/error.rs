@@ -0,0 +1,181 @@
+//! The error type shared by fallible operations.
+
+use std::fmt;
+use std::str::Utf8Error;
+
+use crate::{sys, InvalidTag, NoFonts, UnknownFontFormat, UnknownProperty, UnknownScript};
+
+/// The result of a fallible operation.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error returned by Fontconfig or by the conversion of a value it returned.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The pattern has no value for the element, or no font matched.
+    NoMatch,
+    /// The element holds a value of a different type than the one requested.
+    TypeMismatch,
+    /// The element holds fewer values than the index requested.
+    NoId,
+    /// Fontconfig could not allocate memory.
+    OutOfMemory,
+    /// Fontconfig could not load a new configuration.
+    ReloadFailed,
+    /// Fontconfig could not load or build a configuration.
+    ConfigFailed,
+    /// The configuration of a handle with a configuration of its own is out of date, and only a
+    /// new handle can pick up the changes.
+    ConfigOutOfDate,
+    /// A string value is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// The "fontformat" element holds a format this crate does not know.
+    UnknownFontFormat(UnknownFontFormat),
+    /// A string is not a valid OpenType tag or axis setting.
+    InvalidTag(InvalidTag),
+    /// A string is not a known script.
+    UnknownScript(UnknownScript),
+    /// A string is not the name of a pattern element Fontconfig knows about.
+    UnknownProperty(UnknownProperty),
+    /// Fontconfig knows no fonts at all.
+    NoFonts(NoFonts),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoMatch => f.write_str("no match"),
+            Error::TypeMismatch => f.write_str("value has a different type"),
+            Error::NoId => f.write_str("no value at this index"),
+            Error::OutOfMemory => f.write_str("out of memory"),
+            Error::ReloadFailed => f.write_str("could not reload the configuration"),
+            Error::ConfigFailed => f.write_str("could not build the configuration"),
+            Error::ConfigOutOfDate => f.write_str("the configuration of the handle is out of date"),
+            Error::InvalidUtf8(err) => write!(f, "value is not valid UTF-8: {}", err),
+            Error::UnknownFontFormat(UnknownFontFormat(format)) => {
+                write!(f, "unknown font format {:?}", format)
+            }
+            Error::InvalidTag(InvalidTag(tag)) => write!(f, "invalid tag {:?}", tag),
+            Error::UnknownScript(UnknownScript(script)) => {
+                write!(f, "unknown script {:?}", script)
+            }
+            Error::UnknownProperty(UnknownProperty(name)) => {
+                write!(f, "unknown property {:?}", name)
+            }
+            Error::NoFonts(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8(err) => Some(err),
+            // `NoFonts` is displayed as the error itself, so it is not reported again as the
+            // source.
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Self {
+        Error::InvalidUtf8(err)
+    }
+}
+
+impl From<UnknownFontFormat> for Error {
+    fn from(err: UnknownFontFormat) -> Self {
+        Error::UnknownFontFormat(err)
+    }
+}
+
+impl From<InvalidTag> for Error {
+    fn from(err: InvalidTag) -> Self {
+        Error::InvalidTag(err)
+    }
+}
+
+impl From<UnknownScript> for Error {
+    fn from(err: UnknownScript) -> Self {
+        Error::UnknownScript(err)
+    }
+}
+
+impl From<UnknownProperty> for Error {
+    fn from(err: UnknownProperty) -> Self {
+        Error::UnknownProperty(err)
+    }
+}
+
+impl From<NoFonts> for Error {
+    fn from(err: NoFonts) -> Self {
+        Error::NoFonts(err)
+    }
+}
+
+/// Conversion of the `FcResult` returned by Fontconfig functions into a `Result`.
+///
+/// This is for calling functions of the `fontconfig-sys` crate that this crate does not wrap,
+/// so that their results can be handled like those of the rest of the crate. `FcResultMatch`
+/// becomes `Ok(())` and every other result the corresponding [`Error`].
+///
+/// `FcResult` is an alias of `c_uint`, so the method is available on any `c_uint` once this
+/// trait is imported, but it is only meaningful on results returned by Fontconfig.
+///
+/// ```
+/// use fontconfig::{Error, FcResultExt};
+/// use fontconfig_sys::{FcResult, FcResultMatch, FcResultNoId};
+///
+/// // E.g. the result of `FcPatternGetString` for an element with too few values.
+/// let result: FcResult = FcResultNoId;
+/// assert!(matches!(result.to_result(), Err(Error::NoId)));
+/// assert!(FcResultMatch.to_result().is_ok());
+/// ```
+pub trait FcResultExt {
+    /// Convert this result into a `Result`.
+    fn to_result(self) -> Result<()>;
+}
+
+impl FcResultExt for sys::FcResult {
+    fn to_result(self) -> Result<()> {
+        match self {
+            sys::FcResultMatch => Ok(()),
+            sys::FcResultTypeMismatch => Err(Error::TypeMismatch),
+            sys::FcResultNoId => Err(Error::NoId),
+            sys::FcResultOutOfMemory => Err(Error::OutOfMemory),
+            _ => Err(Error::NoMatch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_results() {
+        assert!(sys::FcResultMatch.to_result().is_ok());
+        assert!(matches!(
+            sys::FcResultNoMatch.to_result(),
+            Err(Error::NoMatch)
+        ));
+        assert!(matches!(
+            sys::FcResultTypeMismatch.to_result(),
+            Err(Error::TypeMismatch)
+        ));
+        assert_eq!(
+            Error::from(UnknownFontFormat("Foo".into())).to_string(),
+            "unknown font format \"Foo\""
+        );
+
+        let no_fonts = NoFonts {
+            font_dirs: Vec::new(),
+            config_files: Vec::new(),
+        };
+        let message = no_fonts.to_string();
+        let err = Error::from(no_fonts);
+        assert_eq!(err.to_string(), message);
+        assert!(std::error::Error::source(&err).is_none());
+    }
+}
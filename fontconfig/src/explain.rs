@@ -0,0 +1,220 @@
+//! Explaining why Fontconfig ranked fonts the way it did.
+
+use std::ffi::CStr;
+
+use crate::filter::same_name;
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, sort_fonts, Pattern, Value};
+use crate::{
+    FC_CHARSET, FC_COLOR, FC_FAMILY, FC_FOUNDRY, FC_LANG, FC_SCALABLE, FC_SLANT, FC_SPACING,
+    FC_STYLE, FC_WEIGHT, FC_WIDTH,
+};
+
+/// How the values of an element are compared.
+#[derive(Clone, Copy)]
+enum Kind {
+    Name,
+    Number,
+    Bool,
+    Lang,
+    CharSet,
+}
+
+/// The elements compared, in the order of importance Fontconfig gives them when sorting.
+const ELEMENTS: [(&CStr, Kind); 11] = [
+    (FC_COLOR, Kind::Bool),
+    (FC_SCALABLE, Kind::Bool),
+    (FC_FOUNDRY, Kind::Name),
+    (FC_CHARSET, Kind::CharSet),
+    (FC_FAMILY, Kind::Name),
+    (FC_LANG, Kind::Lang),
+    (FC_SPACING, Kind::Number),
+    (FC_STYLE, Kind::Name),
+    (FC_SLANT, Kind::Number),
+    (FC_WEIGHT, Kind::Number),
+    (FC_WIDTH, Kind::Number),
+];
+
+/// A font considered when matching a pattern, with the elements in which it differs from the
+/// pattern.
+///
+/// Fontconfig does not expose the scores it computes while sorting, but the elements in which
+/// a font falls short explain its place in the ranking: a font that differs in a more
+/// important element, such as "family", ranks below one that only differs in a less important
+/// one, such as "weight".
+#[derive(Debug)]
+pub struct Candidate<'fc> {
+    /// The font, with the rendering settings of the configuration applied as by
+    /// [`Pattern::font_match`].
+    pub font: Pattern<'fc>,
+    /// The elements of the pattern whose first value the font does not have, most important
+    /// first.
+    ///
+    /// Only the color, scalable, foundry, charset, family, lang, spacing, style, slant, weight
+    /// and width elements are compared, and only if the pattern has a value for them. A
+    /// "charset" differs if the font does not cover all of it, and a number, or a range of
+    /// numbers, differs if it is outside the range of a variable font.
+    pub differences: Vec<&'static CStr>,
+}
+
+impl Candidate<'_> {
+    /// Returns `true` if the font has the first value of every compared element of the pattern.
+    pub fn is_exact(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns `true` if the font differs from the pattern in the element `name`.
+    pub fn differs_in(&self, name: &CStr) -> bool {
+        self.differences.contains(&name)
+    }
+}
+
+impl<'fc> Pattern<'fc> {
+    /// Find the installed fonts, sorted from the closest match to this pattern to the furthest,
+    /// reporting for each how it differs from the pattern.
+    ///
+    /// This is meant for finding out why a particular font, such as a fallback, was chosen.
+    /// The configuration is applied to a copy of the pattern to sort the fonts, but the fonts
+    /// are compared with this pattern as given, so that elements added by the configuration are
+    /// not reported.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_string(FC_FAMILY, c"Helvetica");
+    /// for candidate in pat.candidates().iter().take(3) {
+    ///     println!("{:?} differs in {:?}", candidate.font.name(), candidate.differences);
+    /// }
+    /// ```
+    pub fn candidates(&self) -> Vec<Candidate<'fc>> {
+        let mut pattern = self.clone();
+        pattern.substitute();
+        sort_fonts(&pattern, false)
+            .iter()
+            .filter_map(|font| {
+                let pat = unsafe {
+                    ffi_dispatch!(
+                        LIB,
+                        FcFontRenderPrepare,
+                        self.fc.config,
+                        pattern.pat,
+                        font.pat
+                    )
+                };
+                if pat.is_null() {
+                    return None;
+                }
+                // The prepared font takes some values from the pattern, so compare the font
+                // as installed.
+                let differences = ELEMENTS
+                    .iter()
+                    .filter(|&&(name, kind)| differs(self, &font, name, kind))
+                    .map(|&(name, _)| name)
+                    .collect();
+                let font = Pattern { pat, fc: self.fc };
+                Some(Candidate { font, differences })
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` if `query` has a value for `name` that `font` does not have.
+fn differs(query: &Pattern, font: &Pattern, name: &CStr, kind: Kind) -> bool {
+    match kind {
+        Kind::Name => query.get_string_at(name, 0).is_some_and(|wanted| {
+            !(0..)
+                .map_while(|n| font.get_string_at(name, n))
+                .any(|value| same_name(value, wanted))
+        }),
+        Kind::Number => number_range(query, name).is_some_and(|(low, high)| {
+            !number_range(font, name).is_some_and(|(min, max)| min <= low && high <= max)
+        }),
+        Kind::Bool => query
+            .try_get_bool(name, 0)
+            .is_ok_and(|wanted| font.try_get_bool(name, 0).ok() != Some(wanted)),
+        Kind::Lang => query.get_string_at(name, 0).is_some_and(|wanted| {
+            !font
+                .get_lang_set()
+                .is_some_and(|langs| langs.contains(wanted))
+        }),
+        Kind::CharSet => query.char_set().is_some_and(|wanted| {
+            !font
+                .char_set()
+                .is_some_and(|chars| wanted.is_subset(&chars))
+        }),
+    }
+}
+
+/// The first value of the numeric element `name` as the range of numbers it covers, which is
+/// a single number unless the value is a range, such as the weights of a variable font.
+fn number_range(pattern: &Pattern, name: &CStr) -> Option<(f64, f64)> {
+    match pattern.get_value(name, 0).ok()? {
+        Value::Integer(i) => Some((i.into(), i.into())),
+        Value::Double(d) => Some((d, d)),
+        Value::Range(low, high) => Some((low, high)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharSet, Fontconfig, FC_WEIGHT_BOLD};
+
+    #[test]
+    fn explain_ranking() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "DejaVu Sans");
+        pat.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+        pat.add(FC_LANG, "en");
+
+        let candidates = pat.candidates();
+        assert_eq!(candidates[0].font.name(), Some("DejaVu Sans Bold"));
+        assert!(candidates[0].is_exact());
+        assert!(candidates
+            .iter()
+            .any(|candidate| candidate.differences == [FC_WEIGHT]));
+        let other = candidates
+            .iter()
+            .find(|candidate| candidate.differs_in(FC_FAMILY))
+            .unwrap();
+        assert!(!other.differs_in(FC_STYLE));
+
+        let mut chars = CharSet::new(&fc);
+        chars.add_char('\u{10FFFD}');
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_CHARSET, &chars);
+        assert!(pat.candidates()[0].differs_in(FC_CHARSET));
+    }
+
+    #[test]
+    fn range_values() {
+        let fc = Fontconfig::new().unwrap();
+        let pattern = |weight: Value| {
+            let mut pat = Pattern::new(&fc);
+            pat.add(FC_WEIGHT, weight);
+            pat
+        };
+        let differs =
+            |query: &Pattern, font: &Pattern| differs(query, font, FC_WEIGHT, Kind::Number);
+
+        // A variable font has every weight in its range.
+        let bold = pattern(Value::Integer(FC_WEIGHT_BOLD));
+        let variable = pattern(Value::Range(0.0, 210.0));
+        assert!(!differs(&bold, &variable));
+        assert!(differs(&bold, &pattern(Value::Range(0.0, 100.0))));
+        assert!(!differs(&bold, &pattern(Value::Double(200.0))));
+        assert!(differs(&bold, &pattern(Value::Integer(80))));
+
+        // A range is only matched by a font covering all of it.
+        assert!(!differs(&pattern(Value::Range(50.0, 200.0)), &variable));
+        assert!(differs(&pattern(Value::Range(50.0, 250.0)), &variable));
+        assert!(differs(&variable, &bold));
+    }
+}
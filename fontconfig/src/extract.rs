@@ -0,0 +1,203 @@
+//! Extracting typed structs from patterns.
+
+use std::ffi::{c_int, CStr};
+use std::path::PathBuf;
+
+use crate::{cstr_to_path, Error, FontFormat, Pattern, Result, Spacing};
+
+/// A type that can be built from the elements of a pattern.
+///
+/// With the `derive` feature, `#[derive(FromPattern)]` implements this for a struct with named
+/// fields, reading each field with [`FromPatternValue`] from the element named after it with
+/// underscores removed, so `postscript_name` reads "postscriptname". Two attributes adjust this:
+///
+/// * `#[fc(rename = "...")]` reads another element, e.g. `#[fc(rename = "fullname")]`.
+/// * `#[fc(index = n)]` reads the value at position `n` rather than the first.
+///
+/// Fields of type `Option<T>` are `None` when the element is missing, and fields of type `Vec<T>`
+/// hold every value of the element.
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use std::path::PathBuf;
+/// use fontconfig::{FromPattern, Fontconfig, Pattern, FC_FAMILY};
+///
+/// #[derive(FromPattern)]
+/// struct MyFont {
+///     family: String,
+///     file: PathBuf,
+///     weight: i32,
+///     #[fc(rename = "postscriptname")]
+///     ps_name: Option<String>,
+/// }
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let mut pat = Pattern::new(&fc);
+/// pat.add_string(FC_FAMILY, c"sans-serif");
+/// let font = MyFont::from_pattern(&pat.font_match()).unwrap();
+/// println!("{} {}", font.family, font.file.display());
+/// # }
+/// ```
+pub trait FromPattern: Sized {
+    /// Build a value from the elements of `pattern`.
+    ///
+    /// Returns an error, such as [`Error::NoMatch`] for a missing element, if an element
+    /// cannot be read.
+    fn from_pattern(pattern: &Pattern<'_>) -> Result<Self>;
+}
+
+/// A type that can be read from the values of a pattern element.
+pub trait FromPatternValue: Sized {
+    /// Read the value at position `n` of the element `name` of `pattern`.
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self>;
+}
+
+impl FromPatternValue for String {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.try_get_string(name, n).map(str::to_owned)
+    }
+}
+
+impl FromPatternValue for PathBuf {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.try_get_cstr(name, n).map(cstr_to_path)
+    }
+}
+
+impl FromPatternValue for i32 {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.try_get_int(name, n)
+    }
+}
+
+impl FromPatternValue for f64 {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.try_get_double(name, n)
+    }
+}
+
+impl FromPatternValue for bool {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.try_get_bool(name, n)
+    }
+}
+
+impl FromPatternValue for Spacing {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        Spacing::from_value(pattern.try_get_int(name, n)?).ok_or(Error::TypeMismatch)
+    }
+}
+
+impl FromPatternValue for FontFormat {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        Ok(pattern.try_get_string(name, n)?.parse()?)
+    }
+}
+
+/// `None` if the element is missing or has fewer values.
+impl<T: FromPatternValue> FromPatternValue for Option<T> {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        match T::from_pattern_value(pattern, name, n) {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NoMatch | Error::NoId) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Every value of the element from position `n` on, which is empty if the element is missing.
+impl<T: FromPatternValue> FromPatternValue for Vec<T> {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        let mut values = Vec::new();
+        for n in n.. {
+            match Option::<T>::from_pattern_value(pattern, name, n)? {
+                Some(value) => values.push(value),
+                None => break,
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fontconfig, FC_FAMILY, FC_WEIGHT};
+
+    #[test]
+    fn read_values() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "Foo");
+        pat.add(FC_FAMILY, "Bar");
+        pat.add(FC_WEIGHT, 80);
+
+        assert_eq!(
+            String::from_pattern_value(&pat, FC_FAMILY, 1).unwrap(),
+            "Bar"
+        );
+        assert_eq!(
+            Vec::<String>::from_pattern_value(&pat, FC_FAMILY, 0).unwrap(),
+            ["Foo", "Bar"]
+        );
+        assert_eq!(
+            Option::<String>::from_pattern_value(&pat, FC_FAMILY, 2).unwrap(),
+            None
+        );
+        assert!(matches!(
+            i32::from_pattern_value(&pat, FC_FAMILY, 0),
+            Err(Error::TypeMismatch)
+        ));
+        assert!(Option::<i32>::from_pattern_value(&pat, FC_FAMILY, 0).is_err());
+        assert_eq!(i32::from_pattern_value(&pat, FC_WEIGHT, 0).unwrap(), 80);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(crate::FC_FILE, c"/fonts/caf\xe9.ttf");
+        assert_eq!(
+            PathBuf::from_pattern_value(&pat, crate::FC_FILE, 0).unwrap(),
+            OsStr::from_bytes(b"/fonts/caf\xe9.ttf")
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_from_pattern() {
+        #[derive(crate::FromPattern, Debug, PartialEq)]
+        struct Font {
+            family: String,
+            #[fc(index = 1)]
+            #[fc(rename = "family")]
+            second_family: Option<String>,
+            weight: i32,
+            postscript_name: Option<String>,
+            file: Option<PathBuf>,
+        }
+
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "Foo");
+        pat.add(FC_WEIGHT, 80);
+        pat.add(crate::FC_POSTSCRIPT_NAME, "Foo-Regular");
+        assert_eq!(
+            Font::from_pattern(&pat).unwrap(),
+            Font {
+                family: "Foo".into(),
+                second_family: None,
+                weight: 80,
+                postscript_name: Some("Foo-Regular".into()),
+                file: None,
+            }
+        );
+
+        pat.del(FC_WEIGHT);
+        assert!(matches!(Font::from_pattern(&pat), Err(Error::NoMatch)));
+    }
+}
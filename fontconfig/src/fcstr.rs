@@ -0,0 +1,116 @@
+//! Conversion of Rust strings to the C strings Fontconfig expects.
+
+use std::borrow::Cow;
+#[cfg(unix)]
+use std::ffi::OsStr;
+use std::ffi::{CStr, CString};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+/// A string that can be passed to Fontconfig, such as a `&str`, `String` or `&CStr`.
+///
+/// Methods taking an `impl IntoFcStr` accept Rust strings directly, so there is no need to build
+/// a `CString` first:
+///
+/// ```
+/// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let family = String::from("DejaVu Sans");
+/// let mut pat = Pattern::new(&fc);
+/// pat.add_string(FC_FAMILY, &family);
+/// pat.add_string(FC_FAMILY, "sans-serif");
+/// pat.add_string(FC_FAMILY, c"serif");
+/// ```
+///
+/// C strings are passed through as they are. Rust strings are copied to add the terminating NUL.
+/// A Rust string containing a NUL byte cannot be passed to Fontconfig: methods adding it skip
+/// the value, and methods looking it up find nothing, as no name Fontconfig knows contains one.
+pub trait IntoFcStr<'a> {
+    /// Convert this string to a C string, or return `None` if it contains a NUL byte.
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>>;
+}
+
+impl<'a> IntoFcStr<'a> for &'a CStr {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl<'a> IntoFcStr<'a> for &'a CString {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl<'a> IntoFcStr<'a> for CString {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        Some(Cow::Owned(self))
+    }
+}
+
+impl<'a> IntoFcStr<'a> for Cow<'a, CStr> {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        Some(self)
+    }
+}
+
+impl<'a> IntoFcStr<'a> for &str {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        CString::new(self).ok().map(Cow::Owned)
+    }
+}
+
+impl<'a> IntoFcStr<'a> for &String {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        self.as_str().into_fc_str()
+    }
+}
+
+impl<'a> IntoFcStr<'a> for String {
+    fn into_fc_str(self) -> Option<Cow<'a, CStr>> {
+        CString::new(self).ok().map(Cow::Owned)
+    }
+}
+
+/// Convert a path to the file name Fontconfig expects.
+///
+/// File names are bytes to Fontconfig, so on Unix any path without a NUL byte is passed as it
+/// is, whether or not it is valid UTF-8. Elsewhere the path must be valid UTF-8.
+pub(crate) fn path_to_cstring(path: &Path) -> Option<CString> {
+    #[cfg(unix)]
+    let bytes = path.as_os_str().as_bytes();
+    #[cfg(not(unix))]
+    let bytes = path.to_str()?.as_bytes();
+    CString::new(bytes).ok()
+}
+
+/// Convert a file name returned by Fontconfig to a path.
+///
+/// On Unix the bytes are kept as they are, elsewhere invalid UTF-8 is replaced.
+pub(crate) fn cstr_to_path(s: &CStr) -> PathBuf {
+    #[cfg(unix)]
+    return PathBuf::from(OsStr::from_bytes(s.to_bytes()));
+    #[cfg(not(unix))]
+    return PathBuf::from(&*s.to_string_lossy());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_strings() {
+        assert!(matches!(c"serif".into_fc_str(), Some(Cow::Borrowed(s)) if s == c"serif"));
+        assert_eq!(&*"serif".into_fc_str().unwrap(), c"serif");
+        assert_eq!(&*String::from("serif").into_fc_str().unwrap(), c"serif");
+        assert_eq!(&*CString::from(c"serif").into_fc_str().unwrap(), c"serif");
+    }
+
+    #[test]
+    fn reject_nul() {
+        assert!("se\0rif".into_fc_str().is_none());
+        assert!(String::from("se\0rif").into_fc_str().is_none());
+    }
+}
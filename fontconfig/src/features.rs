@@ -0,0 +1,102 @@
+//! Finding the fonts that support an OpenType feature.
+
+use crate::variation::FeatureSetting;
+use crate::{list_fonts, Font, Fontconfig, InvalidTag, ObjectSet, Pattern, Tag};
+use crate::{FC_CAPABILITY, FC_FILE, FC_FONT_FEATURES, FC_FULLNAME, FC_INDEX};
+
+impl Fontconfig {
+    /// List the installed fonts that support the OpenType feature `feature`, e.g. `"smcp"` for
+    /// small capitals.
+    ///
+    /// Fonts whose "fontfeatures" element enables the feature are accepted as configured.
+    /// Settings that disable it, such as `-smcp` or `smcp=0`, do not count. Otherwise a font is
+    /// accepted if its "capability" element shows it has OpenType layout tables, which does not
+    /// tell which features they define, so fonts lacking the feature are included too.
+    ///
+    /// With the `ttf-parser` feature, the GSUB and GPOS tables of those fonts are also read to
+    /// keep only the fonts defining the feature. This is slow with many fonts installed, so the
+    /// result is worth caching.
+    pub fn fonts_with_feature(&self, feature: &str) -> Result<Vec<Font>, InvalidTag> {
+        let feature: Tag = feature.parse()?;
+
+        let mut objects = ObjectSet::new(self);
+        for object in [
+            FC_FULLNAME,
+            FC_FILE,
+            FC_INDEX,
+            FC_CAPABILITY,
+            FC_FONT_FEATURES,
+        ] {
+            objects.add(object);
+        }
+        let fonts = list_fonts(&Pattern::new(self), Some(&objects));
+        Ok(fonts
+            .iter()
+            .filter(|pattern| {
+                let configured = pattern
+                    .setting_strings(FC_FONT_FEATURES)
+                    .any(|setting| enabled_feature(setting) == Some(feature));
+                configured || (has_layout_tables(pattern) && defines_feature(pattern, feature))
+            })
+            .filter_map(|pattern| Font::from_pattern(&pattern))
+            .collect())
+    }
+}
+
+/// Returns the feature a setting of the "fontfeatures" element turns on, or `None` if it turns
+/// a feature off or cannot be parsed.
+fn enabled_feature(setting: &str) -> Option<Tag> {
+    FeatureSetting::parse(setting)
+        .filter(|setting| setting.value != 0)
+        .map(|setting| setting.tag)
+}
+
+/// Returns `false` if the "capability" element shows the font has no OpenType layout tables.
+fn has_layout_tables(pattern: &Pattern) -> bool {
+    match pattern.get_string(FC_CAPABILITY) {
+        Some(capability) => capability.contains("otlayout:"),
+        // Older versions of Fontconfig do not always record the capability.
+        None => true,
+    }
+}
+
+#[cfg(feature = "ttf-parser")]
+fn defines_feature(pattern: &Pattern, feature: Tag) -> bool {
+    crate::ttf::supports_feature(pattern, feature)
+}
+
+/// Without `ttf-parser` the tables cannot be read, so every font with them is accepted.
+#[cfg(not(feature = "ttf-parser"))]
+fn defines_feature(_: &Pattern, _: Tag) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fonts_with_feature() {
+        let fc = Fontconfig::new().unwrap();
+        let fonts = fc.fonts_with_feature("kern").unwrap();
+        assert!(fonts.iter().any(|font| font.name == "DejaVu Sans"));
+        assert!(fc.fonts_with_feature("toolong").is_err());
+        #[cfg(feature = "ttf-parser")]
+        assert!(fc.fonts_with_feature("zzzz").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_feature_settings() {
+        let smcp = Some(Tag::new(b"smcp"));
+        assert_eq!(enabled_feature("smcp"), smcp);
+        assert_eq!(enabled_feature(" +smcp "), smcp);
+        assert_eq!(enabled_feature("smcp=1"), smcp);
+        assert_eq!(enabled_feature("smcp=on"), smcp);
+        assert_eq!(enabled_feature("'smcp'[3:5]=2"), smcp);
+        assert_eq!(enabled_feature("-smcp"), None);
+        assert_eq!(enabled_feature("smcp=0"), None);
+        assert_eq!(enabled_feature("smcp=off"), None);
+        assert_eq!(enabled_feature("smcp=x"), None);
+        assert_eq!(enabled_feature("smcp[3:5"), None);
+    }
+}
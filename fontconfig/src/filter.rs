@@ -0,0 +1,234 @@
+//! Rules hiding installed fonts from the results of queries.
+
+use crate::{FontFormat, Pattern, FC_FAMILY, FC_FONTVERSION, FC_FOUNDRY};
+
+/// Lists of families, foundries, files and formats that fonts must, or must not, belong to.
+///
+/// This lets an application hide fonts, for example on a kiosk that should only offer its own
+/// fonts, or where licensing rules out some foundries or formats, without editing the system
+/// configuration. An empty allow list allows everything, and a font must pass every list.
+/// Families and foundries are compared ignoring case and spaces, as Fontconfig does. Files are
+/// matched against glob patterns in which `*` matches any run of characters, including `/`,
+/// and `?` matches any one character; bytes of a path that are not valid UTF-8 only match
+/// `*` and `?`.
+///
+/// ```
+/// use fontconfig::{FontFilter, FontQuery, Fontconfig};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let query = FontQuery {
+///     families: vec!["sans-serif".into()],
+///     filter: FontFilter {
+///         deny_families: vec!["Comic Sans MS".into()],
+///         deny_files: vec!["/usr/share/fonts/X11/*".into()],
+///         ..FontFilter::default()
+///     },
+///     ..FontQuery::default()
+/// };
+/// let fonts = query.matches(&fc);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FontFilter {
+    /// If not empty, only fonts with one of these family names are shown.
+    pub allow_families: Vec<String>,
+    /// Fonts with any of these family names are hidden.
+    pub deny_families: Vec<String>,
+    /// If not empty, only fonts from one of these foundries are shown.
+    pub allow_foundries: Vec<String>,
+    /// Fonts from any of these foundries are hidden.
+    pub deny_foundries: Vec<String>,
+    /// If not empty, only fonts in files matching one of these globs are shown.
+    pub allow_files: Vec<String>,
+    /// Fonts in files matching any of these globs are hidden.
+    pub deny_files: Vec<String>,
+    /// If not empty, only fonts in one of these formats are shown, e.g. `TrueType` and `CFF`
+    /// to leave out bitmap and Type 1 fonts.
+    pub allow_formats: Vec<FontFormat>,
+    /// Fonts in any of these formats are hidden.
+    pub deny_formats: Vec<FontFormat>,
+    /// If set, only fonts whose version is at least this are shown, as a 16.16 fixed-point
+    /// number like [`FontInfo::version`], e.g. `0x0002_0000` for version 2.0. Fonts without a
+    /// version are hidden.
+    ///
+    /// [`FontInfo::version`]: crate::FontInfo::version
+    pub min_version: Option<i32>,
+}
+
+impl FontFilter {
+    /// Returns `true` if the font described by `pattern` passes this filter.
+    ///
+    /// The pattern must hold the "family", "foundry", "file", "fontformat" and "fontversion"
+    /// elements for them to be checked; a listing made with an object set must include them.
+    pub fn accepts(&self, pattern: &Pattern) -> bool {
+        let families: Vec<&str> = (0..)
+            .map_while(|n| pattern.get_string_at(FC_FAMILY, n))
+            .collect();
+        let foundry = pattern.get_string(FC_FOUNDRY);
+        let file = pattern.file_path();
+        let format = pattern.format().ok();
+
+        let name_in = |names: &[String], name: &str| names.iter().any(|n| same_name(n, name));
+        let file_in = |globs: &[String], file: &str| globs.iter().any(|g| glob_match(g, file));
+
+        check(&self.allow_families, &self.deny_families, |list| {
+            families.iter().any(|family| name_in(list, family))
+        }) && check(&self.allow_foundries, &self.deny_foundries, |list| {
+            foundry.is_some_and(|foundry| name_in(list, foundry))
+        }) && check(&self.allow_files, &self.deny_files, |list| {
+            file.as_ref()
+                .is_some_and(|file| file_in(list, &file.to_string_lossy()))
+        }) && check(&self.allow_formats, &self.deny_formats, |list| {
+            format.is_some_and(|format| list.contains(&format))
+        }) && self.min_version.map_or(true, |min| {
+            pattern
+                .get_int(FC_FONTVERSION)
+                .is_some_and(|version| version >= min)
+        })
+    }
+
+    /// Returns `true` if this filter hides no fonts.
+    pub fn is_empty(&self) -> bool {
+        *self == FontFilter::default()
+    }
+}
+
+fn check<T>(allow: &[T], deny: &[T], contains: impl Fn(&[T]) -> bool) -> bool {
+    (allow.is_empty() || contains(allow)) && !contains(deny)
+}
+
+/// Compare names ignoring case and spaces, like `FcStrCmpIgnoreBlanksAndCase`.
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    let fold = |s: &str| {
+        s.chars()
+            .filter(|c| *c != ' ')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    fold(a) == fold(b)
+}
+
+fn glob_match(glob: &str, s: &str) -> bool {
+    let (glob, s): (Vec<char>, Vec<char>) = (glob.chars().collect(), s.chars().collect());
+    let (mut g, mut i) = (0, 0);
+    // The position of the last `*` and the character it currently matches up to.
+    let mut star = None;
+    while i < s.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, i));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == s[i] => {
+                g += 1;
+                i += 1;
+            }
+            _ => match star {
+                Some((star_g, star_i)) => {
+                    star = Some((star_g, star_i + 1));
+                    g = star_g + 1;
+                    i = star_i + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fontconfig, FC_FILE};
+
+    #[test]
+    fn match_globs() {
+        assert!(glob_match(
+            "/usr/share/fonts/*",
+            "/usr/share/fonts/X11/misc/6x13.pcf.gz"
+        ));
+        assert!(glob_match(
+            "*.pcf.gz",
+            "/usr/share/fonts/X11/misc/6x13.pcf.gz"
+        ));
+        assert!(glob_match("*/DejaVuSans?ttf", "/fonts/DejaVuSans.ttf"));
+        assert!(!glob_match("*.ttf", "/fonts/DejaVuSans.otf"));
+        assert!(!glob_match("/fonts", "/fonts/DejaVuSans.ttf"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn filter_patterns() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FAMILY, c"DejaVu Sans");
+        pattern.add_string(FC_FILE, c"/fonts/DejaVuSans.ttf");
+        assert!(FontFilter::default().accepts(&pattern));
+
+        let deny = FontFilter {
+            deny_families: vec!["dejavusans".into()],
+            ..FontFilter::default()
+        };
+        assert!(!deny.accepts(&pattern));
+
+        let allow = FontFilter {
+            allow_files: vec!["/fonts/*".into()],
+            allow_foundries: vec!["Example".into()],
+            ..FontFilter::default()
+        };
+        assert!(!allow.accepts(&pattern));
+        pattern.add_string(FC_FOUNDRY, c"example");
+        assert!(allow.accepts(&pattern));
+
+        // Files whose path is not valid UTF-8 are still matched against the globs.
+        let mut non_utf8 = Pattern::new(&fc);
+        non_utf8.add_string(FC_FILE, c"/fonts/caf\xe9.ttf");
+        let deny_files = FontFilter {
+            deny_files: vec!["/fonts/*.ttf".into()],
+            ..FontFilter::default()
+        };
+        assert!(!deny_files.accepts(&non_utf8));
+        let allow_files = FontFilter {
+            allow_files: vec!["/fonts/caf?.ttf".into()],
+            ..FontFilter::default()
+        };
+        assert!(allow_files.accepts(&non_utf8));
+    }
+
+    #[test]
+    fn filter_metadata() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(crate::FC_FONTFORMAT, c"TrueType");
+        pattern.add_integer(FC_FONTVERSION, 0x0002_0025);
+
+        let outline = FontFilter {
+            allow_formats: vec![FontFormat::TrueType, FontFormat::CFF],
+            min_version: Some(0x0002_0000),
+            ..FontFilter::default()
+        };
+        assert!(outline.accepts(&pattern));
+        let newer = FontFilter {
+            min_version: Some(0x0003_0000),
+            ..FontFilter::default()
+        };
+        assert!(!newer.accepts(&pattern));
+        let no_truetype = FontFilter {
+            deny_formats: vec![FontFormat::TrueType],
+            ..FontFilter::default()
+        };
+        assert!(!no_truetype.accepts(&pattern));
+        assert!(!outline.accepts(&Pattern::new(&fc)));
+
+        // Only DejaVu fonts are installed for the tests, which are all TrueType.
+        let query = crate::FontQuery {
+            families: vec!["DejaVu Sans".into()],
+            filter: FontFilter {
+                allow_formats: vec![FontFormat::CFF],
+                ..FontFilter::default()
+            },
+            ..crate::FontQuery::default()
+        };
+        assert!(query.list(&fc).is_empty());
+        assert!(query.matches(&fc).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+//! Integration with faces loaded by the `freetype-rs` crate.
+
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::Path;
+use std::ptr;
+
+use freetype::freetype_sys as ft_sys;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, path_to_cstring, sys, CharSet, Fontconfig, Pattern, FC_FT_FACE};
+
+impl<'fc> Pattern<'fc> {
+    /// Query a face that has already been loaded by FreeType, returning a pattern describing it.
+    ///
+    /// `path` and `index` are recorded in the pattern as its file and index, as the face itself
+    /// does not know where it was loaded from. Returns `None` if Fontconfig cannot describe the
+    /// face.
+    pub fn from_ft_face(
+        fc: &'fc Fontconfig,
+        face: &freetype::Face,
+        path: impl AsRef<Path>,
+        index: i32,
+    ) -> Option<Pattern<'fc>> {
+        let file = path_to_cstring(path.as_ref())?;
+        let pat = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcFreeTypeQueryFace,
+                face.raw() as *const _ as *mut c_void,
+                file.as_ptr() as *const u8,
+                index,
+                ptr::null_mut()
+            )
+        };
+        if pat.is_null() {
+            None
+        } else {
+            Some(Pattern { pat, fc })
+        }
+    }
+
+    /// Get the FreeType face stored in the "ftface" element of this pattern.
+    ///
+    /// Fontconfig never creates faces itself, but libraries such as Cairo store the face they have
+    /// loaded in the patterns they hand out. The returned guard borrows this pattern and
+    /// dereferences to the face; clone the `Face` to keep it after the pattern is gone.
+    ///
+    /// The pattern does not keep the face alive. Only unsafe code can store a face in a pattern,
+    /// with `FcPatternAddFTFace` or by wrapping a pattern from another library, and it must keep
+    /// the face, and the data of a face loaded from memory, alive for as long as the pattern
+    /// refers to it, without using the face from another thread.
+    pub fn ft_face(&self) -> Option<PatternFace<'_>> {
+        let mut face: *mut c_void = ptr::null_mut();
+        let res = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcPatternGetFTFace,
+                self.pat,
+                FC_FT_FACE.as_ptr(),
+                0,
+                &mut face
+            )
+        };
+        if res != sys::FcResultMatch || face.is_null() {
+            return None;
+        }
+
+        let face = face as ft_sys::FT_Face;
+        unsafe {
+            if ft_sys::FT_Reference_Face(face) != ft_sys::FT_Err_Ok {
+                return None;
+            }
+            let library = (*(*face).glyph).library;
+            Some(PatternFace {
+                face: freetype::Face::from_raw(library, face, None),
+                _pattern: PhantomData,
+            })
+        }
+    }
+}
+
+/// A FreeType face stored in a pattern, borrowed from it by [`Pattern::ft_face`].
+pub struct PatternFace<'a> {
+    face: freetype::Face,
+    _pattern: PhantomData<&'a Pattern<'a>>,
+}
+
+impl Deref for PatternFace<'_> {
+    type Target = freetype::Face;
+
+    fn deref(&self) -> &freetype::Face {
+        &self.face
+    }
+}
+
+impl CharSet {
+    /// Compute the characters covered by a face that has already been loaded by FreeType.
+    ///
+    /// Unlike querying a file, this works for faces loaded from memory, such as web fonts or
+    /// fonts embedded in a document. Returns `None` if Fontconfig cannot read the character
+    /// map of the face.
+    pub fn from_ft_face(fc: &Fontconfig, face: &freetype::Face) -> Option<CharSet> {
+        let fcset = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcFreeTypeCharSet,
+                face.raw() as *const _ as *mut c_void,
+                ptr::null_mut()
+            )
+        };
+        (!fcset.is_null()).then(|| unsafe { CharSet::from_raw(fc, fcset) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FC_FAMILY;
+
+    #[test]
+    fn query_ft_face() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let index = font.index.unwrap_or(0);
+
+        let library = freetype::Library::init().unwrap();
+        let face = library.new_face(&font.path, index as isize).unwrap();
+        let pattern = Pattern::from_ft_face(&fc, &face, &font.path, index).unwrap();
+        assert_eq!(pattern.get_string(FC_FAMILY), Some("DejaVu Sans"));
+        assert_eq!(pattern.filename(), font.path.to_str());
+        assert_eq!(pattern.face_index(), Some(index));
+    }
+
+    #[test]
+    fn ft_face_char_set() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let data = std::fs::read(&font.path).unwrap();
+        let library = freetype::Library::init().unwrap();
+        let face = library
+            .new_memory_face(data, font.index.unwrap_or(0) as isize)
+            .unwrap();
+
+        let chars = CharSet::from_ft_face(&fc, &face).unwrap();
+        assert!(chars.contains('A'));
+        assert!(chars.contains('ж'));
+        assert!(!chars.contains('あ'));
+        let queried = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+        assert_eq!(Some(chars), queried.char_set());
+    }
+
+    #[test]
+    fn get_ft_face() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let library = freetype::Library::init().unwrap();
+        let face = library
+            .new_face(&font.path, font.index.unwrap_or(0) as isize)
+            .unwrap();
+
+        let mut pattern = Pattern::new(&fc);
+        assert!(pattern.ft_face().is_none());
+        unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcPatternAddFTFace,
+                pattern.as_mut_ptr(),
+                FC_FT_FACE.as_ptr(),
+                face.raw() as *const _ as *const c_void
+            );
+        }
+
+        let stored = pattern.ft_face().unwrap();
+        assert_eq!(stored.raw() as *const _, face.raw() as *const _);
+        assert_eq!(stored.family_name().as_deref(), Some("DejaVu Sans"));
+        drop(stored);
+        assert!(pattern.clone().share().ft_face().is_none());
+        drop(pattern);
+        assert_eq!(face.family_name().as_deref(), Some("DejaVu Sans"));
+    }
+}
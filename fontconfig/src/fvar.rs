@@ -0,0 +1,116 @@
+//! Checking variation settings against the axes of a variable font, using the `skrifa` crate.
+
+use std::io;
+
+use skrifa::{FontRef, MetadataProvider};
+
+use crate::{AxisSetting, Pattern, Tag};
+
+impl Pattern<'_> {
+    /// Resolve the variation settings of this font pattern against the axes the font defines.
+    ///
+    /// The font file is read to find its variation axes. Each axis starts at its default value,
+    /// or at the coordinates of the named instance selected by the index, and is then set from
+    /// the "fontvariations" element of this pattern, clamped to the range the axis supports.
+    /// Settings for axes the font does not have are dropped.
+    ///
+    /// The result holds one setting per axis, in the order the font defines them, and is empty
+    /// for fonts that are not variable.
+    pub fn axis_settings(&self) -> io::Result<Vec<AxisSetting>> {
+        let data = self.map_file()?;
+        let index = data.index() as u32;
+        let font = FontRef::from_index(&data, index & 0xFFFF)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        Ok(resolve_axes(&font, index >> 16, &self.font_variations()))
+    }
+}
+
+fn resolve_axes(
+    font: &FontRef,
+    named_instance: u32,
+    requested: &[AxisSetting],
+) -> Vec<AxisSetting> {
+    let axes = font.axes();
+    let mut values: Vec<f32> = axes.iter().map(|axis| axis.default_value()).collect();
+
+    // Named instances are numbered from 1, with 0 meaning the default instance.
+    let instance = named_instance
+        .checked_sub(1)
+        .and_then(|i| font.named_instances().get(i as usize));
+    if let Some(instance) = instance {
+        for (value, coord) in values.iter_mut().zip(instance.user_coords()) {
+            *value = coord;
+        }
+    }
+
+    for setting in requested {
+        let tag = skrifa::Tag::new(&setting.tag.to_bytes());
+        if let Some(axis) = axes.iter().find(|axis| axis.tag() == tag) {
+            // Unlike `f32::clamp`, this does not panic on a malformed axis whose minimum
+            // exceeds its maximum.
+            values[axis.index()] = setting.value.max(axis.min_value()).min(axis.max_value());
+        }
+    }
+
+    axes.iter()
+        .zip(values)
+        .map(|(axis, value)| AxisSetting {
+            tag: Tag::new(&axis.tag().to_be_bytes()),
+            value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::variable_font;
+    use crate::Fontconfig;
+
+    #[test]
+    fn clamp_to_axes() {
+        let data = variable_font(
+            &[
+                (b"wght", 100.0, 400.0, 900.0),
+                (b"wdth", 75.0, 100.0, 100.0),
+            ],
+            &[("Bold Condensed", &[700.0, 75.0])],
+        );
+        let font = FontRef::new(&data).unwrap();
+        let settings = |named_instance, requested: &[&str]| -> Vec<String> {
+            let requested: Vec<AxisSetting> =
+                requested.iter().map(|s| s.parse().unwrap()).collect();
+            resolve_axes(&font, named_instance, &requested)
+                .iter()
+                .map(|setting| format!("{}={}", setting.tag, setting.value))
+                .collect()
+        };
+
+        assert_eq!(settings(0, &[]), ["wght=400", "wdth=100"]);
+        // Values outside an axis are clamped to it, and unknown axes are dropped.
+        assert_eq!(
+            settings(0, &["wdth=50", "wght=1000", "slnt=-10"]),
+            ["wght=900", "wdth=75"]
+        );
+        assert_eq!(settings(0, &["wght=50"]), ["wght=100", "wdth=100"]);
+        // A named instance sets the starting point, which requested values override.
+        assert_eq!(settings(1, &[]), ["wght=700", "wdth=75"]);
+        assert_eq!(settings(1, &["wght=350.5"]), ["wght=350.5", "wdth=75"]);
+        assert_eq!(settings(2, &[]), ["wght=400", "wdth=100"]);
+
+        // A malformed axis whose minimum exceeds its maximum does not panic.
+        let data = variable_font(&[(b"opsz", 72.0, 12.0, 8.0)], &[]);
+        let font = FontRef::new(&data).unwrap();
+        let inverted = resolve_axes(&font, 0, &["opsz=20".parse().unwrap()]);
+        assert_eq!(inverted[0].value, 8.0);
+    }
+
+    #[test]
+    fn static_font_has_no_axes() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+        assert_eq!(pattern.axis_settings().unwrap(), []);
+        assert!(Pattern::new(&fc).axis_settings().is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Helpers for setting up HarfBuzz shaping from a matched pattern.
+//!
+//! The types in this module are layout compatible with their HarfBuzz counterparts, so slices of
+//! them can be passed directly to functions such as `hb_shape` and `hb_font_set_variations` by
+//! casting the pointer, whichever HarfBuzz bindings are in use.
+
+use std::path::PathBuf;
+
+use crate::variation::FeatureSetting;
+use crate::{AxisSetting, Pattern, FC_FONT_FEATURES};
+
+/// The start of a feature range covering the whole buffer (`HB_FEATURE_GLOBAL_START`).
+pub const FEATURE_GLOBAL_START: u32 = 0;
+/// The end of a feature range covering the whole buffer (`HB_FEATURE_GLOBAL_END`).
+pub const FEATURE_GLOBAL_END: u32 = u32::MAX;
+
+/// An OpenType feature setting, layout compatible with `hb_feature_t`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HbFeature {
+    /// The feature tag, as an `hb_tag_t`.
+    pub tag: u32,
+    /// The value of the feature. 0 disables it, 1 enables it, and larger values select
+    /// alternates.
+    pub value: u32,
+    /// The cluster at which the feature starts applying.
+    pub start: u32,
+    /// The cluster at which the feature stops applying.
+    pub end: u32,
+}
+
+/// A variation axis setting, layout compatible with `hb_variation_t`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HbVariation {
+    /// The axis tag, as an `hb_tag_t`.
+    pub tag: u32,
+    /// The value of the axis.
+    pub value: f32,
+}
+
+/// The location of the face to load with `hb_face_create`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HbFaceSource {
+    /// The font file to load into a blob.
+    pub path: PathBuf,
+    /// The index of the face within the file.
+    pub index: u32,
+    /// The named instance of a variable font to select with `hb_font_set_var_named_instance`.
+    pub named_instance: Option<u32>,
+}
+
+impl HbFeature {
+    /// Parse a feature in the syntax accepted by `hb_feature_from_string`, such as `smcp`,
+    /// `-liga`, `aalt=2` or `kern[3:5]=0`.
+    pub fn parse(s: &str) -> Option<HbFeature> {
+        FeatureSetting::parse(s).map(|setting| HbFeature {
+            tag: setting.tag.to_u32(),
+            value: setting.value,
+            start: setting.start,
+            end: setting.end,
+        })
+    }
+}
+
+impl HbVariation {
+    /// Parse a variation in the syntax accepted by `hb_variation_from_string`, such as
+    /// `wght=650`.
+    pub fn parse(s: &str) -> Option<HbVariation> {
+        let setting: AxisSetting = s.parse().ok()?;
+        Some(HbVariation::from(setting))
+    }
+}
+
+impl From<AxisSetting> for HbVariation {
+    fn from(setting: AxisSetting) -> HbVariation {
+        HbVariation {
+            tag: setting.tag.to_u32(),
+            value: setting.value,
+        }
+    }
+}
+
+impl Pattern<'_> {
+    /// Get the OpenType features requested by the "fontfeatures" element of this pattern.
+    ///
+    /// Settings that cannot be parsed are skipped.
+    pub fn hb_features(&self) -> Vec<HbFeature> {
+        self.setting_strings(FC_FONT_FEATURES)
+            .filter_map(HbFeature::parse)
+            .collect()
+    }
+
+    /// Get the variation axis settings requested by the "fontvariations" element of this pattern.
+    ///
+    /// Settings that cannot be parsed are skipped.
+    pub fn hb_variations(&self) -> Vec<HbVariation> {
+        self.font_variations()
+            .into_iter()
+            .map(HbVariation::from)
+            .collect()
+    }
+
+    /// Get the file and face index to create an `hb_face_t` for this pattern.
+    ///
+    /// Fontconfig stores the named instance of a variable font in the upper 16 bits of the index,
+    /// which HarfBuzz expects to be selected on the font instead of the face. Returns `None` if
+    /// the pattern has no file or its index is negative.
+    pub fn hb_face_source(&self) -> Option<HbFaceSource> {
+        let index = u32::try_from(self.face_index().unwrap_or(0)).ok()?;
+        let named_instance = index >> 16;
+        Some(HbFaceSource {
+            path: self.file_path()?,
+            index: index & 0xFFFF,
+            named_instance: named_instance.checked_sub(1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn tag(t: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*t)
+    }
+
+    #[test]
+    fn parse_features() {
+        let global = |tag, value| HbFeature {
+            tag,
+            value,
+            start: FEATURE_GLOBAL_START,
+            end: FEATURE_GLOBAL_END,
+        };
+        assert_eq!(HbFeature::parse("smcp"), Some(global(tag(b"smcp"), 1)));
+        assert_eq!(HbFeature::parse("-liga"), Some(global(tag(b"liga"), 0)));
+        assert_eq!(HbFeature::parse("aalt=2"), Some(global(tag(b"aalt"), 2)));
+        assert_eq!(
+            HbFeature::parse("'cv01' = off"),
+            Some(global(tag(b"cv01"), 0))
+        );
+        assert_eq!(
+            HbFeature::parse("kern[3:5]=0"),
+            Some(HbFeature {
+                tag: tag(b"kern"),
+                value: 0,
+                start: 3,
+                end: 5
+            })
+        );
+        assert_eq!(
+            HbFeature::parse("kern[3]"),
+            Some(HbFeature {
+                tag: tag(b"kern"),
+                value: 1,
+                start: 3,
+                end: 4
+            })
+        );
+        assert_eq!(HbFeature::parse("toolong"), None);
+        assert_eq!(HbFeature::parse(""), None);
+    }
+
+    #[test]
+    fn pattern_settings() {
+        let fc = crate::Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FONT_FEATURES, c"smcp,-liga");
+        pattern.add_string(FC_FONT_FEATURES, c"ss01");
+        pattern.add_string(crate::FC_FONT_VARIATIONS, c"wght=650,wdth=75");
+        pattern.add_string(crate::FC_FILE, c"/fonts/variable.ttf");
+        pattern.add_integer(crate::FC_INDEX, (3 << 16) | 1);
+
+        let features: Vec<_> = pattern.hb_features().iter().map(|f| f.tag).collect();
+        assert_eq!(features, [tag(b"smcp"), tag(b"liga"), tag(b"ss01")]);
+        assert_eq!(
+            pattern.hb_variations(),
+            [
+                HbVariation {
+                    tag: tag(b"wght"),
+                    value: 650.0
+                },
+                HbVariation {
+                    tag: tag(b"wdth"),
+                    value: 75.0
+                },
+            ]
+        );
+        assert_eq!(
+            pattern.hb_face_source(),
+            Some(HbFaceSource {
+                path: PathBuf::from("/fonts/variable.ttf"),
+                index: 1,
+                named_instance: Some(2),
+            })
+        );
+
+        pattern.del(crate::FC_INDEX);
+        pattern.add_integer(crate::FC_INDEX, -1);
+        assert_eq!(pattern.hb_face_source(), None);
+    }
+
+    #[test]
+    fn parse_variations() {
+        assert_eq!(
+            HbVariation::parse("wght=650"),
+            Some(HbVariation {
+                tag: tag(b"wght"),
+                value: 650.0
+            })
+        );
+        assert_eq!(HbVariation::parse("wght"), None);
+    }
+}
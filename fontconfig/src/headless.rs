@@ -0,0 +1,142 @@
+//! Checks for environments without fonts, such as containers running server-side renderers.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, path_to_cstring, sys, Error, Fontconfig, Result};
+
+/// Error returned when Fontconfig knows no fonts at all.
+///
+/// It lists where Fontconfig looked, to help tell a missing font package from a configuration
+/// that points at the wrong directories.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoFonts {
+    /// The font directories of the configuration.
+    pub font_dirs: Vec<PathBuf>,
+    /// The configuration files that were loaded.
+    pub config_files: Vec<PathBuf>,
+}
+
+impl fmt::Display for NoFonts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("no fonts found")?;
+        if self.config_files.is_empty() {
+            f.write_str("; no configuration file was loaded, check FONTCONFIG_FILE")?;
+        } else {
+            write!(f, "; configuration files: {:?}", self.config_files)?;
+        }
+        if self.font_dirs.is_empty() {
+            f.write_str("; no font directories are configured")?;
+        } else {
+            write!(f, "; searched font directories: {:?}", self.font_dirs)?;
+        }
+        f.write_str("; install a font package or register a directory of bundled fonts")
+    }
+}
+
+impl std::error::Error for NoFonts {}
+
+impl Fontconfig {
+    /// Check that Fontconfig knows at least one font.
+    ///
+    /// Minimal containers often ship no fonts, in which case every match fails or returns an
+    /// empty pattern and text silently fails to render. Calling this at startup turns that into
+    /// an error describing where Fontconfig looked.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// if let Err(err) = fc.ensure_fonts() {
+    ///     eprintln!("{}", err);
+    /// }
+    /// ```
+    pub fn ensure_fonts(&self) -> Result<()> {
+        if self.font_count() > 0 {
+            return Ok(());
+        }
+        Err(Error::NoFonts(NoFonts {
+            font_dirs: self.font_dirs(),
+            config_files: self.config_files(),
+        }))
+    }
+
+    /// Use the fonts in `dir`, such as fonts bundled with the application, if Fontconfig knows
+    /// no other fonts.
+    ///
+    /// Where system fonts are installed they are used as before, and `dir` is not scanned.
+    /// Otherwise every font in `dir` and its subdirectories is made available to this handle
+    /// alone, so that text renders the same way in every container without system fonts.
+    ///
+    /// Returns an error as [`Fontconfig::ensure_fonts`] does if there are still no fonts.
+    pub fn use_fallback_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        if self.font_count() > 0 {
+            return Ok(());
+        }
+        if let Some(dir) = path_to_cstring(dir.as_ref()) {
+            let config = self.private_config()?;
+            unsafe {
+                ffi_dispatch!(
+                    LIB,
+                    FcConfigAppFontAddDir,
+                    config,
+                    dir.as_ptr() as *const u8
+                );
+            }
+        }
+        self.ensure_fonts()
+    }
+
+    /// The number of fonts known, whether installed or added by the application.
+    fn font_count(&self) -> usize {
+        [sys::FcSetSystem, sys::FcSetApplication]
+            .into_iter()
+            .map(|set| unsafe {
+                let set = ffi_dispatch!(LIB, FcConfigGetFonts, self.config, set);
+                if set.is_null() || (*set).nfont < 0 {
+                    0
+                } else {
+                    (*set).nfont as usize
+                }
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::dejavu_dir;
+    use crate::{Pattern, SandboxFonts, FC_FAMILY};
+
+    #[test]
+    fn no_fonts() {
+        let fc = Fontconfig::new().unwrap();
+        fc.ensure_fonts().unwrap();
+
+        let mut fc = SandboxFonts {
+            cache_dir: Some(std::env::temp_dir().join("fontconfig-rs-headless-test")),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        }
+        .build()
+        .unwrap();
+        match fc.ensure_fonts() {
+            Err(Error::NoFonts(err)) => {
+                assert!(err.font_dirs.is_empty());
+                assert!(err.to_string().contains("no font directories"));
+            }
+            other => panic!("expected NoFonts, got {:?}", other),
+        }
+        assert!(fc.use_fallback_dir("/nonexistent").is_err());
+
+        fc.use_fallback_dir(dejavu_dir()).unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "DejaVu Sans");
+        assert_eq!(pat.font_match().name(), Some("DejaVu Sans"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Conversions between ICU locales and Fontconfig language tags.
+
+use icu_locid::LanguageIdentifier;
+
+use crate::{LangSet, Pattern, FC_LANG};
+
+/// Fontconfig tags for languages whose orthography depends on the script, keyed by language,
+/// script and default territory.
+const SCRIPT_TAGS: &[(&str, &str, &str)] = &[
+    ("az", "Latn", "az-az"),
+    ("az", "Arab", "az-ir"),
+    ("ku", "Latn", "ku-tr"),
+    ("ku", "Arab", "ku-iq"),
+    ("mn", "Cyrl", "mn-mn"),
+    ("mn", "Mong", "mn-cn"),
+    ("pa", "Guru", "pa"),
+    ("pa", "Arab", "pa-pk"),
+    ("sr", "Cyrl", "sr"),
+    ("sr", "Latn", "sh"),
+];
+
+/// Convert a locale to the Fontconfig language tag for its orthography.
+///
+/// The script subtag is taken into account where Fontconfig distinguishes orthographies by
+/// territory instead: `zh-Hans` becomes `zh-cn`, `zh-Hant` becomes `zh-tw` (or `zh-hk` and
+/// `zh-mo` in those regions), and `sr-Latn` becomes `sh`.
+pub fn fc_lang_from_locale(locale: &LanguageIdentifier) -> String {
+    let lang = locale.language.as_str();
+    let script = locale.script.as_ref().map(|script| script.as_str());
+    let region = locale
+        .region
+        .as_ref()
+        .map(|region| region.as_str().to_ascii_lowercase());
+
+    if lang == "zh" {
+        let region = region.as_deref();
+        let territory = match (script, region) {
+            (Some("Hant"), Some(region @ ("hk" | "mo"))) => region,
+            (Some("Hant"), _) => "tw",
+            (Some("Hans"), Some("sg")) => "sg",
+            (Some("Hans"), _) => "cn",
+            (_, Some(region @ ("cn" | "sg" | "tw" | "hk" | "mo"))) => region,
+            _ => "cn",
+        };
+        return format!("zh-{}", territory);
+    }
+
+    if let Some(script) = script {
+        let tag = SCRIPT_TAGS
+            .iter()
+            .find(|&&(l, s, _)| l == lang && s == script);
+        if let Some(&(_, _, tag)) = tag {
+            return tag.to_string();
+        }
+    }
+
+    match region {
+        Some(region) => format!("{}-{}", lang, region),
+        None => lang.to_string(),
+    }
+}
+
+/// Convert a BCP 47 language tag, such as `zh-Hant-HK`, to a Fontconfig language tag.
+///
+/// Returns `None` if `tag` is not a valid language tag.
+pub fn fc_lang_from_bcp47(tag: &str) -> Option<String> {
+    let locale: LanguageIdentifier = tag.parse().ok()?;
+    Some(fc_lang_from_locale(&locale))
+}
+
+/// Convert a Fontconfig language tag to a locale, adding the script subtag Fontconfig implies,
+/// e.g. `zh-tw` becomes `zh-Hant-TW` and `sh` becomes `sr-Latn`.
+///
+/// Returns `None` if `lang` is not a valid language tag.
+pub fn locale_from_fc_lang(lang: &str) -> Option<LanguageIdentifier> {
+    let lang = lang.to_ascii_lowercase();
+    let tag = match lang.as_str() {
+        "zh-cn" | "zh-sg" => format!("zh-Hans-{}", &lang[3..]),
+        "zh-tw" | "zh-hk" | "zh-mo" => format!("zh-Hant-{}", &lang[3..]),
+        _ => match SCRIPT_TAGS.iter().find(|&&(_, _, tag)| tag == lang) {
+            Some((lang, script, _)) => format!("{}-{}", lang, script),
+            None => lang,
+        },
+    };
+    tag.parse().ok()
+}
+
+impl LangSet {
+    /// Add the orthography of `locale` to this set.
+    pub fn add_locale(&mut self, locale: &LanguageIdentifier) {
+        self.add(fc_lang_from_locale(locale));
+    }
+
+    /// Returns `true` if this set contains the orthography of `locale`.
+    pub fn contains_locale(&self, locale: &LanguageIdentifier) -> bool {
+        self.contains(&fc_lang_from_locale(locale))
+    }
+
+    /// Returns the languages in this set as locales.
+    pub fn locales(&self) -> Vec<LanguageIdentifier> {
+        self.langs()
+            .iter()
+            .filter_map(|lang| locale_from_fc_lang(lang))
+            .collect()
+    }
+}
+
+impl Pattern<'_> {
+    /// Add the orthography of `locale` to the "lang" element of this pattern, so that fonts
+    /// supporting it are preferred.
+    pub fn add_locale(&mut self, locale: &LanguageIdentifier) {
+        self.add_string(FC_LANG, fc_lang_from_locale(locale));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fontconfig;
+
+    #[test]
+    fn locale_to_fc_lang() {
+        let convert = |tag: &str| fc_lang_from_bcp47(tag).unwrap();
+        assert_eq!(convert("en"), "en");
+        assert_eq!(convert("en-GB"), "en-gb");
+        assert_eq!(convert("zh-Hans"), "zh-cn");
+        assert_eq!(convert("zh-Hant"), "zh-tw");
+        assert_eq!(convert("zh-Hant-HK"), "zh-hk");
+        assert_eq!(convert("zh-SG"), "zh-sg");
+        assert_eq!(convert("sr-Latn-RS"), "sh");
+        assert_eq!(fc_lang_from_bcp47("not a tag"), None);
+    }
+
+    #[test]
+    fn fc_lang_to_locale() {
+        let convert = |lang: &str| locale_from_fc_lang(lang).unwrap().to_string();
+        assert_eq!(convert("en"), "en");
+        assert_eq!(convert("pt-br"), "pt-BR");
+        assert_eq!(convert("zh-tw"), "zh-Hant-TW");
+        assert_eq!(convert("zh-cn"), "zh-Hans-CN");
+        assert_eq!(convert("sh"), "sr-Latn");
+    }
+
+    #[test]
+    fn lang_set_locales() {
+        let fc = Fontconfig::new().unwrap();
+        let mut set = LangSet::new(&fc);
+        set.add_locale(&"zh-Hant".parse().unwrap());
+        assert!(set.contains("zh-tw"));
+        assert!(set.contains_locale(&"zh-Hant-TW".parse().unwrap()));
+        assert_eq!(set.locales(), ["zh-Hant-TW".parse().unwrap()]);
+    }
+}
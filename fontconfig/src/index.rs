@@ -0,0 +1,276 @@
+//! A prebuilt index of the installed fonts, keyed by family name.
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::path::PathBuf;
+
+use crate::{list_fonts, Error, FontFormat, Fontconfig, Generation, ObjectSet, Pattern};
+use crate::{
+    FC_COLOR, FC_DECORATIVE, FC_FAMILY, FC_FAMILYLANG, FC_FILE, FC_FONTFORMAT, FC_FONTVERSION,
+    FC_FOUNDRY, FC_FULLNAME, FC_INDEX, FC_POSTSCRIPT_NAME, FC_SCALABLE, FC_SLANT, FC_STYLE,
+    FC_VARIABLE, FC_WEIGHT, FC_WIDTH,
+};
+
+/// Summary information about an installed font.
+///
+/// This gathers the commonly used elements of a font pattern into one owned struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontInfo {
+    /// The family names of this font.
+    pub families: Vec<String>,
+    /// The languages of the family names, in the same order, e.g. "en".
+    pub family_langs: Vec<String>,
+    /// The style name of this font, e.g. "Bold Italic".
+    pub style: Option<String>,
+    /// The full name of this font, e.g. "DejaVu Sans Bold".
+    pub fullname: Option<String>,
+    /// The PostScript name of this font.
+    pub postscript_name: Option<String>,
+    /// The location of this font on the filesystem.
+    pub path: PathBuf,
+    /// The index of the font within the file.
+    pub index: Option<i32>,
+    /// The format of the font file.
+    pub format: Option<FontFormat>,
+    /// The version of the font, from its "head" table, as a 16.16 fixed-point number.
+    pub version: Option<i32>,
+    /// The foundry that made this font.
+    pub foundry: Option<String>,
+    /// The weight, e.g. `FC_WEIGHT_BOLD`, or `None` for a variable font covering a range.
+    pub weight: Option<i32>,
+    /// The slant, e.g. `FC_SLANT_ITALIC`.
+    pub slant: Option<i32>,
+    /// The width, e.g. `FC_WIDTH_CONDENSED`, or `None` for a variable font covering a range.
+    pub width: Option<i32>,
+    /// Whether the font is scalable rather than a bitmap font.
+    pub scalable: bool,
+    /// Whether the font has color glyphs.
+    pub color: bool,
+    /// Whether this is a variable font rather than one of its instances.
+    pub variable: bool,
+    /// Whether the style is decorative, e.g. "Outline" or "Shadow".
+    pub decorative: bool,
+}
+
+/// The elements read by [`FontInfo::from_pattern`].
+const OBJECTS: [&CStr; 17] = [
+    FC_FAMILY,
+    FC_FAMILYLANG,
+    FC_STYLE,
+    FC_FULLNAME,
+    FC_POSTSCRIPT_NAME,
+    FC_FILE,
+    FC_INDEX,
+    FC_FONTFORMAT,
+    FC_FONTVERSION,
+    FC_FOUNDRY,
+    FC_WEIGHT,
+    FC_SLANT,
+    FC_WIDTH,
+    FC_SCALABLE,
+    FC_COLOR,
+    FC_VARIABLE,
+    FC_DECORATIVE,
+];
+
+impl FontInfo {
+    /// Gather the information about the font described by `pattern`, such as a match or a
+    /// listed font.
+    ///
+    /// Returns `None` if the pattern has no file. Elements missing from the pattern are left
+    /// empty, or `false` for flags, so a listing made with an object set must include the
+    /// elements of interest.
+    pub fn from_pattern(pattern: &Pattern) -> Option<FontInfo> {
+        // Values that are not valid UTF-8 are converted lossily rather than skipped, so that
+        // each family name stays at the same position as its language.
+        let strings = |name| {
+            (0..)
+                .map(|n| pattern.try_get_cstr(name, n))
+                .take_while(|value| !matches!(value, Err(Error::NoId | Error::NoMatch)))
+                .map(|value| {
+                    value
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+        let string = |name| pattern.get_string(name).map(str::to_owned);
+        let flag = |name| pattern.get_bool(name).unwrap_or(false);
+
+        Some(FontInfo {
+            families: strings(FC_FAMILY),
+            family_langs: strings(FC_FAMILYLANG),
+            style: string(FC_STYLE),
+            fullname: string(FC_FULLNAME),
+            postscript_name: string(FC_POSTSCRIPT_NAME),
+            path: pattern.file_path()?,
+            index: pattern.face_index(),
+            format: pattern.format().ok(),
+            version: pattern.get_int(FC_FONTVERSION),
+            foundry: string(FC_FOUNDRY),
+            weight: pattern.weight(),
+            slant: pattern.slant(),
+            width: pattern.width(),
+            scalable: flag(FC_SCALABLE),
+            color: flag(FC_COLOR),
+            variable: flag(FC_VARIABLE),
+            decorative: flag(FC_DECORATIVE),
+        })
+    }
+}
+
+/// An index of all installed fonts, built once and queried without calling into Fontconfig.
+///
+/// Fonts are keyed by each of their case-folded family names as well as their PostScript name,
+/// so lookups take constant time. This is useful for interfaces that look up family names
+/// repeatedly, such as autocompletion in a font picker.
+///
+/// The index is a snapshot: fonts installed after it was built are not visible until it is
+/// rebuilt.
+///
+/// ```
+/// use fontconfig::{FontIndex, Fontconfig};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let index = FontIndex::new(&fc);
+/// for font in index.lookup("DejaVu Sans") {
+///     println!("{:?} {}", font.style, font.path.display());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FontIndex {
+    fonts: HashMap<String, Vec<FontInfo>>,
+    /// The generation of the configuration this index was built from.
+    pub(crate) generation: Generation,
+}
+
+impl FontIndex {
+    /// Build an index of all fonts known to Fontconfig.
+    pub fn new(fc: &Fontconfig) -> FontIndex {
+        let mut objects = ObjectSet::new(fc);
+        for object in OBJECTS {
+            objects.add(object);
+        }
+
+        let mut index = FontIndex {
+            generation: fc.generation(),
+            ..FontIndex::default()
+        };
+        for pattern in list_fonts(&Pattern::new(fc), Some(&objects)).iter() {
+            if let Some(info) = FontInfo::from_pattern(&pattern) {
+                index.insert(info);
+            }
+        }
+        index
+    }
+
+    fn insert(&mut self, info: FontInfo) {
+        let mut keys: Vec<String> = info.families.iter().map(|f| fold_case(f)).collect();
+        if let Some(ps_name) = &info.postscript_name {
+            keys.push(fold_case(ps_name));
+        }
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            self.fonts.entry(key).or_default().push(info.clone());
+        }
+    }
+
+    /// Return the fonts whose family name or PostScript name is `name`, ignoring case.
+    ///
+    /// Returns an empty slice if no such font is installed.
+    pub fn lookup(&self, name: &str) -> &[FontInfo] {
+        self.fonts
+            .get(&fold_case(name))
+            .map_or(&[], |fonts| fonts.as_slice())
+    }
+
+    /// Iterate the case-folded names in this index.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fonts.keys().map(String::as_str)
+    }
+
+    /// Returns the number of distinct names in this index.
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Returns `true` if this index contains no fonts.
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+}
+
+fn fold_case(name: &str) -> String {
+    name.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_family() {
+        let fc = Fontconfig::new().unwrap();
+        let index = FontIndex::new(&fc);
+        assert!(!index.is_empty());
+
+        let fonts = index.lookup("dejavu sans");
+        assert!(!fonts.is_empty());
+        assert_eq!(fonts, index.lookup("DejaVu Sans"));
+        assert!(fonts
+            .iter()
+            .all(|font| font.families.iter().any(|f| f == "DejaVu Sans")));
+
+        let ps_name = fonts[0].postscript_name.as_deref().unwrap();
+        assert!(index.lookup(ps_name).contains(&fonts[0]));
+
+        assert!(index.lookup("no such family").is_empty());
+    }
+
+    #[test]
+    fn info_from_match() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "DejaVu Sans");
+        pat.add(FC_WEIGHT, crate::FC_WEIGHT_BOLD);
+        let info = FontInfo::from_pattern(&pat.font_match()).unwrap();
+        assert_eq!(info.families[0], "DejaVu Sans");
+        assert_eq!(info.families.len(), info.family_langs.len());
+        assert_eq!(info.fullname.as_deref(), Some("DejaVu Sans Bold"));
+        assert_eq!(info.format, Some(FontFormat::TrueType));
+        assert_eq!(info.weight, Some(crate::FC_WEIGHT_BOLD));
+        assert!(info.version.is_some());
+        assert!(info.scalable && !info.color && !info.variable && !info.decorative);
+
+        assert!(FontIndex::new(&fc).lookup("DejaVu Sans").contains(&info));
+        assert_eq!(FontInfo::from_pattern(&Pattern::new(&fc)), None);
+    }
+
+    #[test]
+    fn info_with_invalid_family() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FILE, c"/fonts/Example.ttf");
+        pat.add_string(FC_FAMILY, c"Exa\xffmple");
+        pat.add_string(FC_FAMILY, c"Example");
+        pat.add_string(FC_FAMILYLANG, c"x-bad");
+        pat.add_string(FC_FAMILYLANG, c"en");
+        let info = FontInfo::from_pattern(&pat).unwrap();
+        assert_eq!(info.families, ["Exa\u{FFFD}mple", "Example"]);
+        assert_eq!(info.family_langs, ["x-bad", "en"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let fc = Fontconfig::new().unwrap();
+        let index = FontIndex::new(&fc);
+        let info = &index.lookup("dejavu sans")[0];
+
+        let json = serde_json::to_string(info).unwrap();
+        assert_eq!(&serde_json::from_str::<FontInfo>(&json).unwrap(), info);
+    }
+}
@@ -0,0 +1,120 @@
+//! Named instances of variable fonts.
+
+use std::path::Path;
+
+use crate::{FontSet, Fontconfig, Pattern};
+use crate::{FC_INDEX, FC_NAMED_INSTANCE, FC_STYLE};
+
+/// A named instance of a variable font, such as "Semibold Condensed".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedInstance {
+    /// The style name of this instance.
+    pub style: String,
+    /// The value of the "index" element that selects this instance.
+    pub index: i32,
+}
+
+/// Build the value of the "index" element that selects a named instance of a face.
+///
+/// Fontconfig stores the face index within the file in the low 16 bits of the index, and the
+/// named instance in the high 16 bits. Instances are numbered from 1; instance 0 selects the
+/// variable face itself, which can be varied freely with the "fontvariations" element.
+///
+/// The result is `None` if `instance` does not fit in the high bits of a non-negative index,
+/// which Fontconfig treats as an error.
+///
+/// ```
+/// assert_eq!(fontconfig::instance_index(1, 3), Some(0x30001));
+/// assert_eq!(fontconfig::instance_index(0, 0x8000), None);
+/// ```
+pub const fn instance_index(face: u16, instance: u16) -> Option<i32> {
+    if instance > 0x7FFF {
+        return None;
+    }
+    Some(((instance as i32) << 16) | face as i32)
+}
+
+/// List the named instances of the variable fonts in the file at `path`.
+///
+/// The file does not need to be installed. Each instance can be selected by adding its index to
+/// a pattern, or by passing it to [`Pattern::from_file`], in the same way as a static face. The
+/// result is empty if the file contains no variable fonts or cannot be read.
+pub fn named_instances(fc: &Fontconfig, path: impl AsRef<Path>) -> Vec<NamedInstance> {
+    FontSet::from_file(fc, path)
+        .iter()
+        .filter_map(|pattern| {
+            let index = pattern.get_int(FC_INDEX)?;
+            // Fontconfig before 2.14.2 does not set "namedinstance", so fall back to the
+            // instance bits of the index.
+            if !pattern.named_instance().unwrap_or(index >> 16 != 0) {
+                return None;
+            }
+            Some(NamedInstance {
+                style: pattern.get_string(FC_STYLE)?.to_owned(),
+                index,
+            })
+        })
+        .collect()
+}
+
+impl Pattern<'_> {
+    /// Get the "namedinstance" element of this font pattern, which is `true` if the pattern
+    /// describes a named instance of a variable font.
+    pub fn named_instance(&self) -> Option<bool> {
+        self.get_bool(FC_NAMED_INSTANCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_encoding() {
+        assert_eq!(instance_index(0, 0), Some(0));
+        assert_eq!(instance_index(2, 0), Some(2));
+        assert_eq!(instance_index(0, 5), Some(5 << 16));
+        assert_eq!(instance_index(0, 0x7FFF), Some(0x7FFF << 16));
+        assert_eq!(instance_index(0, 0x8000), None);
+        assert_eq!(instance_index(0, u16::MAX), None);
+    }
+
+    #[test]
+    fn list_instances() {
+        let data = crate::tests::variable_font(
+            &[(b"wght", 100.0, 400.0, 900.0)],
+            &[("Light", &[300.0]), ("Bold", &[700.0])],
+        );
+        let path = std::env::temp_dir().join(format!(
+            "fontconfig-rs-instances-{}.ttf",
+            std::process::id()
+        ));
+        std::fs::write(&path, data).unwrap();
+
+        let fc = Fontconfig::new().unwrap();
+        let instances = named_instances(&fc, &path);
+        std::fs::remove_file(&path).unwrap();
+
+        let light = NamedInstance {
+            style: "Light".to_owned(),
+            index: instance_index(0, 1).unwrap(),
+        };
+        let bold = NamedInstance {
+            style: "Bold".to_owned(),
+            index: instance_index(0, 2).unwrap(),
+        };
+        assert!(instances.contains(&light), "{:?}", instances);
+        assert!(instances.contains(&bold), "{:?}", instances);
+        assert!(instances.iter().all(|instance| instance.index >> 16 != 0));
+    }
+
+    #[test]
+    fn static_font_has_no_instances() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        assert_eq!(named_instances(&fc, &font.path), []);
+
+        let pattern = Pattern::from_file(&fc, &font.path, 0).unwrap();
+        assert_ne!(pattern.named_instance(), Some(true));
+    }
+}
@@ -0,0 +1,276 @@
+//! Splitting text into runs that can each be rendered with a single font.
+
+use std::ops::Range;
+
+use crate::{sort_fonts, CharSet, Font, Fontconfig, LangSet, Pattern, Script};
+
+/// A run of text in a single script that can be rendered with a single font.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextRun {
+    /// The byte range of the run within the text.
+    pub range: Range<usize>,
+    /// The script of the run, or `None` if the text has only characters shared between scripts
+    /// or of scripts [`Script`] does not list.
+    pub script: Option<Script>,
+    /// The font to render the run with.
+    pub font: Font,
+}
+
+impl Pattern<'_> {
+    /// Split `text` into runs of one script, each mapped to the best font for it.
+    ///
+    /// The text is first split by [`Script::of`]. Whitespace, punctuation, digits and combining
+    /// marks, which are shared between scripts, belong to the script before them, or to the
+    /// first script of the text at its start. Han ideographs next to kana belong to the
+    /// Japanese run.
+    ///
+    /// The fonts are those Fontconfig considers for this pattern, in order of preference, so
+    /// the pattern's language and family choose between fonts that cover the same characters.
+    /// Each character goes to the first font that covers it and supports the language
+    /// [`Script::lang`] gives for its script, or else to the first font that covers it, so
+    /// that a font with a few stray characters of a script does not win over one made for it.
+    /// Shared characters stay in the current run when its font covers them, so that they do
+    /// not break up runs. Characters no font covers also stay in the current run, or go to the
+    /// most preferred font at the start of the text.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_string(FC_FAMILY, c"sans-serif");
+    /// for run in pat.itemize("Hello, мир!") {
+    ///     println!("{:?} {}", run.range, run.font.name);
+    /// }
+    /// ```
+    pub fn itemize(&self, text: &str) -> Vec<TextRun> {
+        let mut pattern = self.clone();
+        pattern.substitute();
+
+        let fonts: Vec<(Font, Option<CharSet>, Option<LangSet>)> = sort_fonts(&pattern, true)
+            .iter()
+            .filter_map(|pattern| {
+                let font = Font::from_pattern(&pattern)?;
+                Some((font, pattern.char_set(), pattern.get_lang_set()))
+            })
+            .collect();
+        if fonts.is_empty() {
+            return Vec::new();
+        }
+        let covers =
+            |font: usize, c: char| fonts[font].1.as_ref().is_some_and(|set| set.contains(c));
+        let supports = |font: usize, script: Option<Script>| {
+            script.is_some_and(|script| {
+                fonts[font]
+                    .2
+                    .as_ref()
+                    .is_some_and(|langs| langs.contains(script.lang()))
+            })
+        };
+
+        let mut runs: Vec<(Range<usize>, Option<Script>, usize)> = Vec::new();
+        for ((start, c), script) in text.char_indices().zip(scripts(text)) {
+            let current = runs
+                .last()
+                .filter(|(_, current, _)| *current == script)
+                .map(|(_, _, font)| *font);
+            let font = match current {
+                Some(font) if is_shared(c) && covers(font, c) => font,
+                _ => (0..fonts.len())
+                    .find(|&font| covers(font, c) && supports(font, script))
+                    .or_else(|| (0..fonts.len()).find(|&font| covers(font, c)))
+                    .or(current)
+                    .unwrap_or(0),
+            };
+
+            let end = start + c.len_utf8();
+            match runs.last_mut() {
+                Some((range, current, current_font))
+                    if *current == script && *current_font == font =>
+                {
+                    range.end = end
+                }
+                _ => runs.push((start..end, script, font)),
+            }
+        }
+
+        runs.into_iter()
+            .map(|(range, script, font)| TextRun {
+                range,
+                script,
+                font: fonts[font].0.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the script of each character of `text`, resolving shared characters and Han
+/// ideographs by their neighbours as [`Pattern::itemize`] describes.
+fn scripts(text: &str) -> Vec<Option<Script>> {
+    let cjk = |script| matches!(script, Some(Script::Japanese | Script::HanTraditional));
+    let mut scripts = Vec::new();
+    let mut current = None;
+    for c in text.chars() {
+        current = match Script::of(c) {
+            None => current,
+            Some(Script::HanSimplified) if cjk(current) => current,
+            script => script,
+        };
+        scripts.push(current);
+    }
+    // Han ideographs before kana, and characters before the first of any script, take the
+    // script that follows them.
+    let mut next = None;
+    for (script, c) in scripts.iter_mut().rev().zip(text.chars().rev()) {
+        let shared = Script::of(c).is_none();
+        if script.is_none() || (*script == Some(Script::HanSimplified) && cjk(next)) {
+            *script = next;
+        } else if !shared {
+            next = *script;
+        }
+    }
+    scripts
+}
+
+impl Fontconfig {
+    /// Find the fonts that together cover the characters of `text`, in the order to try them.
+    ///
+    /// The fonts are taken from those Fontconfig prefers for `base` after the configuration
+    /// has been applied, keeping each font that covers a character none of the fonts before it
+    /// covers, until every character is covered. The first font is therefore the preferred
+    /// font whenever it covers any of the text. Characters no installed font covers are left
+    /// out.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut base = Pattern::new(&fc);
+    /// base.add(FC_FAMILY, "monospace");
+    /// for font in fc.fallback_chain("fn main() {} // ∰ мир", &base) {
+    ///     println!("{}", font.name);
+    /// }
+    /// ```
+    pub fn fallback_chain(&self, text: &str, base: &Pattern) -> Vec<Font> {
+        let mut missing: Vec<char> = text.chars().collect();
+        missing.sort_unstable();
+        missing.dedup();
+
+        let mut pattern = base.clone();
+        pattern.substitute();
+        let mut chain = Vec::new();
+        for font in sort_fonts(&pattern, true).iter() {
+            if missing.is_empty() {
+                break;
+            }
+            let Some(chars) = font.char_set() else {
+                continue;
+            };
+            let before = missing.len();
+            missing.retain(|&c| !chars.contains(c));
+            if missing.len() < before {
+                chain.extend(Font::from_pattern(&font));
+            }
+        }
+        chain
+    }
+}
+
+/// Returns `true` for characters that are commonly shared between scripts.
+fn is_shared(c: char) -> bool {
+    c.is_whitespace()
+        || c.is_ascii_punctuation()
+        || c.is_ascii_digit()
+        || matches!(c,
+            '\u{0300}'..='\u{036F}' // Combining Diacritical Marks
+            | '\u{200C}'..='\u{200D}' // Zero-width (non-)joiner
+            | '\u{FE00}'..='\u{FE0F}' // Variation selectors
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FC_FAMILY;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn itemize_text() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FAMILY, c"DejaVu Sans");
+
+        let runs = pattern.itemize("Hello, world!");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].range, 0..13);
+        assert_eq!(runs[0].font.name, "DejaVu Sans");
+        assert_eq!(runs[0].script, Some(Script::Latin));
+
+        assert_eq!(pattern.itemize(""), []);
+        assert_eq!(pattern.itemize("1, 2")[0].script, None);
+
+        // The font covers both scripts, but the runs are split where the script changes.
+        let text = "Hello мир!";
+        let runs = pattern.itemize(text);
+        let split: Vec<_> = runs
+            .iter()
+            .map(|run| (&text[run.range.clone()], run.script))
+            .collect();
+        assert_eq!(
+            split,
+            [
+                ("Hello ", Some(Script::Latin)),
+                ("мир!", Some(Script::Cyrillic))
+            ]
+        );
+        assert!(runs.iter().all(|run| run.font.name == "DejaVu Sans"));
+
+        // Every byte is covered by exactly one run.
+        let text = "abc ∑ 中文 def";
+        let runs = pattern.itemize(text);
+        assert_eq!(runs.first().unwrap().range.start, 0);
+        assert_eq!(runs.last().unwrap().range.end, text.len());
+        assert!(runs
+            .windows(2)
+            .all(|pair| pair[0].range.end == pair[1].range.start));
+    }
+
+    #[test]
+    fn resolve_scripts() {
+        let japanese = Some(Script::Japanese);
+        let latin = Some(Script::Latin);
+        assert_eq!(
+            scripts(" 漢字、かなabc"),
+            [japanese, japanese, japanese, japanese, japanese, japanese, latin, latin, latin]
+        );
+        assert_eq!(
+            scripts("中文 abc"),
+            [Some(Script::HanSimplified); 3]
+                .into_iter()
+                .chain([latin; 3])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let fc = Fontconfig::new().unwrap();
+        let mut base = Pattern::new(&fc);
+        base.add(FC_FAMILY, "DejaVu Sans Mono");
+
+        let files = |text| -> Vec<_> {
+            fc.fallback_chain(text, &base)
+                .into_iter()
+                .map(|font| font.path.file_name().unwrap().to_owned())
+                .collect()
+        };
+        assert_eq!(files("let x = 1;"), [OsStr::new("DejaVuSansMono.ttf")]);
+        // The monospace font lacks the integral sign, which the next font covers.
+        let chain = files("x = ∰");
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0], "DejaVuSansMono.ttf");
+        // Characters no font covers are left out.
+        assert!(files("\u{10FFFD}").is_empty());
+        assert!(files("").is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Sets of languages, used to describe the orthographies a font covers.
+
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::ops::{BitOr, Sub};
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    ffi_dispatch, ffi_dispatch_optional, sys, CharSet, FcTrue, Fontconfig, IntoFcStr, Pattern,
+    StrSet, FC_LANG,
+};
+
+/// A safe wrapper around fontconfig's `FcLangSet`.
+///
+/// Languages are named by Fontconfig's language tags: an ISO 639 language code, optionally
+/// followed by a hyphen and an ISO 3166 territory code, in lower case, e.g. `en` or `zh-tw`.
+pub struct LangSet {
+    fcset: *mut sys::FcLangSet,
+}
+
+impl LangSet {
+    /// Create a new, empty `LangSet`.
+    pub fn new(_: &Fontconfig) -> LangSet {
+        LangSet::empty()
+    }
+
+    /// Create an empty set, for functions that do not take a `Fontconfig` handle.
+    fn empty() -> LangSet {
+        let fcset = unsafe { ffi_dispatch!(LIB, FcLangSetCreate,) };
+        assert!(!fcset.is_null());
+        LangSet { fcset }
+    }
+
+    /// Wrap an existing `FcLangSet`.
+    ///
+    /// The returned wrapper assumes ownership of the `FcLangSet`.
+    ///
+    /// # Safety
+    ///
+    /// The lang set pointer must be valid/non-null.
+    pub unsafe fn from_raw(_: &Fontconfig, raw_set: *mut sys::FcLangSet) -> LangSet {
+        LangSet { fcset: raw_set }
+    }
+
+    pub(crate) unsafe fn copy_raw(raw_set: *const sys::FcLangSet) -> LangSet {
+        let fcset = ffi_dispatch!(LIB, FcLangSetCopy, raw_set);
+        assert!(!fcset.is_null());
+        LangSet { fcset }
+    }
+
+    /// Add a language to this set.
+    ///
+    /// A language containing a NUL byte is ignored.
+    pub fn add<'a>(&mut self, lang: impl IntoFcStr<'a>) {
+        let Some(lang) = lang.into_fc_str() else {
+            return;
+        };
+        unsafe {
+            ffi_dispatch!(LIB, FcLangSetAdd, self.fcset, lang.as_ptr() as *const u8);
+        }
+    }
+
+    /// Returns `true` if this set contains `lang`, including its territory if it has one.
+    pub fn contains(&self, lang: &str) -> bool {
+        let Ok(lang) = CString::new(lang) else {
+            return false;
+        };
+        unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcLangSetHasLang,
+                self.fcset,
+                lang.as_ptr() as *const u8
+            ) == sys::FcLangEqual
+        }
+    }
+
+    /// Returns the languages in this set.
+    pub fn langs(&self) -> Vec<String> {
+        let langs = unsafe { StrSet::from_raw(ffi_dispatch!(LIB, FcLangSetGetLangs, self.fcset)) };
+        langs.iter().lossy().map(Cow::into_owned).collect()
+    }
+
+    /// Returns the languages in either this set or `other`, e.g. those supported by any font of
+    /// a font stack.
+    ///
+    /// This is also available as `&a | &b`.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, LangSet};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut latin = LangSet::new(&fc);
+    /// latin.add("en");
+    /// let mut cyrillic = LangSet::new(&fc);
+    /// cyrillic.add("ru");
+    /// let stack = &latin | &cyrillic;
+    /// assert!(stack.contains("en") && stack.contains("ru"));
+    /// assert!(!(&stack - &latin).contains("en"));
+    /// ```
+    pub fn union(&self, other: &LangSet) -> LangSet {
+        if !sys::available::FcLangSetUnion() {
+            let mut set = self.clone();
+            for lang in other.langs() {
+                set.add(lang.as_str());
+            }
+            return set;
+        }
+        let fcset = unsafe { ffi_dispatch_optional!(FcLangSetUnion, self.fcset, other.fcset) };
+        assert!(!fcset.is_null());
+        LangSet { fcset }
+    }
+
+    /// Returns the languages in this set that are not in `other`, e.g. those only one font of
+    /// a font stack supports.
+    ///
+    /// This is also available as `&a - &b`.
+    pub fn subtract(&self, other: &LangSet) -> LangSet {
+        if !sys::available::FcLangSetSubtract() {
+            let other = other.langs();
+            let mut set = LangSet::empty();
+            for lang in self.langs() {
+                if !other.contains(&lang) {
+                    set.add(lang.as_str());
+                }
+            }
+            return set;
+        }
+        let fcset = unsafe { ffi_dispatch_optional!(FcLangSetSubtract, self.fcset, other.fcset) };
+        assert!(!fcset.is_null());
+        LangSet { fcset }
+    }
+
+    /// Returns the characters Fontconfig requires a font to cover to support `lang`, or `None`
+    /// if Fontconfig has no orthography for it.
+    ///
+    /// The set is a copy owned by the caller. Fontconfig's orthographies are static data that
+    /// must not be modified or destroyed, and copying avoids tying a borrow of them to any
+    /// lifetime.
+    pub fn charset(_: &Fontconfig, lang: &str) -> Option<CharSet> {
+        let lang = CString::new(lang).ok()?;
+        unsafe {
+            let set = ffi_dispatch!(LIB, FcLangGetCharSet, lang.as_ptr() as *const u8);
+            (!set.is_null()).then(|| CharSet::copy_raw(set))
+        }
+    }
+
+    /// Returns a raw pointer to underlying `FcLangSet`.
+    pub fn as_ptr(&self) -> *const sys::FcLangSet {
+        self.fcset
+    }
+
+    /// Returns an unsafe mutable pointer to the underlying `FcLangSet`.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::FcLangSet {
+        self.fcset
+    }
+}
+
+/// Returns every language Fontconfig has an orthography for, which are the languages fonts can
+/// be matched for, e.g. to offer only those in a language picker.
+///
+/// ```
+/// use fontconfig::Fontconfig;
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let langs = fontconfig::known_langs(&fc);
+/// assert!(langs.iter().any(|lang| lang == "fr"));
+/// ```
+pub fn known_langs(_: &Fontconfig) -> StrSet {
+    unsafe { StrSet::from_raw(ffi_dispatch!(LIB, FcGetLangs,)) }
+}
+
+/// The most characters [`exemplar_chars`] returns.
+const MAX_EXEMPLARS: usize = 32;
+
+/// Returns a sample of the letters of the orthography of `lang`, for previewing fonts in the
+/// script the user reads, or nothing if Fontconfig has no orthography for it.
+///
+/// The sample is drawn evenly from the letters Fontconfig requires a font to cover for the
+/// language, in code point order, leaving out capitals whose lower case is also required.
+/// It holds at most 32 characters.
+///
+/// ```
+/// use fontconfig::Fontconfig;
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let sample: String = fontconfig::exemplar_chars(&fc, "ru").into_iter().collect();
+/// assert!(sample.contains('ж'));
+/// ```
+pub fn exemplar_chars(fc: &Fontconfig, lang: &str) -> Vec<char> {
+    let Some(charset) = LangSet::charset(fc, lang) else {
+        return Vec::new();
+    };
+    let has_lower = |c: char| {
+        let mut lower = c.to_lowercase();
+        lower.len() == 1 && lower.next().is_some_and(|l| l != c && charset.contains(l))
+    };
+    let letters: Vec<char> = charset
+        .chars()
+        .filter(|&c| c.is_alphabetic() && !has_lower(c))
+        .collect();
+    if letters.len() <= MAX_EXEMPLARS {
+        return letters;
+    }
+    (0..MAX_EXEMPLARS)
+        .map(|i| letters[i * letters.len() / MAX_EXEMPLARS])
+        .collect()
+}
+
+// SAFETY: A `LangSet` owns its `FcLangSet`, which is only modified through `&mut self`, and the
+// Fontconfig functions that take `&self` only read it.
+unsafe impl Send for LangSet {}
+unsafe impl Sync for LangSet {}
+
+impl Clone for LangSet {
+    fn clone(&self) -> Self {
+        unsafe { LangSet::copy_raw(self.fcset) }
+    }
+}
+
+impl PartialEq for LangSet {
+    fn eq(&self, other: &LangSet) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcLangSetEqual, self.fcset, other.fcset) == FcTrue }
+    }
+}
+
+impl Eq for LangSet {}
+
+impl BitOr for &LangSet {
+    type Output = LangSet;
+
+    fn bitor(self, other: &LangSet) -> LangSet {
+        self.union(other)
+    }
+}
+
+impl Sub for &LangSet {
+    type Output = LangSet;
+
+    fn sub(self, other: &LangSet) -> LangSet {
+        self.subtract(other)
+    }
+}
+
+impl std::fmt::Debug for LangSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.langs()).finish()
+    }
+}
+
+impl Drop for LangSet {
+    fn drop(&mut self) {
+        unsafe { ffi_dispatch!(LIB, FcLangSetDestroy, self.fcset) }
+    }
+}
+
+impl Pattern<'_> {
+    /// Get a copy of the "lang" element of this pattern: the languages the font supports.
+    pub fn get_lang_set(&self) -> Option<LangSet> {
+        let mut fcset = ptr::null_mut();
+        unsafe {
+            if ffi_dispatch!(
+                LIB,
+                FcPatternGetLangSet,
+                self.pat,
+                FC_LANG.as_ptr(),
+                0,
+                &mut fcset
+            ) == sys::FcResultMatch
+            {
+                Some(LangSet::copy_raw(fcset))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Add a lang set to the "lang" element of this pattern.
+    pub fn add_lang_set(&mut self, lang_set: &LangSet) {
+        unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcPatternAddLangSet,
+                self.pat,
+                FC_LANG.as_ptr(),
+                lang_set.fcset
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_query() {
+        let fc = Fontconfig::new().unwrap();
+        let mut set = LangSet::new(&fc);
+        set.add("en");
+        set.add("zh-tw");
+        assert!(set.contains("en"));
+        assert!(set.contains("zh-tw"));
+        assert!(!set.contains("zh-cn"));
+
+        let mut langs = set.langs();
+        langs.sort();
+        assert_eq!(langs, ["en", "zh-tw"]);
+
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_lang_set(&set);
+        assert_eq!(pattern.get_lang_set(), Some(set));
+    }
+
+    #[test]
+    fn union_and_subtract() {
+        let fc = Fontconfig::new().unwrap();
+        let set = |langs: &[&str]| {
+            let mut set = LangSet::new(&fc);
+            for &lang in langs {
+                set.add(lang);
+            }
+            set
+        };
+        let a = set(&["en", "de", "zh-tw"]);
+        let b = set(&["de", "fr", "xx-private"]);
+        assert_eq!(a.union(&b), set(&["en", "de", "fr", "zh-tw", "xx-private"]));
+        assert_eq!(&a | &b, a.union(&b));
+        assert_eq!(a.subtract(&b), set(&["en", "zh-tw"]));
+        assert_eq!(&b - &a, set(&["fr", "xx-private"]));
+        assert_eq!(&a - &a, LangSet::new(&fc));
+    }
+
+    #[test]
+    fn orthography() {
+        let fc = Fontconfig::new().unwrap();
+        let en = LangSet::charset(&fc, "en").unwrap();
+        assert!(en.contains('a'));
+        assert!(!en.contains('\u{3042}'));
+        let ja = LangSet::charset(&fc, "ja").unwrap();
+        assert!(ja.contains('\u{3042}'));
+        assert!(LangSet::charset(&fc, "xx-unknown").is_none());
+    }
+
+    #[test]
+    fn known() {
+        let fc = Fontconfig::new().unwrap();
+        let known = known_langs(&fc);
+        let langs: Vec<&str> = known.iter().collect();
+        assert!(langs.len() > 200);
+        assert!(langs.contains(&"zh-tw"));
+        assert!(langs
+            .iter()
+            .all(|lang| LangSet::charset(&fc, lang).is_some()));
+    }
+
+    #[test]
+    fn exemplars() {
+        let fc = Fontconfig::new().unwrap();
+        let ru = exemplar_chars(&fc, "ru");
+        assert!(ru.contains(&'а') && ru.contains(&'я'));
+        assert!(!ru.contains(&'А'));
+        assert!(ru.iter().all(|c| ('\u{400}'..='\u{4FF}').contains(c)));
+
+        let ja = exemplar_chars(&fc, "ja");
+        assert_eq!(ja.len(), MAX_EXEMPLARS);
+        assert!(ja.windows(2).all(|w| w[0] < w[1]));
+
+        assert!(exemplar_chars(&fc, "xx-unknown").is_empty());
+    }
+}
@@ -35,46 +35,261 @@
 //!
 //! ### Cargo Features
 //!
-//! | Feature       | Description                       | Default Enabled | Extra Dependencies    |
-//! |---------------|-----------------------------------|:---------------:|-----------------------|
-//! | `dlopen`      | [dlopen] libfontconfig at runtime |        ❌       |                       |
+//! | Feature       | Description                           | Default Enabled | Extra Dependencies          |
+//! |---------------|---------------------------------------|:---------------:|-----------------------------|
+//! | `derive`      | Derive `FromPattern` for structs      |        ❌       | [`fontconfig-derive`]       |
+//! | `dlopen`      | [dlopen] libfontconfig at runtime     |        ❌       |                             |
+//! | `euclid`      | Convert matrices to euclid transforms |        ❌       | [`euclid`]                  |
+//! | `freetype`    | Query patterns from FreeType faces    |        ❌       | [`freetype-rs`]             |
+//! | `harfbuzz`    | HarfBuzz shaping setup helpers        |        ❌       |                             |
+//! | `icu`         | Convert ICU locales to language tags  |        ❌       | [`icu_locid`]               |
+//! | `kurbo`       | Convert matrices to kurbo transforms  |        ❌       | [`kurbo`]                   |
+//! | `memmap2`     | Memory-map matched font files         |        ❌       | [`memmap2`]                 |
+//! | `mint`        | Convert matrices to mint types        |        ❌       | [`mint`]                    |
+//! | `serde`       | Serialize and deserialize fonts       |        ❌       | `serde`                     |
+//! | `skrifa`      | Check variations against font axes    |        ❌       | [`skrifa`], [`memmap2`]     |
+//! | `ttf-parser`  | Parse matched fonts with ttf-parser   |        ❌       | [`ttf-parser`], [`memmap2`] |
 //!
 //! The `dlopen` feature enables building this crate without dynamically linking to the Fontconfig C
 //! library at link time. Instead, Fontconfig will be dynamically loaded at runtime with the
 //! [dlopen] function. This can be useful in cross-compiling situations as you don't need to have a
 //! version of Fontcofig available for the target platform available at compile time.
+//! Functions added in later versions of Fontconfig are looked up one by one, so an older library
+//! still loads and only the features that need them are lost, as documented on each.
+//!
+//! The `derive` feature adds `#[derive(FromPattern)]`, which generates the code reading the
+//! fields of a struct from the elements of a matched pattern. It is implemented in the
+//! [`fontconfig-derive`] crate.
+//!
+//! The `harfbuzz` feature adds the [`harfbuzz`] module, which converts the font features and
+//! variations of a matched pattern into the form HarfBuzz expects. It has no dependencies, so it
+//! can be used with any HarfBuzz bindings.
+//!
+//! The `freetype` feature allows patterns to be created from faces that have already been loaded
+//! with the [`freetype-rs`] crate, which is re-exported as `fontconfig::freetype`. Querying a
+//! font file with `Pattern::from_file` or `FontSet::from_file` does not need it.
+//!
+//! The `memmap2` feature adds `Font::map` and `Pattern::map_file`, which memory-map the matched
+//! font file with the [`memmap2`] crate so that large fonts can be passed to parsers and shapers
+//! without being copied.
+//!
+//! The `ttf-parser` feature adds `Font::open_face` and `Pattern::open_face`, which memory-map the
+//! matched font file so that its tables can be read with the [`ttf-parser`] crate. It also lets
+//! `Fontconfig::fonts_with_feature` check which features each font defines.
+//!
+//! The `skrifa` feature adds `Pattern::axis_settings`, which uses the [`skrifa`] crate to check the
+//! variation settings of a matched variable font against the axes it actually defines. This
+//! feature requires Rust 1.85 or newer.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for `Font` and `FontInfo`, so that
+//! font choices can be saved. Use `Font::revalidate` to check a restored font is still installed.
+//!
+//! The `mint`, `euclid` and `kurbo` features implement `From` conversions between `Matrix` and the
+//! transform types of the [`mint`], [`euclid`] and [`kurbo`] crates, so that the matrix of a
+//! matched font can be passed straight to a rendering pipeline.
+//!
+//! The `icu` feature converts between the locales of the [`icu_locid`] crate, which is
+//! re-exported as `fontconfig::icu_locid`, and Fontconfig's language tags, taking the script
+//! subtag into account so that `zh-Hant` selects traditional Chinese fonts.
 //!
 //! [dlopen]: https://pubs.opengroup.org/onlinepubs/9699919799/functions/dlopen.html
+//! [`euclid`]: https://crates.io/crates/euclid
+//! [`fontconfig-derive`]: https://crates.io/crates/fontconfig-derive
+//! [`freetype-rs`]: https://crates.io/crates/freetype-rs
+//! [`icu_locid`]: https://crates.io/crates/icu_locid
+//! [`kurbo`]: https://crates.io/crates/kurbo
+//! [`memmap2`]: https://crates.io/crates/memmap2
+//! [`mint`]: https://crates.io/crates/mint
+//! [`skrifa`]: https://crates.io/crates/skrifa
+//! [`ttf-parser`]: https://crates.io/crates/ttf-parser
+
+// Lets the code generated by `#[derive(FromPattern)]` be tested within this crate.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as fontconfig;
 
 use fontconfig_sys as sys;
-use fontconfig_sys::ffi_dispatch;
+use fontconfig_sys::{ffi_dispatch, ffi_dispatch_optional};
 
 #[cfg(feature = "dlopen")]
 use sys::statics::{LIB, LIB_RESULT};
 #[cfg(not(feature = "dlopen"))]
 use sys::*;
 
+use std::borrow::Cow;
 use std::ffi::{c_int, CStr, CString};
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
-use std::mem;
+use std::mem::{self, ManuallyDrop};
+use std::ops::Deref;
 use std::os::raw::c_char;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 pub use sys::constants::*;
 use sys::{FcBool, FcPattern};
 
+mod attribute;
+mod binding;
+mod blocks;
+mod cache;
+mod charset;
+mod config;
+mod coverage;
+mod css;
+mod data;
+mod database;
+mod dirs;
+mod embed;
+mod emoji;
+mod error;
+mod explain;
+mod extract;
+mod fcstr;
+mod features;
+mod filter;
+#[cfg(feature = "freetype")]
+mod ft;
+#[cfg(feature = "skrifa")]
+mod fvar;
+#[cfg(feature = "harfbuzz")]
+pub mod harfbuzz;
+mod headless;
+#[cfg(feature = "icu")]
+mod icu;
+mod index;
+mod instance;
+mod itemize;
+mod langset;
+mod locale;
+#[cfg(feature = "memmap2")]
+mod mmap;
+mod priority;
+mod property;
+mod query;
+mod reload;
+mod render;
+mod sandbox;
+mod scan;
+mod script;
+mod shared;
+pub mod strings;
+mod terminal;
+#[cfg(any(feature = "euclid", feature = "kurbo", feature = "mint"))]
+mod transform;
+#[cfg(feature = "ttf-parser")]
+mod ttf;
+mod value;
+mod variation;
+mod weight;
+
+pub use attribute::Attribute;
+pub use binding::{Binding, BoundValue};
+pub use blocks::{BlockCoverage, UnicodeBlock};
+pub use cache::DirCache;
+pub use charset::CharSet;
+pub use coverage::{LangGap, PartialCoverage};
+pub use database::{DatabaseFont, FontDatabase};
+pub use dirs::ConfigFileInfo;
+pub use embed::Embedding;
+pub use emoji::{Presentation, EMOJI_PRESENTATION_SELECTOR, TEXT_PRESENTATION_SELECTOR};
+pub use error::{Error, FcResultExt, Result};
+pub use explain::Candidate;
+pub use extract::{FromPattern, FromPatternValue};
+pub use fcstr::IntoFcStr;
+use fcstr::{cstr_to_path, path_to_cstring};
+pub use filter::FontFilter;
+#[cfg(feature = "derive")]
+pub use fontconfig_derive::FromPattern;
+#[cfg(feature = "freetype")]
+pub use freetype;
+#[cfg(feature = "freetype")]
+pub use ft::PatternFace;
+pub use headless::NoFonts;
+#[cfg(feature = "icu")]
+pub use icu::{fc_lang_from_bcp47, fc_lang_from_locale, locale_from_fc_lang};
+#[cfg(feature = "icu")]
+pub use icu_locid;
+pub use index::{FontIndex, FontInfo};
+pub use instance::{instance_index, named_instances, NamedInstance};
+pub use itemize::TextRun;
+pub use langset::{exemplar_chars, known_langs, LangSet};
+#[cfg(feature = "memmap2")]
+pub use mmap::MappedFont;
+pub use priority::AppFontPriority;
+pub use property::{FontProperty, UnknownProperty, ValueType};
+pub use query::FontQuery;
+pub use reload::Generation;
+use reload::{current_generation, next_generation};
+pub use render::{HintStyle, LcdFilter, Matrix, RenderSettings, RendererFont, Rgba};
+pub use sandbox::{HostFontDir, SandboxFonts};
+pub use script::{GenericFamily, Script, UnknownScript};
+pub use shared::SharedPattern;
+pub use terminal::TerminalFont;
+#[cfg(feature = "ttf-parser")]
+pub use ttf::MappedFace;
+#[cfg(feature = "ttf-parser")]
+pub use ttf_parser;
+pub use value::{AddOptions, IntoPatternValue, Value};
+pub use variation::{AxisSetting, InvalidTag, Tag};
+pub use weight::Weight;
+
 #[allow(non_upper_case_globals)]
 const FcTrue: FcBool = 1;
-#[allow(non_upper_case_globals, dead_code)]
+#[allow(non_upper_case_globals)]
 const FcFalse: FcBool = 0;
 
 /// Handle obtained after Fontconfig has been initialised.
+///
+/// # Threads
+///
+/// A `Fontconfig` is `Send` and `Sync`. Matching, sorting and listing only need `&self`, so
+/// worker threads can share a handle, while the methods changing its configuration, such as
+/// [`Fontconfig::app_font_add_dir`], need `&mut self` and so exclusive access, e.g. through an
+/// [`RwLock`](std::sync::RwLock). That access covers the whole configuration being changed, as
+/// a handle sharing the current configuration is given one of its own first, as described under
+/// [Changing the configuration](#changing-the-configuration). The patterns and font sets
+/// borrowing a handle stay on the thread that made them: convert a pattern to a
+/// [`SharedPattern`] to pass it on.
+///
+/// # Changing the configuration
+///
+/// A handle from [`Fontconfig::new`] owns its configuration, so changing it affects no other
+/// code. A handle from [`Fontconfig::global`] or [`Fontconfig::with_cached_config`] shares the
+/// current configuration with the rest of the process. Before such a handle changes its
+/// configuration, e.g. to add application fonts, it is given a configuration of its own, loaded
+/// from the default configuration files, so that the change does not reach other code using
+/// Fontconfig. From then on it behaves like a handle from [`Fontconfig::new`].
+///
+/// ```
+/// use std::sync::{Arc, RwLock};
+/// use std::thread;
+/// use fontconfig::Fontconfig;
+///
+/// let fc = Arc::new(RwLock::new(Fontconfig::new().unwrap()));
+/// let worker = {
+///     let fc = Arc::clone(&fc);
+///     thread::spawn(move || fc.read().unwrap().find("sans-serif", None))
+/// };
+/// println!("{:?}", worker.join().unwrap());
+/// fc.write().unwrap().app_font_clear();
+/// ```
 pub struct Fontconfig {
-    _initialised: (),
+    /// The configuration used by this handle, or null to use the current configuration.
+    config: *mut sys::FcConfig,
+    /// Whether `config` was made for this handle alone, so that changing it affects no other
+    /// handle.
+    owns_config: bool,
+    /// The generation of `config`, if it is not null.
+    generation: Generation,
 }
 
+// SAFETY: Fontconfig's configuration is reference counted atomically and may be queried from
+// several threads at once.
+unsafe impl Send for Fontconfig {}
+unsafe impl Sync for Fontconfig {}
+
 /// Error type returned from Pattern::format.
 ///
 /// The error holds the name of the unknown format.
@@ -82,7 +297,8 @@ pub struct Fontconfig {
 pub struct UnknownFontFormat(pub String);
 
 /// The format of a font matched by Fontconfig.
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum FontFormat {
     TrueType,
@@ -96,22 +312,161 @@ pub enum FontFormat {
     WindowsFNT,
 }
 
+/// The spacing of a font, from the "spacing" element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Spacing {
+    /// Glyphs have varying advance widths.
+    Proportional,
+    /// Glyphs have one of two advance widths, e.g. for CJK fonts with full-width ideographs.
+    Dual,
+    /// Glyphs all have the same advance width.
+    Mono,
+    /// Glyphs all have the same advance width and fit within a character cell.
+    CharCell,
+}
+
+impl Spacing {
+    fn from_value(value: c_int) -> Option<Spacing> {
+        match value {
+            FC_PROPORTIONAL => Some(Spacing::Proportional),
+            FC_DUAL => Some(Spacing::Dual),
+            FC_MONO => Some(Spacing::Mono),
+            FC_CHARCELL => Some(Spacing::CharCell),
+            _ => None,
+        }
+    }
+}
+
 impl Fontconfig {
     /// Initialise Fontconfig and return a handle allowing further interaction with the API.
     ///
-    /// If Fontconfig fails to initialise, returns `None`.
+    /// The handle owns a configuration of its own, loaded from the default configuration files
+    /// with its fonts scanned, and passes it to every call. Changes made through the handle,
+    /// such as application fonts or extra configuration files, do not reach other code using
+    /// Fontconfig in the process, and changes other code makes to Fontconfig's current
+    /// configuration, e.g. by calling `FcConfigSetCurrent`, do not reach the handle. Fonts
+    /// installed after the handle was created are picked up with [`Fontconfig::build_fonts`].
+    /// See [`Fontconfig::with_cached_config`] for a handle sharing the current configuration.
+    ///
+    /// If Fontconfig fails to initialise or to load the configuration, returns `None`.
     pub fn new() -> Option<Self> {
-        #[cfg(feature = "dlopen")]
-        if LIB_RESULT.is_err() {
+        if !Fontconfig::loaded() {
+            return None;
+        }
+        let config = unsafe { ffi_dispatch!(LIB, FcInitLoadConfigAndFonts,) };
+        if config.is_null() {
             return None;
         }
-        if unsafe { ffi_dispatch!(LIB, FcInit,) == FcTrue } {
-            Some(Fontconfig { _initialised: () })
+        Some(Fontconfig {
+            config,
+            owns_config: true,
+            generation: next_generation(),
+        })
+    }
+
+    /// Initialise Fontconfig and return a handle that uses its current configuration, looking
+    /// it up on every call so that it follows Fontconfig's rescans and [`Fontconfig::reload`].
+    fn current() -> Option<Self> {
+        if Fontconfig::loaded() && unsafe { ffi_dispatch!(LIB, FcInit,) == FcTrue } {
+            Some(Fontconfig {
+                config: ptr::null_mut(),
+                owns_config: false,
+                generation: Generation::default(),
+            })
         } else {
             None
         }
     }
 
+    /// Returns `false` if the Fontconfig library could not be loaded.
+    fn loaded() -> bool {
+        #[cfg(feature = "dlopen")]
+        if LIB_RESULT.is_err() {
+            return false;
+        }
+        true
+    }
+
+    /// Initialise Fontconfig and return a handle that shares its current configuration with
+    /// the rest of the process.
+    ///
+    /// The configuration is resolved once and reused, which saves looking it up in tight loops
+    /// such as text layout. The handle sees changes other code makes to the current
+    /// configuration, such as application fonts, but not fonts installed after it was created,
+    /// as Fontconfig's rescans replace the current configuration. Like any handle, it is given
+    /// a configuration of its own before it changes it, as described under
+    /// [Changing the configuration](Fontconfig#changing-the-configuration).
+    ///
+    /// If Fontconfig fails to initialise, returns `None`.
+    pub fn with_cached_config() -> Option<Self> {
+        let mut fc = Fontconfig::current()?;
+        fc.config = unsafe { ffi_dispatch!(LIB, FcConfigReference, ptr::null_mut()) };
+        fc.generation = current_generation();
+        if fc.config.is_null() {
+            None
+        } else {
+            Some(fc)
+        }
+    }
+
+    /// Initialise Fontconfig and return a handle with an empty configuration, which has no
+    /// fonts, font directories or rules, whatever the configuration of the system.
+    ///
+    /// Fonts can then be added with [`Fontconfig::app_font_add_file`] or
+    /// [`Fontconfig::load_config_str`], so that tests and sandboxed applications match only
+    /// against fonts they chose. See [`SandboxFonts`] for a configuration that also exposes
+    /// some of the installed fonts.
+    ///
+    /// If Fontconfig fails to initialise, returns `None`.
+    ///
+    /// ```
+    /// use fontconfig::{list_fonts, Fontconfig, Pattern};
+    ///
+    /// let font = Fontconfig::new().unwrap().find("DejaVu Sans", None).unwrap();
+    /// let mut fc = Fontconfig::empty().unwrap();
+    /// assert_eq!(list_fonts(&Pattern::new(&fc), None).iter().count(), 0);
+    /// fc.app_font_add_file(&font.path).unwrap();
+    /// assert_eq!(list_fonts(&Pattern::new(&fc), None).iter().count(), 1);
+    /// ```
+    pub fn empty() -> Option<Self> {
+        if !Fontconfig::loaded() {
+            return None;
+        }
+        let config = unsafe { ffi_dispatch!(LIB, FcConfigCreate,) };
+        if config.is_null() {
+            return None;
+        }
+        let mut fc = Fontconfig {
+            config,
+            owns_config: true,
+            generation: next_generation(),
+        };
+        fc.build_fonts().ok()?;
+        Some(fc)
+    }
+
+    /// Return a handle shared by the whole process, initialising Fontconfig on first use.
+    ///
+    /// This allows libraries to use Fontconfig without a handle being passed down to them. If
+    /// Fontconfig fails to initialise, returns `None`, and will do so on every subsequent call.
+    ///
+    /// Unlike a handle from [`Fontconfig::new`], this handle uses Fontconfig's current
+    /// configuration, looking it up on every call, as it is shared with the rest of the
+    /// process. It follows Fontconfig's rescans, and [`Fontconfig::reload`] brings it up to date
+    /// without exclusive access.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::global().unwrap();
+    /// let font = fc.find("sans-serif", None).unwrap();
+    /// println!("Path: {}", font.path.display());
+    /// ```
+    pub fn global() -> Option<&'static Fontconfig> {
+        static GLOBAL: OnceLock<Option<Fontconfig>> = OnceLock::new();
+        GLOBAL.get_or_init(Fontconfig::current).as_ref()
+    }
+
     /// Find a font of the given `family` (e.g. Dejavu Sans, FreeSerif),
     /// optionally filtering by `style`. Both fields are case-insensitive.
     pub fn find(&self, family: &str, style: Option<&str>) -> Option<Font> {
@@ -119,6 +474,14 @@ impl Fontconfig {
     }
 }
 
+impl Drop for Fontconfig {
+    fn drop(&mut self) {
+        if !self.config.is_null() {
+            unsafe { ffi_dispatch!(LIB, FcConfigDestroy, self.config) }
+        }
+    }
+}
+
 /// A very high-level view of a font, only concerned with the name and its file location.
 ///
 /// ##Example
@@ -129,6 +492,8 @@ impl Fontconfig {
 /// let font = fc.find("sans-serif", Some("italic")).unwrap();
 /// println!("Name: {}\nPath: {}", font.name, font.path.display());
 /// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     /// The true name of this font
     pub name: String,
@@ -136,30 +501,98 @@ pub struct Font {
     pub path: PathBuf,
     /// The index of the font within the file.
     pub index: Option<i32>,
+    /// The generation of the configuration this font was found in.
+    ///
+    /// This is not compared by `==` and is not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub generation: Generation,
+}
+
+impl PartialEq for Font {
+    fn eq(&self, other: &Font) -> bool {
+        self.name == other.name && self.path == other.path && self.index == other.index
+    }
+}
+
+impl Eq for Font {}
+
+/// The location of a font: the file containing it and the index of the face within that file.
+///
+/// This is the information a rasterizer needs to load the selected font, and mirrors the
+/// `Handle::Path` variant used by font-kit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    /// The location of the font file on the filesystem.
+    pub path: PathBuf,
+    /// The index of the face within the file. For variable fonts, the upper 16 bits hold the
+    /// named instance, as understood by FreeType.
+    pub index: u32,
 }
 
 impl Font {
+    /// Get the location of this font as a `Handle`.
+    pub fn handle(&self) -> Handle {
+        Handle {
+            path: self.path.clone(),
+            index: self.index.unwrap_or(0) as u32,
+        }
+    }
+
     fn find(fc: &Fontconfig, family: &str, style: Option<&str>) -> Option<Font> {
         let mut pat = Pattern::new(fc);
-        let family = CString::new(family).ok()?;
-        pat.add_string(FC_FAMILY, &family);
-
+        pat.add_string(FC_FAMILY, family);
         if let Some(style) = style {
-            let style = CString::new(style).ok()?;
-            pat.add_string(FC_STYLE, &style);
+            pat.add_string(FC_STYLE, style);
         }
 
-        let font_match = pat.font_match();
+        let font_match = pat.try_font_match().ok()?;
+        Font::from_pattern(&font_match)
+    }
 
-        font_match.name().and_then(|name| {
-            font_match.filename().map(|filename| Font {
+    fn from_pattern(pattern: &Pattern) -> Option<Font> {
+        pattern.name().and_then(|name| {
+            pattern.file_path().map(|path| Font {
                 name: name.to_owned(),
-                path: PathBuf::from(filename),
-                index: font_match.face_index(),
+                path,
+                index: pattern.face_index(),
+                generation: pattern.fc.generation(),
             })
         })
     }
 
+    /// Returns `true` if this font was found in the configuration `fc` is using now, so it is
+    /// still installed unless the font files were changed without the configuration being
+    /// reloaded.
+    ///
+    /// Use [`Font::revalidate`] to look the font up again when this returns `false`.
+    pub fn is_current(&self, fc: &Fontconfig) -> bool {
+        self.generation == fc.generation()
+    }
+
+    /// Check that this font, for example one restored from saved settings, is still installed.
+    ///
+    /// Returns the font as it is currently known to Fontconfig. If no font is installed at the
+    /// recorded path and index any more, the font is looked up again by name, in case it has
+    /// been moved or upgraded. Returns `None` if neither lookup finds the font.
+    pub fn revalidate(&self, fc: &Fontconfig) -> Option<Font> {
+        let mut objects = ObjectSet::new(fc);
+        for object in [FC_FULLNAME, FC_FILE, FC_INDEX] {
+            objects.add(object);
+        }
+
+        let mut by_path = Pattern::new(fc);
+        by_path.add_string(FC_FILE, &path_to_cstring(&self.path)?);
+        by_path.add_integer(FC_INDEX, self.index.unwrap_or(0));
+        let mut by_name = Pattern::new(fc);
+        by_name.add_string(FC_FULLNAME, &self.name);
+
+        [by_path, by_name].iter().find_map(|pattern| {
+            let fonts = list_fonts(pattern, Some(&objects));
+            let font = fonts.iter().find_map(|font| Font::from_pattern(&font));
+            font
+        })
+    }
+
     #[allow(dead_code)]
     fn print_debug(&self) {
         println!(
@@ -172,6 +605,20 @@ impl Font {
 }
 
 /// A safe wrapper around fontconfig's `FcPattern`.
+///
+/// A `Pattern` is neither `Send` nor `Sync`, as it may share its `FcPattern` with other patterns,
+/// such as the patterns in a [`FontSet`] it was iterated from. Convert a pattern to a
+/// [`SharedPattern`] to pass it between threads.
+///
+/// # Adding values
+///
+/// The `add` methods return nothing, although the `FcPatternAdd` functions report whether they
+/// added the value. They fail only in two cases. One is running out of memory, which Rust's own
+/// collections treat as fatal rather than as an error to return. The other is a value whose type
+/// an element known to Fontconfig does not accept, such as a string for "weight". Fontconfig
+/// then prints a warning and leaves the value out, and reading the element with a `try_get`
+/// method reports the missing value as [`Error::NoMatch`]. Names and strings holding a NUL
+/// byte, which Fontconfig cannot represent, are left out in the same way.
 #[repr(C)]
 pub struct Pattern<'fc> {
     /// Raw pointer to `FcPattern`
@@ -181,7 +628,7 @@ pub struct Pattern<'fc> {
 
 impl<'fc> Pattern<'fc> {
     /// Create a new empty `Pattern`.
-    pub fn new(fc: &Fontconfig) -> Pattern {
+    pub fn new(fc: &Fontconfig) -> Pattern<'_> {
         let pat = unsafe { ffi_dispatch!(LIB, FcPatternCreate,) };
         assert!(!pat.is_null());
 
@@ -192,19 +639,73 @@ impl<'fc> Pattern<'fc> {
     ///
     /// The pattern is referenced.
     ///
-    /// **Safety:** The pattern pointer must be valid/non-null.
-    pub unsafe fn from_pattern(fc: &Fontconfig, pat: *mut FcPattern) -> Pattern {
+    /// # Safety
+    ///
+    /// The pattern pointer must be valid/non-null.
+    pub unsafe fn from_pattern(fc: &Fontconfig, pat: *mut FcPattern) -> Pattern<'_> {
         ffi_dispatch!(LIB, FcPatternReference, pat);
 
         Pattern { pat, fc }
     }
 
+    /// Borrow a raw fontconfig FcPattern pointer as a `Pattern`, without taking a reference.
+    ///
+    /// This is intended for patterns owned by other libraries, such as the one returned by
+    /// `pango_fc_font_get_pattern`. The pattern is neither referenced nor destroyed, so the
+    /// caller chooses the lifetime `'a` for which it may be inspected.
+    ///
+    /// # Safety
+    ///
+    /// The pattern pointer must be valid/non-null, and the pattern must not be destroyed or
+    /// modified while the returned value is alive.
+    pub unsafe fn from_raw_borrowed<'a>(fc: &'a Fontconfig, pat: *mut FcPattern) -> PatternRef<'a> {
+        PatternRef {
+            pattern: ManuallyDrop::new(Pattern { pat, fc }),
+        }
+    }
+
+    /// Query the font at `index` within the file at `path`, returning a pattern describing it.
+    ///
+    /// The font does not need to be installed or known to Fontconfig. Returns `None` if the file
+    /// cannot be read or contains no font at `index`.
+    ///
+    /// Fontconfig reads the file with the FreeType it links to itself, so this is available
+    /// without the `freetype` feature, which adds `Pattern::from_ft_face` for a face that is
+    /// already loaded.
+    pub fn from_file(
+        fc: &'fc Fontconfig,
+        path: impl AsRef<Path>,
+        index: i32,
+    ) -> Option<Pattern<'fc>> {
+        let file = path_to_cstring(path.as_ref())?;
+        let mut count = 0;
+        let pat = unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcFreeTypeQuery,
+                file.as_ptr() as *const u8,
+                index,
+                ptr::null_mut(),
+                &mut count
+            )
+        };
+        if pat.is_null() {
+            None
+        } else {
+            Some(Pattern { pat, fc })
+        }
+    }
+
     /// Add a key-value pair of type `String` to this pattern.
     ///
-    /// See useful keys in the [fontconfig reference][1].
+    /// See useful keys in the [fontconfig reference][1], and [Adding values](Pattern#adding-values)
+    /// for when the value is left out.
     ///
     /// [1]: http://www.freedesktop.org/software/fontconfig/fontconfig-devel/x19.html
-    pub fn add_string(&mut self, name: &CStr, val: &CStr) {
+    pub fn add_string<'a, 'b>(&mut self, name: impl IntoFcStr<'a>, val: impl IntoFcStr<'b>) {
+        let (Some(name), Some(val)) = (name.into_fc_str(), val.into_fc_str()) else {
+            return;
+        };
         unsafe {
             ffi_dispatch!(
                 LIB,
@@ -218,7 +719,8 @@ impl<'fc> Pattern<'fc> {
 
     /// Add a key-value pair of type `Int` to this pattern
     ///
-    /// See useful keys in the [fontconfig reference][1].
+    /// See useful keys in the [fontconfig reference][1], and [Adding values](Pattern#adding-values)
+    /// for when the value is left out.
     ///
     /// [1]: http://www.freedesktop.org/software/fontconfig/fontconfig-devel/x19.html
     pub fn add_integer(&mut self, name: &CStr, val: c_int) {
@@ -227,44 +729,168 @@ impl<'fc> Pattern<'fc> {
         }
     }
 
+    /// Add a key-value pair of type `Double` to this pattern
+    ///
+    /// See useful keys in the [fontconfig reference][1], and [Adding values](Pattern#adding-values)
+    /// for when the value is left out.
+    ///
+    /// [1]: http://www.freedesktop.org/software/fontconfig/fontconfig-devel/x19.html
+    pub fn add_double(&mut self, name: &CStr, val: f64) {
+        unsafe {
+            ffi_dispatch!(LIB, FcPatternAddDouble, self.pat, name.as_ptr(), val);
+        }
+    }
+
+    /// Add a key-value pair of type `Bool` to this pattern
+    ///
+    /// See useful keys in the [fontconfig reference][1], and [Adding values](Pattern#adding-values)
+    /// for when the value is left out.
+    ///
+    /// [1]: http://www.freedesktop.org/software/fontconfig/fontconfig-devel/x19.html
+    pub fn add_bool(&mut self, name: &CStr, val: bool) {
+        let val = if val { FcTrue } else { FcFalse };
+        unsafe {
+            ffi_dispatch!(LIB, FcPatternAddBool, self.pat, name.as_ptr(), val);
+        }
+    }
+
+    /// Remove all values of a key from this pattern.
+    ///
+    /// Returns `true` if the pattern held the key.
+    pub fn del(&mut self, name: &CStr) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcPatternDel, self.pat, name.as_ptr()) == FcTrue }
+    }
+
+    /// Remove the value at index `n` of a key from this pattern, keeping its other values.
+    ///
+    /// Returns `true` if the key had a value at that index.
+    pub fn remove(&mut self, name: &CStr, n: c_int) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcPatternRemove, self.pat, name.as_ptr(), n) == FcTrue }
+    }
+
+    /// Create a copy of this pattern holding only the elements in `objects`.
+    ///
+    /// If `objects` is `None` the whole pattern is copied.
+    pub fn filter(&self, objects: Option<&ObjectSet>) -> Pattern<'fc> {
+        let os = objects.map_or(ptr::null(), ObjectSet::as_ptr);
+        let pat = unsafe { ffi_dispatch!(LIB, FcPatternFilter, self.pat, os) };
+        assert!(!pat.is_null());
+        Pattern { pat, fc: self.fc }
+    }
+
     /// Get string the value for a key from this pattern.
     pub fn get_string<'a>(&'a self, name: &'a CStr) -> Option<&'a str> {
+        self.get_string_at(name, 0)
+    }
+
+    /// Get the string value at position `n` for a key from this pattern.
+    ///
+    /// Returns `None` if the value is not valid UTF-8.
+    fn get_string_at<'a>(&'a self, name: &CStr, n: c_int) -> Option<&'a str> {
+        self.try_get_string(name, n).ok()
+    }
+
+    /// Get the string value at position `n` for a key from this pattern.
+    ///
+    /// Unlike [`Pattern::get_string`], the error tells whether the key is missing, has fewer
+    /// values, holds another type or holds a string that is not valid UTF-8.
+    pub fn try_get_string(&self, name: &CStr, n: c_int) -> Result<&str> {
+        Ok(self.try_get_cstr(name, n)?.to_str()?)
+    }
+
+    /// Get the string value at position `n` for a key from this pattern, without checking
+    /// that it is valid UTF-8.
+    ///
+    /// The value is borrowed from the pattern without copying it. This is useful in hot loops
+    /// that only compare or hash values, such as deduplicating many patterns by file name.
+    pub fn get_cstr(&self, name: &CStr, n: c_int) -> Option<&CStr> {
+        self.try_get_cstr(name, n).ok()
+    }
+
+    /// Get the string value at position `n` for a key from this pattern, without checking
+    /// that it is valid UTF-8, or the reason there is none.
+    pub fn try_get_cstr(&self, name: &CStr, n: c_int) -> Result<&CStr> {
         unsafe {
             let mut ret: *mut sys::FcChar8 = ptr::null_mut();
-            if ffi_dispatch!(
+            ffi_dispatch!(
                 LIB,
                 FcPatternGetString,
                 self.pat,
                 name.as_ptr(),
-                0,
+                n,
                 &mut ret as *mut _
-            ) == sys::FcResultMatch
-            {
-                let cstr = CStr::from_ptr(ret as *const c_char);
-                Some(cstr.to_str().unwrap())
-            } else {
-                None
-            }
+            )
+            .to_result()?;
+            Ok(CStr::from_ptr(ret as *const c_char))
         }
     }
 
     /// Get the integer value for a key from this pattern.
     pub fn get_int(&self, name: &CStr) -> Option<i32> {
+        self.try_get_int(name, 0).ok()
+    }
+
+    /// Get the integer value at position `n` for a key from this pattern, or the reason there
+    /// is none.
+    pub fn try_get_int(&self, name: &CStr, n: c_int) -> Result<i32> {
         unsafe {
             let mut ret: i32 = 0;
-            if ffi_dispatch!(
+            ffi_dispatch!(
                 LIB,
                 FcPatternGetInteger,
                 self.pat,
                 name.as_ptr(),
-                0,
+                n,
                 &mut ret as *mut i32
-            ) == sys::FcResultMatch
-            {
-                Some(ret)
-            } else {
-                None
-            }
+            )
+            .to_result()?;
+            Ok(ret)
+        }
+    }
+
+    /// Get the double value for a key from this pattern.
+    pub fn get_double(&self, name: &CStr) -> Option<f64> {
+        self.try_get_double(name, 0).ok()
+    }
+
+    /// Get the double value at position `n` for a key from this pattern, or the reason there
+    /// is none.
+    pub fn try_get_double(&self, name: &CStr, n: c_int) -> Result<f64> {
+        unsafe {
+            let mut ret: f64 = 0.0;
+            ffi_dispatch!(
+                LIB,
+                FcPatternGetDouble,
+                self.pat,
+                name.as_ptr(),
+                n,
+                &mut ret as *mut f64
+            )
+            .to_result()?;
+            Ok(ret)
+        }
+    }
+
+    /// Get the boolean value for a key from this pattern.
+    pub fn get_bool(&self, name: &CStr) -> Option<bool> {
+        self.try_get_bool(name, 0).ok()
+    }
+
+    /// Get the boolean value at position `n` for a key from this pattern, or the reason there
+    /// is none.
+    pub fn try_get_bool(&self, name: &CStr, n: c_int) -> Result<bool> {
+        unsafe {
+            let mut ret: FcBool = FcFalse;
+            ffi_dispatch!(
+                LIB,
+                FcPatternGetBool,
+                self.pat,
+                name.as_ptr(),
+                n,
+                &mut ret as *mut FcBool
+            )
+            .to_result()?;
+            Ok(ret != FcFalse)
         }
     }
 
@@ -286,24 +912,145 @@ impl<'fc> Pattern<'fc> {
             ffi_dispatch!(
                 LIB,
                 FcConfigSubstitute,
-                ptr::null_mut(),
+                self.fc.config,
                 self.pat,
                 sys::FcMatchPattern
             );
         }
     }
 
-    /// Get the best available match for this pattern, returned as a new pattern.
-    pub fn font_match(&mut self) -> Pattern {
-        self.default_substitute();
+    /// Prepare this pattern for matching or sorting.
+    ///
+    /// The configuration is applied first and the defaults fill in whatever it left unset, which
+    /// is the order Fontconfig requires. Each substitution must only run once, as running them
+    /// again appends duplicate values to the pattern.
+    fn substitute(&mut self) {
         self.config_substitute();
+        self.default_substitute();
+    }
+
+    /// Get the best available match for this pattern, returned as a new pattern.
+    ///
+    /// The configuration and default substitutions are applied to a copy of this pattern, so
+    /// the pattern itself is left unchanged and can be matched repeatedly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no font matched, which only happens when no fonts are installed. Use
+    /// [`Pattern::try_font_match`] to handle that case.
+    pub fn font_match(&self) -> Pattern<'fc> {
+        self.try_font_match().expect("no font matched")
+    }
+
+    /// Get the best available match for this pattern, or the reason there is none.
+    ///
+    /// Returns [`Error::NoMatch`] if no fonts are installed.
+    pub fn try_font_match(&self) -> Result<Pattern<'fc>> {
+        let mut pattern = self.clone();
+        pattern.substitute();
 
         unsafe {
             let mut res = sys::FcResultNoMatch;
-            Pattern::from_pattern(
-                self.fc,
-                ffi_dispatch!(LIB, FcFontMatch, ptr::null_mut(), self.pat, &mut res),
-            )
+            let pat = ffi_dispatch!(LIB, FcFontMatch, self.fc.config, pattern.pat, &mut res);
+            if pat.is_null() {
+                res.to_result()?;
+                return Err(Error::NoMatch);
+            }
+            // The match is a new pattern that we own, so it must not be referenced again.
+            Ok(Pattern { pat, fc: self.fc })
+        }
+    }
+
+    /// Get the best available match for this pattern at a point size on a particular display.
+    ///
+    /// `dpi` is the resolution of the display and `scale` is the UI scale factor, e.g. 2.0 on a
+    /// HiDPI display. These replace any size, pixel size, DPI and scale already in the pattern,
+    /// so the size is not computed from Fontconfig's default DPI of 75. This pattern itself is
+    /// left unchanged.
+    ///
+    /// Returns the matched pattern together with its pixel size in device pixels.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_string(FC_FAMILY, c"sans-serif");
+    /// let (font, pixel_size) = pat.font_match_sized(12.0, 96.0, 2.0);
+    /// println!("{:?} at {}px", font.name(), pixel_size);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no font matched, which only happens when no fonts are installed. Use
+    /// [`Pattern::try_font_match_sized`] to handle that case.
+    pub fn font_match_sized(&self, point_size: f64, dpi: f64, scale: f64) -> (Pattern<'fc>, f64) {
+        self.try_font_match_sized(point_size, dpi, scale)
+            .expect("no font matched")
+    }
+
+    /// Get the best available match for this pattern at a point size on a particular display,
+    /// or the reason there is none.
+    ///
+    /// This is [`Pattern::font_match_sized`] returning [`Error::NoMatch`] if no fonts are
+    /// installed.
+    pub fn try_font_match_sized(
+        &self,
+        point_size: f64,
+        dpi: f64,
+        scale: f64,
+    ) -> Result<(Pattern<'fc>, f64)> {
+        let mut pattern = self.clone();
+        for name in [FC_SIZE, FC_PIXEL_SIZE, FC_DPI, FC_SCALE] {
+            pattern.del(name);
+        }
+        pattern.add_double(FC_SIZE, point_size);
+        pattern.add_double(FC_DPI, dpi);
+        pattern.add_double(FC_SCALE, scale);
+
+        let font = pattern.try_font_match()?;
+        // Configuration may have adjusted the pixel size computed by the default substitution.
+        let pixel_size = font
+            .get_double(FC_PIXEL_SIZE)
+            .unwrap_or(point_size * scale * dpi / 72.0);
+        Ok((font, pixel_size))
+    }
+
+    /// Get the canonical form of this pattern after the configuration and default substitutions.
+    ///
+    /// The substitutions are applied to a copy, which is then written out in Fontconfig's name
+    /// syntax with its elements in a fixed order. Patterns that select fonts in the same way,
+    /// however their elements were spelled or ordered, give the same string, so it can be used as
+    /// a key when caching the results of matching. The string depends on the configuration and
+    /// on the environment, such as the locale, so it should not be stored across runs. Elements
+    /// Fontconfig does not know about are left out.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_WEIGHT, FC_WEIGHT_BOLD};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut a = Pattern::new(&fc);
+    /// a.add(FC_FAMILY, "sans-serif");
+    /// a.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+    /// let mut b = Pattern::new(&fc);
+    /// b.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+    /// b.add(FC_FAMILY, "sans-serif");
+    /// assert_eq!(a.normalized(), b.normalized());
+    /// ```
+    pub fn normalized(&self) -> String {
+        let mut pattern = self.clone();
+        pattern.substitute();
+        pattern.unparse().to_string_lossy().into_owned()
+    }
+
+    /// Write this pattern out in Fontconfig's name syntax.
+    fn unparse(&self) -> CString {
+        unsafe {
+            let fcstr = ffi_dispatch!(LIB, FcNameUnparse, self.pat);
+            assert!(!fcstr.is_null());
+            let unparsed = CStr::from_ptr(fcstr as *const c_char).to_owned();
+            ffi_dispatch!(LIB, FcStrFree, fcstr);
+            unparsed
         }
     }
 
@@ -317,6 +1064,13 @@ impl<'fc> Pattern<'fc> {
         self.get_string(FC_FILE)
     }
 
+    /// Get the "file" of this font pattern as a path.
+    ///
+    /// Unlike [`Pattern::filename`], this also returns paths that are not valid UTF-8.
+    pub fn file_path(&self) -> Option<PathBuf> {
+        self.try_get_cstr(FC_FILE, 0).ok().map(cstr_to_path)
+    }
+
     /// Get the "index" (The index of the font within the file) of this pattern.
     pub fn face_index(&self) -> Option<i32> {
         self.get_int(FC_INDEX)
@@ -337,11 +1091,26 @@ impl<'fc> Pattern<'fc> {
         self.get_int(FC_WIDTH)
     }
 
+    /// Get the "spacing" (proportional, dual, mono or charcell) of this pattern.
+    ///
+    /// Returns `None` if the pattern has no spacing or holds a value Fontconfig does not define.
+    pub fn spacing(&self) -> Option<Spacing> {
+        Spacing::from_value(self.get_int(FC_SPACING)?)
+    }
+
     /// Get the "fontformat" ("TrueType" "Type 1" "BDF" "PCF" "Type 42" "CID Type 1" "CFF" "PFR" "Windows FNT") of this pattern.
-    pub fn format(&self) -> Result<FontFormat, UnknownFontFormat> {
-        self.get_string(FC_FONTFORMAT)
-            .ok_or_else(|| UnknownFontFormat(String::new()))
-            .and_then(|format| format.parse())
+    pub fn format(&self) -> Result<FontFormat> {
+        Ok(self.try_get_string(FC_FONTFORMAT, 0)?.parse()?)
+    }
+
+    /// Get the location of this font pattern as a `Handle`.
+    ///
+    /// Returns `None` if the pattern has no file.
+    pub fn handle(&self) -> Option<Handle> {
+        Some(Handle {
+            path: self.file_path()?,
+            index: self.face_index().unwrap_or(0) as u32,
+        })
     }
 
     /// Returns a raw pointer to underlying `FcPattern`.
@@ -355,13 +1124,30 @@ impl<'fc> Pattern<'fc> {
     }
 }
 
+/// A `Pattern` borrowed from a raw pointer by [`Pattern::from_raw_borrowed`].
+///
+/// It dereferences to `Pattern` and does not destroy the pattern when dropped.
+pub struct PatternRef<'a> {
+    pattern: ManuallyDrop<Pattern<'a>>,
+}
+
+impl<'a> Deref for PatternRef<'a> {
+    type Target = Pattern<'a>;
+
+    fn deref(&self) -> &Pattern<'a> {
+        &self.pattern
+    }
+}
+
+impl<'a> std::fmt::Debug for PatternRef<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.pattern.fmt(f)
+    }
+}
+
 impl<'fc> std::fmt::Debug for Pattern<'fc> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let fcstr = unsafe { ffi_dispatch!(LIB, FcNameUnparse, self.pat) };
-        let fcstr = unsafe { CStr::from_ptr(fcstr as *const c_char) };
-        let result = write!(f, "{:?}", fcstr);
-        unsafe { ffi_dispatch!(LIB, FcStrFree, fcstr.as_ptr() as *mut u8) };
-        result
+        write!(f, "{:?}", self.unparse())
     }
 }
 
@@ -375,6 +1161,22 @@ impl<'fc> Clone for Pattern<'fc> {
     }
 }
 
+/// Patterns are equal if they hold the same values in the same order for each element.
+impl PartialEq for Pattern<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcPatternEqual, self.pat, other.pat) == FcTrue }
+    }
+}
+
+/// A pattern holding a NaN double is not equal to itself, which Fontconfig never produces.
+impl Eq for Pattern<'_> {}
+
+impl Hash for Pattern<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(unsafe { ffi_dispatch!(LIB, FcPatternHash, self.pat) });
+    }
+}
+
 impl<'fc> Drop for Pattern<'fc> {
     fn drop(&mut self) {
         unsafe {
@@ -385,7 +1187,10 @@ impl<'fc> Drop for Pattern<'fc> {
 
 impl Pattern<'_> {
     /// Get the languages set of this pattern.
-    pub fn lang_set(&self) -> Option<StrList<'_>> {
+    ///
+    /// The set is a copy owned by the caller, which frees it when it is dropped, and its
+    /// strings borrow from it rather than from the pattern.
+    pub fn lang_set(&self) -> Option<StrSet> {
         unsafe {
             let mut ret: *mut sys::FcLangSet = ptr::null_mut();
             if ffi_dispatch!(
@@ -397,9 +1202,7 @@ impl Pattern<'_> {
                 &mut ret as *mut _
             ) == sys::FcResultMatch
             {
-                let ss: *mut sys::FcStrSet = ffi_dispatch!(LIB, FcLangSetGetLangs, ret);
-                let lang_strs: *mut sys::FcStrList = ffi_dispatch!(LIB, FcStrListCreate, ss);
-                Some(StrList::from_raw(self.fc, lang_strs))
+                Some(StrSet::from_raw(ffi_dispatch!(LIB, FcLangSetGetLangs, ret)))
             } else {
                 None
             }
@@ -407,10 +1210,9 @@ impl Pattern<'_> {
     }
 }
 
-/// Wrapper around `FcStrList`
+/// Wrapper around an owned `FcStrSet`, which is destroyed when the wrapper is dropped.
 ///
-/// The wrapper implements `Iterator` so it can be iterated directly, filtered etc.
-/// **Note:** Any entries in the `StrList` that are not valid UTF-8 will be skipped.
+/// The strings are borrowed from the set, so they are iterated with [`StrSet::iter`].
 ///
 /// ```
 /// use fontconfig::{Fontconfig, Pattern};
@@ -421,21 +1223,86 @@ impl Pattern<'_> {
 /// let fonts = fontconfig::list_fonts(&Pattern::new(&fc), None);
 /// let ja_fonts: Vec<_> = fonts
 ///     .iter()
-///     .filter(|p| p.lang_set().map_or(false, |mut langs| langs.any(|l| l == "ja")))
+///     .filter(|p| p.lang_set().map_or(false, |langs| langs.iter().any(|l| l == "ja")))
 ///     .collect();
 /// ```
+///
+/// A `StrSet` is not `Send`, as Fontconfig does not count references to it atomically.
+pub struct StrSet {
+    set: *mut sys::FcStrSet,
+}
+
+impl StrSet {
+    /// Take ownership of a string set, such as one returned by `FcLangSetGetLangs`.
+    unsafe fn from_raw(raw_set: *mut sys::FcStrSet) -> StrSet {
+        assert!(!raw_set.is_null());
+        StrSet { set: raw_set }
+    }
+
+    /// Iterate the strings in this set.
+    pub fn iter(&self) -> StrList<'_> {
+        unsafe {
+            // The list holds a reference to the set, which it releases when it is done.
+            let list = ffi_dispatch!(LIB, FcStrListCreate, self.set);
+            assert!(!list.is_null());
+            StrList {
+                list,
+                _life: PhantomData,
+            }
+        }
+    }
+}
+
+impl Drop for StrSet {
+    fn drop(&mut self) {
+        unsafe { ffi_dispatch!(LIB, FcStrSetDestroy, self.set) };
+    }
+}
+
+impl<'a> IntoIterator for &'a StrSet {
+    type Item = &'a str;
+    type IntoIter = StrList<'a>;
+
+    fn into_iter(self) -> StrList<'a> {
+        self.iter()
+    }
+}
+
+/// Wrapper around `FcStrList`, an iterator over the strings of a [`StrSet`].
+///
+/// The wrapper implements `Iterator` so it can be iterated directly, filtered etc.
+/// **Note:** Any entries in the `StrList` that are not valid UTF-8 will be skipped.
+///
+/// A `StrList` is not `Send`, as Fontconfig does not count references to the underlying string
+/// set atomically.
 pub struct StrList<'a> {
     list: *mut sys::FcStrList,
     _life: PhantomData<&'a sys::FcStrList>,
 }
 
 impl<'a> StrList<'a> {
-    unsafe fn from_raw(_: &Fontconfig, raw_list: *mut sys::FcStrSet) -> Self {
-        Self {
-            list: raw_list,
-            _life: PhantomData,
+    fn next_cstr(&mut self) -> Option<&'a CStr> {
+        let s: *mut sys::FcChar8 = unsafe { ffi_dispatch!(LIB, FcStrListNext, self.list) };
+        if s.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(s as *const c_char) })
         }
     }
+
+    /// Iterate the strings without checking that they are valid UTF-8.
+    ///
+    /// Unlike iterating the list itself, this yields every string, and costs nothing per item.
+    pub fn cstrs(mut self) -> impl Iterator<Item = &'a CStr> {
+        std::iter::from_fn(move || self.next_cstr())
+    }
+
+    /// Iterate the strings, replacing any invalid UTF-8 with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Strings are only copied if they need to be repaired.
+    pub fn lossy(self) -> impl Iterator<Item = Cow<'a, str>> {
+        self.cstrs().map(CStr::to_string_lossy)
+    }
 }
 
 impl<'a> Drop for StrList<'a> {
@@ -447,14 +1314,11 @@ impl<'a> Drop for StrList<'a> {
 impl<'a> Iterator for StrList<'a> {
     type Item = &'a str;
 
+    /// Returns the next string, skipping any that are not valid UTF-8.
     fn next(&mut self) -> Option<&'a str> {
-        let lang_str: *mut sys::FcChar8 = unsafe { ffi_dispatch!(LIB, FcStrListNext, self.list) };
-        if lang_str.is_null() {
-            None
-        } else {
-            match unsafe { CStr::from_ptr(lang_str as *const c_char) }.to_str() {
-                Ok(s) => Some(s),
-                _ => self.next(),
+        loop {
+            if let Ok(s) = self.next_cstr()?.to_str() {
+                return Some(s);
             }
         }
     }
@@ -468,7 +1332,7 @@ pub struct FontSet<'fc> {
 
 impl<'fc> FontSet<'fc> {
     /// Create a new, empty `FontSet`.
-    pub fn new(fc: &Fontconfig) -> FontSet {
+    pub fn new(fc: &Fontconfig) -> FontSet<'_> {
         let fcset = unsafe { ffi_dispatch!(LIB, FcFontSetCreate,) };
         FontSet { fcset, fc }
     }
@@ -477,11 +1341,56 @@ impl<'fc> FontSet<'fc> {
     ///
     /// The returned wrapper assumes ownership of the `FcFontSet`.
     ///
-    /// **Safety:** The font set pointer must be valid/non-null.
-    pub unsafe fn from_raw(fc: &Fontconfig, raw_set: *mut sys::FcFontSet) -> FontSet {
+    /// # Safety
+    ///
+    /// The font set pointer must be valid/non-null.
+    pub unsafe fn from_raw(fc: &Fontconfig, raw_set: *mut sys::FcFontSet) -> FontSet<'_> {
         FontSet { fcset: raw_set, fc }
     }
 
+    /// Query every font in the file at `path`, including the named instances of variable fonts.
+    ///
+    /// The fonts do not need to be installed or known to Fontconfig. The returned set is empty if
+    /// the file cannot be read or contains no fonts. Named instances are left out if the loaded
+    /// Fontconfig is older than 2.12.91.
+    pub fn from_file(fc: &Fontconfig, path: impl AsRef<Path>) -> FontSet<'_> {
+        let set = FontSet::new(fc);
+        if let Some(file) = path_to_cstring(path.as_ref()) {
+            let mut count = 0;
+            unsafe {
+                if sys::available::FcFreeTypeQueryAll() {
+                    ffi_dispatch_optional!(
+                        FcFreeTypeQueryAll,
+                        file.as_ptr() as *const u8,
+                        -1,
+                        ptr::null_mut(),
+                        &mut count,
+                        set.fcset
+                    );
+                } else {
+                    // Older versions query one face at a time, without the named instances.
+                    let mut id = 0;
+                    while id == 0 || id < count {
+                        let pat = ffi_dispatch!(
+                            LIB,
+                            FcFreeTypeQuery,
+                            file.as_ptr() as *const u8,
+                            id,
+                            ptr::null_mut(),
+                            &mut count
+                        );
+                        if pat.is_null() {
+                            break;
+                        }
+                        ffi_dispatch!(LIB, FcFontSetAdd, set.fcset, pat);
+                        id += 1;
+                    }
+                }
+            }
+        }
+        set
+    }
+
     /// Add a `Pattern` to this `FontSet`.
     pub fn add_pattern(&mut self, pat: Pattern) {
         unsafe {
@@ -497,16 +1406,32 @@ impl<'fc> FontSet<'fc> {
 
     /// Iterate the fonts (as `Patterns`) in this `FontSet`.
     pub fn iter(&self) -> impl Iterator<Item = Pattern<'_>> {
-        let patterns = unsafe {
-            let fontset = self.fcset;
-            std::slice::from_raw_parts((*fontset).fonts, (*fontset).nfont as usize)
-        };
-        patterns
+        unsafe { font_set_patterns(self.fcset) }
             .iter()
             .map(move |&pat| unsafe { Pattern::from_pattern(self.fc, pat) })
     }
 }
 
+/// Returns the patterns in a raw font set.
+///
+/// # Safety
+///
+/// The font set pointer must be valid/non-null, and the set must outlive the returned slice.
+unsafe fn font_set_patterns<'a>(fontset: *const sys::FcFontSet) -> &'a [*mut FcPattern] {
+    if (*fontset).fonts.is_null() {
+        // Empty sets have not allocated any storage.
+        &[]
+    } else {
+        std::slice::from_raw_parts((*fontset).fonts, (*fontset).nfont as usize)
+    }
+}
+
+// SAFETY: A `FontSet` owns its `FcFontSet` and the patterns in it. The patterns yielded by `iter`
+// borrow the set, so none are left behind when it is sent to another thread. It is not `Sync`:
+// those patterns are references to the patterns in the set, so two threads iterating a shared
+// set could modify the same `FcPattern` at once.
+unsafe impl<'fc> Send for FontSet<'fc> {}
+
 impl<'fc> Drop for FontSet<'fc> {
     fn drop(&mut self) {
         unsafe { ffi_dispatch!(LIB, FcFontSetDestroy, self.fcset) }
@@ -515,9 +1440,10 @@ impl<'fc> Drop for FontSet<'fc> {
 
 /// Return a `FontSet` containing Fonts that match the supplied `pattern` and `objects`.
 pub fn list_fonts<'fc>(pattern: &Pattern<'fc>, objects: Option<&ObjectSet>) -> FontSet<'fc> {
-    let os = objects.map(|o| o.fcset).unwrap_or(ptr::null_mut());
+    // FcFontList only reads the object set, despite taking a mutable pointer.
+    let os = objects.map_or(ptr::null_mut(), |o| o.as_ptr() as *mut _);
     unsafe {
-        let raw_set = ffi_dispatch!(LIB, FcFontList, ptr::null_mut(), pattern.pat, os);
+        let raw_set = ffi_dispatch!(LIB, FcFontList, pattern.fc.config, pattern.pat, os);
         FontSet::from_raw(pattern.fc, raw_set)
     }
 }
@@ -532,12 +1458,11 @@ pub fn sort_fonts<'fc>(pattern: &Pattern<'fc>, trim: bool) -> FontSet<'fc> {
     // FcFontSort always returns a (possibly empty) set so we don't need to check this.
     let mut res = sys::FcResultNoMatch;
     let unicode_coverage = ptr::null_mut();
-    let config = ptr::null_mut();
     unsafe {
         let raw_set = ffi_dispatch!(
             LIB,
             FcFontSort,
-            config,
+            pattern.fc.config,
             pattern.pat,
             trim as FcBool,
             unicode_coverage,
@@ -565,18 +1490,39 @@ impl ObjectSet {
     ///
     /// The `FcObjectSet` must not be null. This method assumes ownership of the `FcObjectSet`.
     ///
-    /// **Safety:** The object set pointer must be valid/non-null.
+    /// # Safety
+    ///
+    /// The object set pointer must be valid/non-null.
     pub unsafe fn from_raw(_: &Fontconfig, raw_set: *mut sys::FcObjectSet) -> ObjectSet {
         ObjectSet { fcset: raw_set }
     }
 
     /// Add a string to the `ObjectSet`.
-    pub fn add(&mut self, name: &CStr) {
+    ///
+    /// A name containing a NUL byte is ignored, as no element has one.
+    pub fn add<'a>(&mut self, name: impl IntoFcStr<'a>) {
+        let Some(name) = name.into_fc_str() else {
+            return;
+        };
         let res = unsafe { ffi_dispatch!(LIB, FcObjectSetAdd, self.fcset, name.as_ptr()) };
         assert_eq!(res, FcTrue);
     }
+
+    /// Returns a raw pointer to the underlying `FcObjectSet`.
+    pub fn as_ptr(&self) -> *const sys::FcObjectSet {
+        self.fcset
+    }
+
+    /// Returns an unsafe mutable pointer to the underlying `FcObjectSet`.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::FcObjectSet {
+        self.fcset
+    }
 }
 
+// SAFETY: An `ObjectSet` owns its `FcObjectSet`, which is only modified through `&mut self`.
+unsafe impl Send for ObjectSet {}
+unsafe impl Sync for ObjectSet {}
+
 impl Drop for ObjectSet {
     fn drop(&mut self) {
         unsafe { ffi_dispatch!(LIB, FcObjectSetDestroy, self.fcset) }
@@ -602,15 +1548,252 @@ impl FromStr for FontFormat {
     }
 }
 
+/// Compile tests for the thread-safety of the wrapper types.
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<fontconfig::Pattern>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<fontconfig::Pattern>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<fontconfig::FontSet>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<fontconfig::StrList>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<fontconfig::StrSet>();
+/// ```
+#[cfg(doctest)]
+pub struct ThreadSafetyTests;
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The file of an installed DejaVu family, which the tests rely on.
+    pub(crate) fn dejavu_file(family: &str) -> PathBuf {
+        Fontconfig::new().unwrap().find(family, None).unwrap().path
+    }
+
+    /// The directory of the installed DejaVu fonts.
+    pub(crate) fn dejavu_dir() -> PathBuf {
+        dejavu_file("DejaVu Sans").parent().unwrap().to_owned()
+    }
+
+    /// Build a TrueType variable font with a single empty glyph, with the given axes as
+    /// (tag, min, default, max) and named instances as a style name and coordinates.
+    ///
+    /// It holds the tables FreeType needs to open it and its named instances, so Fontconfig can
+    /// query it, and an `fvar` table describing the axes and instances.
+    pub(crate) fn variable_font(
+        axes: &[(&[u8; 4], f32, f32, f32)],
+        instances: &[(&str, &[f32])],
+    ) -> Vec<u8> {
+        fn table(fields: &[u16]) -> Vec<u8> {
+            fields
+                .iter()
+                .flat_map(|field| field.to_be_bytes())
+                .collect()
+        }
+        let fixed = |value: f32| ((value * 65536.0) as i32).to_be_bytes();
+
+        let mut head = table(&[1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+        head.extend([0; 16]);
+        head.extend(table(&[0, 0, 0, 0, 0, 8, 2, 0, 0]));
+        let hhea = table(&[
+            1, 0, 800, 0xFF38, 0, 500, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1,
+        ]);
+        let maxp = table(&[1, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let hmtx = table(&[500, 0]);
+        let loca = table(&[0, 0]);
+        let glyf = vec![0; 4];
+        // No glyph has variations, so the shared tuples and the variation data are empty.
+        let gvar = table(&[1, 0, axes.len() as u16, 0, 0, 24, 1, 0, 0, 24, 0, 0]);
+
+        let mut names = vec![(1, "Variable Test".to_string()), (2, "Regular".to_string())];
+        names.extend(
+            instances
+                .iter()
+                .enumerate()
+                .map(|(i, (style, _))| (256 + i as u16, style.to_string())),
+        );
+        let mut name = table(&[0, names.len() as u16, 6 + 12 * names.len() as u16]);
+        let mut strings = Vec::new();
+        for (id, string) in &names {
+            let string: Vec<u8> = string.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            // A Windows platform, Unicode BMP, US English record, with its string offset.
+            let record = [3, 1, 0x409, *id, string.len() as u16, strings.len() as u16];
+            name.extend(table(&record));
+            strings.extend(string);
+        }
+        name.extend(strings);
+
+        let mut fvar = table(&[1, 0, 16, 2, axes.len() as u16, 20, instances.len() as u16]);
+        fvar.extend((4 + 4 * axes.len() as u16).to_be_bytes());
+        for (tag, min, default, max) in axes {
+            fvar.extend(*tag);
+            for value in [min, default, max] {
+                fvar.extend(fixed(*value));
+            }
+            fvar.extend([0; 4]);
+        }
+        for (i, (_, coords)) in instances.iter().enumerate() {
+            fvar.extend(table(&[256 + i as u16, 0]));
+            for value in *coords {
+                fvar.extend(fixed(*value));
+            }
+        }
+
+        let tables: [(&[u8; 4], Vec<u8>); 9] = [
+            (b"fvar", fvar),
+            (b"glyf", glyf),
+            (b"gvar", gvar),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+            (b"name", name),
+        ];
+        let mut font = table(&[1, 0, tables.len() as u16, 128, 3, 16]);
+        let mut offset = font.len() + 16 * tables.len();
+        for (tag, data) in &tables {
+            font.extend(*tag);
+            font.extend([0; 4]);
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((data.len() as u32).to_be_bytes());
+            offset += data.len().next_multiple_of(4);
+        }
+        for (_, data) in tables {
+            font.extend(data);
+            font.resize(font.len().next_multiple_of(4), 0);
+        }
+        font
+    }
+
+    #[test]
+    fn thread_safety() {
+        fn assert_send<T: Send>() {}
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Fontconfig>();
+        assert_send_sync::<CharSet>();
+        assert_send_sync::<LangSet>();
+        assert_send_sync::<ObjectSet>();
+        assert_send_sync::<SharedPattern>();
+        assert_send_sync::<FontDatabase>();
+        assert_send_sync::<FontIndex>();
+        assert_send::<FontSet>();
+    }
+
+    #[test]
+    fn match_on_threads() {
+        let fc = Fontconfig::new().unwrap();
+        let expected = fc.find("DejaVu Serif", Some("Bold"));
+        assert!(expected.is_some());
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| fc.find("DejaVu Serif", Some("Bold"))))
+                .collect();
+            for worker in workers {
+                assert_eq!(worker.join().unwrap(), expected);
+            }
+        });
+    }
+
     #[test]
     fn it_works() {
         assert!(Fontconfig::new().is_some())
     }
 
+    #[test]
+    fn rejected_value_is_missing() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_WEIGHT, "bold");
+        assert!(matches!(pat.try_get_int(FC_WEIGHT, 0), Err(Error::NoMatch)));
+        pat.add_integer(FC_WEIGHT, FC_WEIGHT_BOLD);
+        assert_eq!(pat.try_get_int(FC_WEIGHT, 0).unwrap(), FC_WEIGHT_BOLD);
+    }
+
+    #[test]
+    fn own_config_is_isolated() {
+        let mut own = Fontconfig::new().unwrap();
+        let shared = Fontconfig::with_cached_config().unwrap();
+        assert_ne!(own.raw_config(), shared.raw_config());
+        assert_ne!(own.raw_config(), Fontconfig::new().unwrap().raw_config());
+        own.load_config_str(
+            "<fontconfig><alias><family>Isolated Serif</family>\
+             <prefer><family>DejaVu Serif</family></prefer></alias></fontconfig>",
+        )
+        .unwrap();
+        let matched = |fc: &Fontconfig| fc.find("Isolated Serif", None).unwrap().name;
+        assert_eq!(matched(&own), "DejaVu Serif");
+        assert_ne!(matched(&shared), "DejaVu Serif");
+    }
+
+    #[test]
+    fn global_is_shared() {
+        let fc = Fontconfig::global().unwrap();
+        assert!(ptr::eq(fc, Fontconfig::global().unwrap()));
+    }
+
+    #[test]
+    fn cached_config() {
+        let fc = Fontconfig::new().unwrap();
+        let cached = Fontconfig::with_cached_config().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        let mut cached_pat = Pattern::new(&cached);
+        cached_pat.add_string(FC_FAMILY, c"DejaVu Sans");
+
+        assert_eq!(
+            cached_pat.font_match().filename(),
+            pat.font_match().filename()
+        );
+        assert_eq!(
+            list_fonts(&cached_pat, None).iter().count(),
+            list_fonts(&pat, None).iter().count()
+        );
+        assert!(cached
+            .scan_fonts(|font| font.filename().map(drop))
+            .is_some());
+    }
+
+    #[test]
+    fn failure_reasons() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"Example");
+        pat.add_string(FC_STYLE, c"\xff");
+
+        assert_eq!(pat.try_get_string(FC_FAMILY, 0).unwrap(), "Example");
+        assert!(matches!(pat.try_get_string(FC_FAMILY, 1), Err(Error::NoId)));
+        assert!(matches!(
+            pat.try_get_int(FC_FAMILY, 0),
+            Err(Error::TypeMismatch)
+        ));
+        assert!(matches!(pat.try_get_int(FC_WEIGHT, 0), Err(Error::NoMatch)));
+        assert!(matches!(
+            pat.try_get_string(FC_STYLE, 0),
+            Err(Error::InvalidUtf8(_))
+        ));
+        assert!(matches!(pat.format(), Err(Error::NoMatch)));
+
+        let font = pat.try_font_match().unwrap();
+        assert!(font.format().is_ok());
+    }
+
     #[test]
     fn test_find_font() {
         let fc = Fontconfig::new().unwrap();
@@ -620,6 +1803,222 @@ mod tests {
             .print_debug();
     }
 
+    #[test]
+    fn revalidate_font() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let current = font.revalidate(&fc).unwrap();
+        assert_eq!(current.path, font.path);
+
+        let moved = Font {
+            name: font.name.clone(),
+            path: PathBuf::from("/nonexistent/DejaVuSans.ttf"),
+            index: font.index,
+            generation: Generation::default(),
+        };
+        assert_eq!(moved.revalidate(&fc).unwrap().path, font.path);
+
+        let missing = Font {
+            name: String::from("No Such Font"),
+            ..moved
+        };
+        assert!(missing.revalidate(&fc).is_none());
+    }
+
+    #[test]
+    fn font_handle() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let handle = font.handle();
+        assert_eq!(handle.path, font.path);
+
+        let pattern = Pattern::from_file(&fc, &handle.path, handle.index as i32).unwrap();
+        assert_eq!(pattern.handle(), Some(handle));
+        assert_eq!(Pattern::new(&fc).handle(), None);
+    }
+
+    #[test]
+    fn query_file() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+
+        let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+        assert_eq!(pattern.filename(), font.path.to_str());
+        assert_eq!(pattern.get_string(FC_FAMILY), Some("DejaVu Sans"));
+        assert!(Pattern::from_file(&fc, Path::new("/nonexistent.ttf"), 0).is_none());
+
+        let fonts = FontSet::from_file(&fc, &font.path);
+        assert!(fonts.iter().count() >= 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn query_non_utf8_file() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let mut name = b"fontconfig-rs-caf\xe9-".to_vec();
+        name.extend(format!("{}.ttf", std::process::id()).bytes());
+        let path = std::env::temp_dir().join(OsStr::from_bytes(&name));
+        std::fs::copy(&font.path, &path).unwrap();
+
+        let pattern = Pattern::from_file(&fc, &path, 0).unwrap();
+        assert_eq!(pattern.file_path(), Some(path.clone()));
+        assert_eq!(
+            pattern.read_file().unwrap(),
+            std::fs::read(&font.path).unwrap()
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn match_leaves_pattern_unchanged() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        let before = format!("{:?}", pat);
+
+        let first = pat.font_match();
+        let second = pat.font_match();
+        assert_eq!(format!("{:?}", pat), before);
+        assert_eq!(first.name(), Some("DejaVu Sans"));
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    }
+
+    #[test]
+    fn pattern_keys() {
+        use std::collections::HashMap;
+
+        let fc = Fontconfig::new().unwrap();
+        let pattern = |families: &[&str]| {
+            let mut pat = Pattern::new(&fc);
+            for family in families {
+                pat.add(FC_FAMILY, *family);
+            }
+            pat
+        };
+        assert_eq!(pattern(&["a", "b"]), pattern(&["a", "b"]));
+        assert_ne!(pattern(&["a", "b"]), pattern(&["b", "a"]));
+
+        let mut cache = HashMap::new();
+        cache.insert(pattern(&["DejaVu Sans"]), 1);
+        cache.insert(pattern(&["DejaVu Serif"]), 2);
+        assert_eq!(cache.get(&pattern(&["DejaVu Sans"])), Some(&1));
+        assert_eq!(cache.get(&pattern(&["DejaVu Sans", "x"])), None);
+    }
+
+    #[test]
+    fn filter_pattern() {
+        let fc = Fontconfig::new().unwrap();
+        let font = Pattern::new(&fc).font_match();
+        let mut objects = ObjectSet::new(&fc);
+        objects.add(FC_FAMILY);
+
+        let filtered = font.filter(Some(&objects));
+        assert_eq!(filtered.get_string(FC_FAMILY), font.get_string(FC_FAMILY));
+        assert_eq!(filtered.filename(), None);
+        assert_eq!(font.filter(None).filename(), font.filename());
+    }
+
+    #[test]
+    fn get_cstr_values() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"First");
+        pat.add_string(FC_FAMILY, c"Second");
+        assert_eq!(pat.get_cstr(FC_FAMILY, 0), Some(c"First"));
+        assert_eq!(pat.get_cstr(FC_FAMILY, 1), Some(c"Second"));
+        assert_eq!(pat.get_cstr(FC_FAMILY, 2), None);
+        assert_eq!(pat.get_cstr(FC_FILE, 0), None);
+    }
+
+    #[test]
+    fn remove_value() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        for family in [c"First", c"Second", c"Third"] {
+            pat.add_string(FC_FAMILY, family);
+        }
+        assert!(pat.remove(FC_FAMILY, 1));
+        assert_eq!(pat.get_cstr(FC_FAMILY, 0), Some(c"First"));
+        assert_eq!(pat.get_cstr(FC_FAMILY, 1), Some(c"Third"));
+        assert!(!pat.remove(FC_FAMILY, 2));
+        assert!(!pat.remove(FC_FILE, 0));
+        assert!(pat.remove(FC_FAMILY, 0));
+        assert!(pat.remove(FC_FAMILY, 0));
+        assert_eq!(pat.get_cstr(FC_FAMILY, 0), None);
+    }
+
+    #[test]
+    fn typed_spacing() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        assert_eq!(pat.spacing(), None);
+        pat.add_integer(FC_SPACING, FC_CHARCELL);
+        assert_eq!(pat.spacing(), Some(Spacing::CharCell));
+
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans Mono");
+        assert_eq!(pat.font_match().spacing(), Some(Spacing::Mono));
+    }
+
+    #[test]
+    fn match_sized() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        pat.add_double(FC_PIXEL_SIZE, 10.0);
+        let (font, pixel_size) = pat.font_match_sized(12.0, 96.0, 2.0);
+        assert_eq!(pixel_size, 32.0);
+        assert_eq!(font.get_double(FC_DPI), Some(96.0));
+        assert!(font.name().is_some());
+        assert_eq!(pat.get_double(FC_PIXEL_SIZE), Some(10.0));
+        assert_eq!(pat.get_double(FC_DPI), None);
+
+        let empty = Fontconfig::empty().unwrap();
+        let pat = Pattern::new(&empty);
+        assert!(matches!(
+            pat.try_font_match_sized(12.0, 96.0, 2.0),
+            Err(Error::NoMatch)
+        ));
+    }
+
+    #[test]
+    fn normalized_key() {
+        let fc = Fontconfig::new().unwrap();
+        let mut a = Pattern::new(&fc);
+        a.add(FC_FAMILY, "DejaVu Sans");
+        a.add(FC_SLANT, FC_SLANT_ITALIC);
+        a.add(FC_SIZE, 12.0);
+        let mut b = Pattern::new(&fc);
+        b.add(FC_SIZE, 12.0);
+        b.add(FC_SLANT, FC_SLANT_ITALIC);
+        b.add(FC_FAMILY, "DejaVu Sans");
+        assert_eq!(a.normalized(), b.normalized());
+        assert!(a.normalized().starts_with("DejaVu Sans,"));
+        // The pattern itself is not substituted.
+        assert_eq!(format!("{:?}", a), "\"DejaVu Sans-12:slant=100\"");
+
+        b.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+        assert_ne!(a.normalized(), b.normalized());
+    }
+
+    #[test]
+    fn borrow_raw_pattern() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        {
+            let borrowed = unsafe { Pattern::from_raw_borrowed(&fc, pat.as_mut_ptr()) };
+            assert_eq!(borrowed.get_string(FC_FAMILY), Some("DejaVu Sans"));
+            assert_eq!(borrowed.as_ptr(), pat.as_ptr());
+        }
+        // The pattern is still alive after the borrow is dropped.
+        assert_eq!(pat.get_string(FC_FAMILY), Some("DejaVu Sans"));
+    }
+
     #[test]
     fn test_iter_and_print() {
         let fc = Fontconfig::new().unwrap();
@@ -639,19 +2038,21 @@ mod tests {
         let family = CString::new("dejavu sans").unwrap();
         pat.add_string(FC_FAMILY, &family);
         let pattern = pat.font_match();
-        for lang in pattern.lang_set().unwrap() {
+        let set = pattern.lang_set().unwrap();
+        for lang in &set {
             println!("{:?}", lang);
         }
 
         // Test find
-        assert!(pattern
-            .lang_set()
-            .unwrap()
-            .find(|&lang| lang == "za")
-            .is_some());
+        assert!(set.iter().find(|&lang| lang == "za").is_some());
 
         // Test collect
-        let langs = pattern.lang_set().unwrap().collect::<Vec<_>>();
+        let langs = set.iter().collect::<Vec<_>>();
         assert!(langs.iter().find(|&&l| l == "ie").is_some());
+
+        let cstrs = set.iter().cstrs().collect::<Vec<_>>();
+        assert_eq!(cstrs.len(), langs.len());
+        assert!(cstrs.contains(&c"ie"));
+        assert!(set.iter().lossy().eq(langs.iter().copied()));
     }
 }
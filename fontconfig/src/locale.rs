@@ -0,0 +1,116 @@
+//! Family names in the user's language.
+
+use std::env;
+
+use crate::{list_fonts, Fontconfig, ObjectSet, Pattern};
+use crate::{FC_FAMILY, FC_FAMILYLANG};
+
+/// Normalize a POSIX locale such as `ja_JP.UTF-8` to the form Fontconfig uses for languages,
+/// such as `ja-jp`.
+fn normalize_locale(locale: &str) -> Option<String> {
+    let lang = locale.split(['.', '@']).next()?;
+    match lang {
+        "" | "C" | "POSIX" => None,
+        lang => Some(lang.replace('_', "-").to_ascii_lowercase()),
+    }
+}
+
+/// The language of the current locale, from the `LC_ALL`, `LC_MESSAGES` and `LANG` environment
+/// variables.
+fn current_lang() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|locale| normalize_locale(&locale))
+}
+
+impl Pattern<'_> {
+    /// Get the family name of this font in the language `lang`, e.g. `ja` or `zh-tw`.
+    ///
+    /// Fonts may give their family name in several languages, which Fontconfig lists in the
+    /// "familylang" element alongside the "family" element. A name in exactly `lang` is
+    /// preferred, then one in the same language for another territory, then the English name.
+    /// Falls back to the first family name.
+    pub fn localized_family(&self, lang: &str) -> Option<&str> {
+        let lang = lang.to_ascii_lowercase();
+        let primary = lang.split('-').next().unwrap_or(&lang);
+        let names: Vec<(&str, Option<&str>)> = (0..)
+            .map_while(|n| {
+                let family = self.get_string_at(FC_FAMILY, n)?;
+                Some((family, self.get_string_at(FC_FAMILYLANG, n)))
+            })
+            .collect();
+
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            names
+                .iter()
+                .find(|(_, family_lang)| {
+                    family_lang.is_some_and(|l| matches(&l.to_ascii_lowercase()))
+                })
+                .map(|&(family, _)| family)
+        };
+        find(&|l| l == lang)
+            .or_else(|| find(&|l| l.split('-').next() == Some(primary)))
+            .or_else(|| find(&|l| l == "en"))
+            .or_else(|| names.first().map(|&(family, _)| family))
+    }
+}
+
+impl Fontconfig {
+    /// List the installed families by their name in the language of the current locale.
+    ///
+    /// The locale is taken from the `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables,
+    /// so a font picker in a Japanese locale shows the native names of Japanese fonts. See
+    /// [`Fontconfig::localized_families_for`].
+    pub fn localized_families(&self) -> Vec<String> {
+        self.localized_families_for(current_lang().as_deref().unwrap_or("en"))
+    }
+
+    /// List the installed families by their name in `lang`, falling back to English.
+    ///
+    /// The names are sorted and without duplicates. See [`Pattern::localized_family`] for how
+    /// names are chosen.
+    pub fn localized_families_for(&self, lang: &str) -> Vec<String> {
+        let mut objects = ObjectSet::new(self);
+        objects.add(FC_FAMILY);
+        objects.add(FC_FAMILYLANG);
+
+        let fonts = list_fonts(&Pattern::new(self), Some(&objects));
+        let mut families: Vec<String> = fonts
+            .iter()
+            .filter_map(|font| font.localized_family(lang).map(str::to_owned))
+            .collect();
+        families.sort();
+        families.dedup();
+        families
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_locale("ja_JP.UTF-8").as_deref(), Some("ja-jp"));
+        assert_eq!(normalize_locale("de_DE@euro").as_deref(), Some("de-de"));
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+    }
+
+    #[test]
+    fn pick_localized_name() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        for (family, lang) in [("Example", "en"), ("例", "ja"), ("Beispiel", "de-ch")] {
+            pattern.add_string(FC_FAMILY, family);
+            pattern.add_string(FC_FAMILYLANG, lang);
+        }
+        assert_eq!(pattern.localized_family("ja-JP"), Some("例"));
+        assert_eq!(pattern.localized_family("de"), Some("Beispiel"));
+        assert_eq!(pattern.localized_family("fr"), Some("Example"));
+
+        let families = fc.localized_families_for("en");
+        assert!(families.iter().any(|family| family == "DejaVu Sans"));
+    }
+}
@@ -0,0 +1,99 @@
+//! Memory-mapped access to matched font files, using the `memmap2` crate.
+
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::data::check_face_index;
+use crate::{Font, Pattern};
+
+/// A memory-mapped font file.
+///
+/// The file stays mapped for as long as this value lives, and it dereferences to the contents
+/// of the file, so it can be handed to parsers and shapers without copying the font. As with any
+/// memory map, the contents may change if the file is modified while it is mapped.
+pub struct MappedFont {
+    data: Mmap,
+    index: i32,
+}
+
+impl MappedFont {
+    fn open(path: &Path, index: i32) -> io::Result<MappedFont> {
+        let file = File::open(path)?;
+        let data = unsafe { Mmap::map(&file)? };
+        check_face_index(&data, index)?;
+        Ok(MappedFont { data, index })
+    }
+
+    /// The contents of the font file.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The index of the font within the file, as held by the "index" element.
+    ///
+    /// The lower 16 bits select the face of a collection and the upper 16 bits the named
+    /// instance of a variable font.
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+}
+
+impl Deref for MappedFont {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl AsRef<[u8]> for MappedFont {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Font {
+    /// Memory-map the file containing this font.
+    ///
+    /// Returns an error of kind `InvalidData` if the file has no face at the index of this font.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let font = fc.find("sans-serif", None).unwrap();
+    /// let mapped = font.map().unwrap();
+    /// println!("{} bytes", mapped.len());
+    /// ```
+    pub fn map(&self) -> io::Result<MappedFont> {
+        MappedFont::open(&self.path, self.index.unwrap_or(0))
+    }
+}
+
+impl Pattern<'_> {
+    /// Memory-map the file of this font pattern.
+    ///
+    /// Returns an error of kind `NotFound` if the pattern has no file, and of kind
+    /// `InvalidData` if the file has no face at the index of the pattern.
+    pub fn map_file(&self) -> io::Result<MappedFont> {
+        MappedFont::open(&self.existing_file_path()?, self.face_index().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Fontconfig;
+
+    #[test]
+    fn map_matched_font() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let mapped = font.map().unwrap();
+        assert_eq!(&*mapped, font.data().unwrap());
+        assert_eq!(mapped.index(), 0);
+    }
+}
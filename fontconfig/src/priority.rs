@@ -0,0 +1,292 @@
+//! Choosing between fonts bundled with an application and installed fonts of the same family.
+
+use std::ffi::CString;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::filter::same_name;
+use crate::sandbox::escape;
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    ffi_dispatch, ffi_dispatch_optional, font_set_patterns, path_to_cstring, sys, Error, FcTrue,
+    FontSet, Fontconfig, Pattern, Result, FC_FAMILY,
+};
+
+/// Which font wins when an application font and an installed font have the same family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AppFontPriority {
+    /// The family comes from the application fonts: installed fonts of the family are hidden.
+    Prefer,
+    /// The family comes from the installed fonts: application fonts of the family are hidden.
+    Yield,
+}
+
+impl Fontconfig {
+    /// Add the fonts in `dir` and its subdirectories as application fonts, deciding whether
+    /// they replace installed fonts of the same family or give way to them.
+    ///
+    /// Fontconfig would otherwise mix the two, picking whichever font matches best style by
+    /// style and the installed font on a tie, so an application shipping a pinned version of a
+    /// family could render with a different version depending on the system. The choice is
+    /// made for whole families, as rules added to the configuration of this handle, so it
+    /// holds for every match, sort and listing through it. A handle sharing the current
+    /// configuration is given one of its own first, as described under
+    /// [Changing the configuration](Fontconfig#changing-the-configuration), so the rules do not
+    /// apply to other code in the process.
+    ///
+    /// Preferring the application fonts rescans the installed fonts so that the rules apply to
+    /// them. Returns [`Error::ConfigFailed`] if `dir` cannot be scanned, if the path of a font
+    /// that needs a rule is not valid UTF-8, which the rules cannot name, or if the loaded
+    /// Fontconfig is too old to add the rules.
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use fontconfig::{AppFontPriority, Fontconfig};
+    ///
+    /// let mut fc = Fontconfig::new().unwrap();
+    /// fc.add_app_fonts("/opt/myapp/fonts", AppFontPriority::Prefer).unwrap();
+    /// ```
+    pub fn add_app_fonts(
+        &mut self,
+        dir: impl AsRef<Path>,
+        priority: AppFontPriority,
+    ) -> Result<()> {
+        if !sys::available::FcConfigParseAndLoadFromMemory() {
+            return Err(Error::ConfigFailed);
+        }
+        let dir = dir.as_ref();
+        let c_dir = path_to_cstring(dir).ok_or(Error::ConfigFailed)?;
+        let config = self.private_config()?;
+        let installed = unsafe { installed_families(self, config) };
+        let app_fonts = app_fonts(self, dir);
+        let is_installed = |family: &str| installed.iter().any(|f| same_name(f, family));
+
+        let mut rules = String::new();
+        let mut rescan = false;
+        for (file, families) in &app_fonts {
+            let conflicts: Vec<_> = families.iter().filter(|f| is_installed(f)).collect();
+            if conflicts.is_empty() {
+                continue;
+            }
+            let file = file.to_str().ok_or(Error::ConfigFailed)?;
+            match priority {
+                AppFontPriority::Prefer => {
+                    rescan = true;
+                    // Keep the application font, which the families would also reject.
+                    write!(rules, "<acceptfont>{}</acceptfont>", pattern("file", file)).unwrap();
+                    for family in conflicts {
+                        write!(
+                            rules,
+                            "<rejectfont>{}</rejectfont>",
+                            pattern("family", family)
+                        )
+                        .unwrap();
+                    }
+                }
+                AppFontPriority::Yield => {
+                    write!(rules, "<rejectfont>{}</rejectfont>", pattern("file", file)).unwrap();
+                }
+            }
+        }
+
+        unsafe {
+            if !rules.is_empty() {
+                let xml = format!(
+                    "<fontconfig><selectfont>{}</selectfont></fontconfig>",
+                    rules
+                );
+                let xml = CString::new(xml).map_err(|_| Error::ConfigFailed)?;
+                let loaded = ffi_dispatch_optional!(
+                    FcConfigParseAndLoadFromMemory,
+                    config,
+                    xml.as_ptr() as *const u8,
+                    FcTrue
+                );
+                if loaded != FcTrue {
+                    return Err(Error::ConfigFailed);
+                }
+            }
+            if ffi_dispatch!(
+                LIB,
+                FcConfigAppFontAddDir,
+                config,
+                c_dir.as_ptr() as *const u8
+            ) != FcTrue
+                || rescan && ffi_dispatch!(LIB, FcConfigBuildFonts, config) != FcTrue
+            {
+                return Err(Error::ConfigFailed);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns every family name of the installed fonts.
+unsafe fn installed_families(fc: &Fontconfig, config: *mut sys::FcConfig) -> Vec<String> {
+    let set = ffi_dispatch!(LIB, FcConfigGetFonts, config, sys::FcSetSystem);
+    if set.is_null() {
+        return Vec::new();
+    }
+    let mut families = Vec::new();
+    for &pat in font_set_patterns(set) {
+        families.extend(families_of(&Pattern::from_pattern(fc, pat)));
+    }
+    families
+}
+
+/// Returns the files of fonts in `dir` and its subdirectories, with the families of each.
+///
+/// Symbolic links to directories are not followed, as they could form a loop.
+fn app_fonts(fc: &Fontconfig, dir: &Path) -> Vec<(PathBuf, Vec<String>)> {
+    let mut fonts = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return fonts;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            fonts.extend(app_fonts(fc, &path));
+        } else if file_type.is_file() || path.is_file() {
+            let families: Vec<_> = FontSet::from_file(fc, &path)
+                .iter()
+                .flat_map(|font| families_of(&font))
+                .collect();
+            if !families.is_empty() {
+                fonts.push((path, families));
+            }
+        }
+    }
+    fonts
+}
+
+fn families_of(font: &Pattern) -> Vec<String> {
+    (0..)
+        .map_while(|n| font.get_string_at(FC_FAMILY, n))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A `<pattern>` element matching fonts whose element `name` has the string `value`.
+fn pattern(name: &str, value: &str) -> String {
+    format!(
+        "<pattern><patelt name=\"{}\"><string>{}</string></patelt></pattern>",
+        name,
+        escape(value)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dejavu_dir, dejavu_file};
+    use crate::{HostFontDir, SandboxFonts};
+    use std::env;
+
+    fn installed() -> Fontconfig {
+        SandboxFonts {
+            host_dirs: vec![HostFontDir {
+                path: dejavu_dir(),
+                host_path: None,
+            }],
+            cache_dir: Some(env::temp_dir().join("fontconfig-rs-priority-test")),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        }
+        .build()
+        .unwrap()
+    }
+
+    fn matched_file(fc: &Fontconfig, family: &str) -> PathBuf {
+        let mut pat = Pattern::new(fc);
+        pat.add(FC_FAMILY, family);
+        PathBuf::from(pat.font_match().filename().unwrap())
+    }
+
+    #[test]
+    fn app_font_priority() {
+        let dir = env::temp_dir().join(format!("fontconfig-rs-app-fonts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(dejavu_file("DejaVu Serif"), dir.join("PinnedSerif.ttf")).unwrap();
+
+        let mut fc = installed();
+        fc.add_app_fonts(&dir, AppFontPriority::Prefer).unwrap();
+        assert_eq!(
+            matched_file(&fc, "DejaVu Serif"),
+            dir.join("PinnedSerif.ttf")
+        );
+        // The whole family is replaced, not only the style the application ships.
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "DejaVu Serif");
+        pat.add(crate::FC_WEIGHT, crate::FC_WEIGHT_BOLD);
+        assert!(pat
+            .font_match()
+            .filename()
+            .unwrap()
+            .starts_with(dir.to_str().unwrap()));
+        assert!(matched_file(&fc, "DejaVu Sans").starts_with(dejavu_dir()));
+
+        let mut fc = installed();
+        fc.add_app_fonts(&dir, AppFontPriority::Yield).unwrap();
+        assert!(matched_file(&fc, "DejaVu Serif").starts_with(dejavu_dir()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loop() {
+        let dir = env::temp_dir().join(format!(
+            "fontconfig-rs-app-fonts-{}-loop",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::copy(dejavu_file("DejaVu Serif"), dir.join("sub/PinnedSerif.ttf")).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("sub/PinnedSerif.ttf"), dir.join("Linked.ttf"))
+            .unwrap();
+
+        let fc = installed();
+        let mut files: Vec<_> = app_fonts(&fc, &dir)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [dir.join("Linked.ttf"), dir.join("sub/PinnedSerif.ttf")]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_app_font() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = env::temp_dir().join(format!(
+            "fontconfig-rs-app-fonts-{}-bytes",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(
+            dejavu_file("DejaVu Serif"),
+            dir.join(OsStr::from_bytes(b"Pinned\xffSerif.ttf")),
+        )
+        .unwrap();
+
+        // The font conflicts with an installed family, but no rule can name its file.
+        let mut fc = installed();
+        assert!(fc.add_app_fonts(&dir, AppFontPriority::Yield).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+//! An enumeration of the pattern elements Fontconfig knows about.
+
+use std::ffi::CStr;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    FC_ANTIALIAS, FC_ASPECT, FC_AUTOHINT, FC_CAPABILITY, FC_CHARSET, FC_CHAR_HEIGHT, FC_CHAR_WIDTH,
+    FC_COLOR, FC_DECORATIVE, FC_DPI, FC_EMBEDDED_BITMAP, FC_EMBOLDEN, FC_FAMILY, FC_FAMILYLANG,
+    FC_FILE, FC_FONTFORMAT, FC_FONTVERSION, FC_FONT_FEATURES, FC_FONT_HAS_HINT, FC_FONT_VARIATIONS,
+    FC_FOUNDRY, FC_FT_FACE, FC_FULLNAME, FC_FULLNAMELANG, FC_GLOBAL_ADVANCE, FC_HASH, FC_HINTING,
+    FC_HINT_STYLE, FC_INDEX, FC_LANG, FC_LCD_FILTER, FC_MATRIX, FC_MINSPACE, FC_NAMED_INSTANCE,
+    FC_NAMELANG, FC_ORDER, FC_OUTLINE, FC_PIXEL_SIZE, FC_POSTSCRIPT_NAME, FC_PRGNAME,
+    FC_RASTERIZER, FC_RGBA, FC_SCALABLE, FC_SCALE, FC_SIZE, FC_SLANT, FC_SOURCE, FC_SPACING,
+    FC_STYLE, FC_STYLELANG, FC_SYMBOL, FC_VARIABLE, FC_VERTICAL_LAYOUT, FC_WEIGHT, FC_WIDTH,
+};
+
+/// A pattern element (or "object") known to Fontconfig, such as "family" or "weight".
+///
+/// This crate mostly names elements with `&CStr` constants such as [`FC_FAMILY`]. This enum
+/// covers the same elements so that tools such as query builders and format-string editors can
+/// list them, check names given at runtime and find out the type of their values. Elements
+/// added by later Fontconfig releases will be added to it, so it is non-exhaustive.
+///
+/// ```
+/// use fontconfig::{FontProperty, ValueType};
+///
+/// let property: FontProperty = "pixelsize".parse().unwrap();
+/// assert_eq!(property, FontProperty::PixelSize);
+/// assert_eq!(property.value_type(), ValueType::Double);
+/// assert!("pixel-size".parse::<FontProperty>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum FontProperty {
+    Family,
+    FamilyLang,
+    Style,
+    StyleLang,
+    FullName,
+    FullNameLang,
+    Slant,
+    Weight,
+    Width,
+    Size,
+    Aspect,
+    PixelSize,
+    Spacing,
+    Foundry,
+    Antialias,
+    HintStyle,
+    Hinting,
+    VerticalLayout,
+    Autohint,
+    GlobalAdvance,
+    File,
+    Index,
+    Rasterizer,
+    Outline,
+    Scalable,
+    Dpi,
+    Rgba,
+    Scale,
+    Minspace,
+    CharWidth,
+    CharHeight,
+    Matrix,
+    CharSet,
+    Lang,
+    FontVersion,
+    Capability,
+    FontFormat,
+    Embolden,
+    EmbeddedBitmap,
+    Decorative,
+    LcdFilter,
+    NameLang,
+    FontFeatures,
+    PrgName,
+    Hash,
+    PostscriptName,
+    Color,
+    Symbol,
+    FontVariations,
+    Variable,
+    FontHasHint,
+    Order,
+    NamedInstance,
+    FtFace,
+    Source,
+}
+
+/// The type of the values of a pattern element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// A string.
+    String,
+    /// An integer, often one of a set of constants such as `FC_SLANT_ITALIC`.
+    Integer,
+    /// A floating-point number.
+    Double,
+    /// A number, or a range of numbers for a variable font.
+    Range,
+    /// A boolean.
+    Bool,
+    /// A 2x2 transformation matrix.
+    Matrix,
+    /// A set of characters.
+    CharSet,
+    /// A set of languages.
+    LangSet,
+    /// A FreeType face.
+    FtFace,
+}
+
+/// Error type returned when parsing a `FontProperty`.
+///
+/// The error holds the name that could not be parsed.
+#[derive(Debug)]
+pub struct UnknownProperty(pub String);
+
+impl FontProperty {
+    /// Every property, in the order Fontconfig numbers them.
+    pub const ALL: [FontProperty; 55] = [
+        FontProperty::Family,
+        FontProperty::FamilyLang,
+        FontProperty::Style,
+        FontProperty::StyleLang,
+        FontProperty::FullName,
+        FontProperty::FullNameLang,
+        FontProperty::Slant,
+        FontProperty::Weight,
+        FontProperty::Width,
+        FontProperty::Size,
+        FontProperty::Aspect,
+        FontProperty::PixelSize,
+        FontProperty::Spacing,
+        FontProperty::Foundry,
+        FontProperty::Antialias,
+        FontProperty::HintStyle,
+        FontProperty::Hinting,
+        FontProperty::VerticalLayout,
+        FontProperty::Autohint,
+        FontProperty::GlobalAdvance,
+        FontProperty::File,
+        FontProperty::Index,
+        FontProperty::Rasterizer,
+        FontProperty::Outline,
+        FontProperty::Scalable,
+        FontProperty::Dpi,
+        FontProperty::Rgba,
+        FontProperty::Scale,
+        FontProperty::Minspace,
+        FontProperty::CharWidth,
+        FontProperty::CharHeight,
+        FontProperty::Matrix,
+        FontProperty::CharSet,
+        FontProperty::Lang,
+        FontProperty::FontVersion,
+        FontProperty::Capability,
+        FontProperty::FontFormat,
+        FontProperty::Embolden,
+        FontProperty::EmbeddedBitmap,
+        FontProperty::Decorative,
+        FontProperty::LcdFilter,
+        FontProperty::NameLang,
+        FontProperty::FontFeatures,
+        FontProperty::PrgName,
+        FontProperty::Hash,
+        FontProperty::PostscriptName,
+        FontProperty::Color,
+        FontProperty::Symbol,
+        FontProperty::FontVariations,
+        FontProperty::Variable,
+        FontProperty::FontHasHint,
+        FontProperty::Order,
+        FontProperty::NamedInstance,
+        FontProperty::FtFace,
+        FontProperty::Source,
+    ];
+
+    /// The name of the element, e.g. "pixelsize", as used in patterns and configuration files.
+    pub fn as_cstr(self) -> &'static CStr {
+        match self {
+            FontProperty::Family => FC_FAMILY,
+            FontProperty::FamilyLang => FC_FAMILYLANG,
+            FontProperty::Style => FC_STYLE,
+            FontProperty::StyleLang => FC_STYLELANG,
+            FontProperty::FullName => FC_FULLNAME,
+            FontProperty::FullNameLang => FC_FULLNAMELANG,
+            FontProperty::Slant => FC_SLANT,
+            FontProperty::Weight => FC_WEIGHT,
+            FontProperty::Width => FC_WIDTH,
+            FontProperty::Size => FC_SIZE,
+            FontProperty::Aspect => FC_ASPECT,
+            FontProperty::PixelSize => FC_PIXEL_SIZE,
+            FontProperty::Spacing => FC_SPACING,
+            FontProperty::Foundry => FC_FOUNDRY,
+            FontProperty::Antialias => FC_ANTIALIAS,
+            FontProperty::HintStyle => FC_HINT_STYLE,
+            FontProperty::Hinting => FC_HINTING,
+            FontProperty::VerticalLayout => FC_VERTICAL_LAYOUT,
+            FontProperty::Autohint => FC_AUTOHINT,
+            FontProperty::GlobalAdvance => FC_GLOBAL_ADVANCE,
+            FontProperty::File => FC_FILE,
+            FontProperty::Index => FC_INDEX,
+            FontProperty::Rasterizer => FC_RASTERIZER,
+            FontProperty::Outline => FC_OUTLINE,
+            FontProperty::Scalable => FC_SCALABLE,
+            FontProperty::Dpi => FC_DPI,
+            FontProperty::Rgba => FC_RGBA,
+            FontProperty::Scale => FC_SCALE,
+            FontProperty::Minspace => FC_MINSPACE,
+            FontProperty::CharWidth => FC_CHAR_WIDTH,
+            FontProperty::CharHeight => FC_CHAR_HEIGHT,
+            FontProperty::Matrix => FC_MATRIX,
+            FontProperty::CharSet => FC_CHARSET,
+            FontProperty::Lang => FC_LANG,
+            FontProperty::FontVersion => FC_FONTVERSION,
+            FontProperty::Capability => FC_CAPABILITY,
+            FontProperty::FontFormat => FC_FONTFORMAT,
+            FontProperty::Embolden => FC_EMBOLDEN,
+            FontProperty::EmbeddedBitmap => FC_EMBEDDED_BITMAP,
+            FontProperty::Decorative => FC_DECORATIVE,
+            FontProperty::LcdFilter => FC_LCD_FILTER,
+            FontProperty::NameLang => FC_NAMELANG,
+            FontProperty::FontFeatures => FC_FONT_FEATURES,
+            FontProperty::PrgName => FC_PRGNAME,
+            FontProperty::Hash => FC_HASH,
+            FontProperty::PostscriptName => FC_POSTSCRIPT_NAME,
+            FontProperty::Color => FC_COLOR,
+            FontProperty::Symbol => FC_SYMBOL,
+            FontProperty::FontVariations => FC_FONT_VARIATIONS,
+            FontProperty::Variable => FC_VARIABLE,
+            FontProperty::FontHasHint => FC_FONT_HAS_HINT,
+            FontProperty::Order => FC_ORDER,
+            FontProperty::NamedInstance => FC_NAMED_INSTANCE,
+            FontProperty::FtFace => FC_FT_FACE,
+            FontProperty::Source => FC_SOURCE,
+        }
+    }
+
+    /// The name of the element as a `str`.
+    pub fn as_str(self) -> &'static str {
+        // The names are all ASCII.
+        self.as_cstr().to_str().unwrap()
+    }
+
+    /// The type of the values of the element.
+    pub fn value_type(self) -> ValueType {
+        match self {
+            FontProperty::Family => ValueType::String,
+            FontProperty::FamilyLang => ValueType::String,
+            FontProperty::Style => ValueType::String,
+            FontProperty::StyleLang => ValueType::String,
+            FontProperty::FullName => ValueType::String,
+            FontProperty::FullNameLang => ValueType::String,
+            FontProperty::Slant => ValueType::Integer,
+            FontProperty::Weight => ValueType::Range,
+            FontProperty::Width => ValueType::Range,
+            FontProperty::Size => ValueType::Range,
+            FontProperty::Aspect => ValueType::Double,
+            FontProperty::PixelSize => ValueType::Double,
+            FontProperty::Spacing => ValueType::Integer,
+            FontProperty::Foundry => ValueType::String,
+            FontProperty::Antialias => ValueType::Bool,
+            FontProperty::HintStyle => ValueType::Integer,
+            FontProperty::Hinting => ValueType::Bool,
+            FontProperty::VerticalLayout => ValueType::Bool,
+            FontProperty::Autohint => ValueType::Bool,
+            FontProperty::GlobalAdvance => ValueType::Bool,
+            FontProperty::File => ValueType::String,
+            FontProperty::Index => ValueType::Integer,
+            FontProperty::Rasterizer => ValueType::String,
+            FontProperty::Outline => ValueType::Bool,
+            FontProperty::Scalable => ValueType::Bool,
+            FontProperty::Dpi => ValueType::Double,
+            FontProperty::Rgba => ValueType::Integer,
+            FontProperty::Scale => ValueType::Double,
+            FontProperty::Minspace => ValueType::Bool,
+            FontProperty::CharWidth => ValueType::Integer,
+            FontProperty::CharHeight => ValueType::Integer,
+            FontProperty::Matrix => ValueType::Matrix,
+            FontProperty::CharSet => ValueType::CharSet,
+            FontProperty::Lang => ValueType::LangSet,
+            FontProperty::FontVersion => ValueType::Integer,
+            FontProperty::Capability => ValueType::String,
+            FontProperty::FontFormat => ValueType::String,
+            FontProperty::Embolden => ValueType::Bool,
+            FontProperty::EmbeddedBitmap => ValueType::Bool,
+            FontProperty::Decorative => ValueType::Bool,
+            FontProperty::LcdFilter => ValueType::Integer,
+            FontProperty::NameLang => ValueType::String,
+            FontProperty::FontFeatures => ValueType::String,
+            FontProperty::PrgName => ValueType::String,
+            FontProperty::Hash => ValueType::String,
+            FontProperty::PostscriptName => ValueType::String,
+            FontProperty::Color => ValueType::Bool,
+            FontProperty::Symbol => ValueType::Bool,
+            FontProperty::FontVariations => ValueType::String,
+            FontProperty::Variable => ValueType::Bool,
+            FontProperty::FontHasHint => ValueType::Bool,
+            FontProperty::Order => ValueType::Integer,
+            FontProperty::NamedInstance => ValueType::Bool,
+            FontProperty::FtFace => ValueType::FtFace,
+            FontProperty::Source => ValueType::String,
+        }
+    }
+}
+
+impl FromStr for FontProperty {
+    type Err = UnknownProperty;
+
+    /// Parse the name of an element, which is case sensitive as in Fontconfig.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FontProperty::ALL
+            .into_iter()
+            .find(|property| property.as_str() == s)
+            .ok_or_else(|| UnknownProperty(s.to_string()))
+    }
+}
+
+impl fmt::Display for FontProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names() {
+        for property in FontProperty::ALL {
+            assert_eq!(property.as_str().parse::<FontProperty>().unwrap(), property);
+        }
+        assert_eq!(FontProperty::Family.as_cstr(), FC_FAMILY);
+        assert_eq!(FontProperty::Lang.value_type(), ValueType::LangSet);
+        assert_eq!(FontProperty::PostscriptName.to_string(), "postscriptname");
+        assert!("Family".parse::<FontProperty>().is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! A typed description of the fonts to find.
+
+use crate::Spacing;
+use crate::{list_fonts, sort_fonts, CharSet, Font, FontFilter, Fontconfig, ObjectSet, Pattern};
+use crate::{
+    FC_CHARSET, FC_COLOR, FC_FAMILY, FC_FILE, FC_FONTFORMAT, FC_FONTVERSION, FC_FOUNDRY,
+    FC_FULLNAME, FC_INDEX, FC_LANG, FC_SCALABLE, FC_SIZE, FC_SLANT, FC_SPACING, FC_STYLE,
+    FC_WEIGHT, FC_WIDTH,
+};
+
+/// A query for fonts, built from typed fields rather than pattern elements.
+///
+/// Fields left empty or `None` do not constrain the result. Weights, slants and widths use
+/// Fontconfig's scales, e.g. `FC_WEIGHT_BOLD` and `FC_SLANT_ITALIC`.
+///
+/// ```
+/// use fontconfig::{FontQuery, Fontconfig, FC_WEIGHT_BOLD};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let query = FontQuery {
+///     families: vec!["DejaVu Sans".into(), "sans-serif".into()],
+///     weight: Some(FC_WEIGHT_BOLD),
+///     languages: vec!["fr".into()],
+///     ..FontQuery::default()
+/// };
+/// if let Some(font) = query.matches(&fc).first() {
+///     println!("{}: {}", font.name, font.path.display());
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontQuery {
+    /// The families to look for, in order of preference.
+    pub families: Vec<String>,
+    /// Families to fall back to after `families`, added with weak binding like the aliases of
+    /// configuration files, so that they rank below the languages of the query when matching.
+    pub fallback_families: Vec<String>,
+    /// The style name, e.g. "Bold Italic".
+    pub style: Option<String>,
+    /// The weight, e.g. `FC_WEIGHT_BOLD`.
+    pub weight: Option<i32>,
+    /// The slant, e.g. `FC_SLANT_ITALIC`.
+    pub slant: Option<i32>,
+    /// The width, e.g. `FC_WIDTH_CONDENSED`.
+    pub width: Option<i32>,
+    /// The size in points.
+    pub size: Option<f64>,
+    /// The languages the font must support, e.g. "fr" or "zh-tw".
+    pub languages: Vec<String>,
+    /// The characters the font must cover.
+    pub charset: Option<CharSet>,
+    /// The spacing of the font.
+    pub spacing: Option<Spacing>,
+    /// Whether the font must be scalable rather than a bitmap font.
+    pub scalable: Option<bool>,
+    /// Whether the font must have color glyphs.
+    pub color: Option<bool>,
+    /// Rank scalable fonts above bitmap fonts when matching, without ruling bitmap fonts out.
+    ///
+    /// Some distributions ship legacy bitmap fonts that otherwise win matches for common
+    /// families and render poorly at high resolutions. This has no effect on listing.
+    pub prefer_scalable: bool,
+    /// Leave bitmap fonts out of the results of both matching and listing.
+    pub exclude_bitmaps: bool,
+    /// Families, foundries and files to hide from the results of both matching and listing.
+    pub filter: FontFilter,
+}
+
+impl FontQuery {
+    /// Build the pattern this query describes.
+    pub fn to_pattern<'fc>(&self, fc: &'fc Fontconfig) -> Pattern<'fc> {
+        let mut pat = Pattern::new(fc);
+        for family in &self.families {
+            pat.add(FC_FAMILY, family);
+        }
+        for family in &self.fallback_families {
+            pat.add_weak(FC_FAMILY, family);
+        }
+        if let Some(style) = &self.style {
+            pat.add(FC_STYLE, style);
+        }
+        if let Some(weight) = self.weight {
+            pat.add(FC_WEIGHT, weight);
+        }
+        if let Some(slant) = self.slant {
+            pat.add(FC_SLANT, slant);
+        }
+        if let Some(width) = self.width {
+            pat.add(FC_WIDTH, width);
+        }
+        if let Some(size) = self.size {
+            pat.add(FC_SIZE, size);
+        }
+        for lang in &self.languages {
+            pat.add(FC_LANG, lang);
+        }
+        if let Some(charset) = &self.charset {
+            pat.add(FC_CHARSET, charset);
+        }
+        if let Some(spacing) = self.spacing {
+            pat.add(FC_SPACING, spacing);
+        }
+        if let Some(scalable) = self.scalable {
+            pat.add(FC_SCALABLE, scalable);
+        }
+        if let Some(color) = self.color {
+            pat.add(FC_COLOR, color);
+        }
+        pat
+    }
+
+    /// Find the installed fonts, sorted from the closest match to this query to the furthest.
+    ///
+    /// The configuration is applied to the query first, as for [`Pattern::font_match`], so
+    /// aliases such as "sans-serif" are resolved. The first font is the one `font_match`
+    /// would choose.
+    pub fn matches(&self, fc: &Fontconfig) -> Vec<Font> {
+        let mut pat = self.to_pattern(fc);
+        if self.prefer_scalable && self.scalable.is_none() {
+            pat.add(FC_SCALABLE, true);
+        }
+        pat.substitute();
+        sort_fonts(&pat, false)
+            .iter()
+            .filter(|pattern| self.accepts(pattern))
+            .filter_map(|pattern| Font::from_pattern(&pattern))
+            .collect()
+    }
+
+    /// List the installed fonts that have every property in this query, in no particular order.
+    ///
+    /// Unlike [`FontQuery::matches`], no configuration is applied and only fonts matching
+    /// exactly are returned, so a family must be given by its real name.
+    pub fn list(&self, fc: &Fontconfig) -> Vec<Font> {
+        let mut objects = ObjectSet::new(fc);
+        for object in [
+            FC_FULLNAME,
+            FC_FAMILY,
+            FC_FOUNDRY,
+            FC_FILE,
+            FC_INDEX,
+            FC_SCALABLE,
+            FC_FONTFORMAT,
+            FC_FONTVERSION,
+        ] {
+            objects.add(object);
+        }
+        list_fonts(&self.to_pattern(fc), Some(&objects))
+            .iter()
+            .filter(|pattern| self.accepts(pattern))
+            .filter_map(|pattern| Font::from_pattern(&pattern))
+            .collect()
+    }
+
+    fn accepts(&self, pattern: &Pattern) -> bool {
+        !(self.exclude_bitmaps && pattern.get_bool(FC_SCALABLE) == Some(false))
+            && self.filter.accepts(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FC_WEIGHT_BOLD;
+
+    #[test]
+    fn match_and_list() {
+        let fc = Fontconfig::new().unwrap();
+        let query = FontQuery {
+            families: vec!["DejaVu Sans".into()],
+            weight: Some(FC_WEIGHT_BOLD),
+            ..FontQuery::default()
+        };
+        let pattern = query.to_pattern(&fc);
+        assert_eq!(pattern.get_string(FC_FAMILY), Some("DejaVu Sans"));
+        assert_eq!(pattern.weight(), Some(FC_WEIGHT_BOLD));
+
+        let matches = query.matches(&fc);
+        assert_eq!(matches[0].name, "DejaVu Sans Bold");
+
+        let listed = query.list(&fc);
+        assert!(!listed.is_empty());
+        assert!(listed.iter().all(|font| font.name.contains("Bold")));
+        assert!(listed.contains(&matches[0]));
+
+        let spacing = FontQuery {
+            spacing: Some(Spacing::Mono),
+            ..FontQuery::default()
+        };
+        assert!(!spacing.list(&fc).is_empty());
+    }
+
+    #[test]
+    fn filter_results() {
+        let fc = Fontconfig::new().unwrap();
+        let mut query = FontQuery {
+            families: vec!["DejaVu Sans".into()],
+            ..FontQuery::default()
+        };
+        let unfiltered = query.matches(&fc);
+        assert_eq!(unfiltered[0].name, "DejaVu Sans");
+
+        query.filter.deny_families.push("DejaVu Sans".into());
+        let filtered = query.matches(&fc);
+        assert!(filtered.len() < unfiltered.len());
+        assert!(filtered.iter().all(|font| font.name != "DejaVu Sans"));
+        assert!(query.list(&fc).is_empty());
+    }
+
+    #[test]
+    fn scalable_policy() {
+        let fc = Fontconfig::new().unwrap();
+        let query = FontQuery {
+            families: vec!["monospace".into()],
+            prefer_scalable: true,
+            exclude_bitmaps: true,
+            ..FontQuery::default()
+        };
+        assert_eq!(query.to_pattern(&fc).get_bool(FC_SCALABLE), None);
+
+        let matches = query.matches(&fc);
+        assert!(!matches.is_empty());
+        for font in matches.iter().take(5) {
+            let pattern = Pattern::from_file(&fc, &font.path, font.index.unwrap_or(0)).unwrap();
+            assert_eq!(pattern.get_bool(FC_SCALABLE), Some(true));
+        }
+    }
+}
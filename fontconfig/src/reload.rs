@@ -0,0 +1,244 @@
+//! Picking up fonts installed or removed while the application runs.
+
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, sys, Error, FcTrue, FontDatabase, FontIndex, Fontconfig, Result};
+
+/// A token identifying the set of fonts known to Fontconfig, which changes whenever the
+/// configuration is reloaded.
+///
+/// Store the generation alongside cached results, such as fonts chosen for each style of a
+/// document, and compare it with [`Fontconfig::generation`] to check cheaply whether the fonts
+/// may have changed since. Generations are only meaningful within one process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Generation(u64);
+
+/// The configuration last seen by `current_generation`.
+///
+/// A reference to it is held so that its address cannot be reused by a later configuration.
+struct Seen {
+    config: *mut sys::FcConfig,
+    generation: u64,
+}
+
+// SAFETY: The configuration is only used to compare addresses and is released with
+// `FcConfigDestroy`, which is safe from any thread.
+unsafe impl Send for Seen {}
+
+static SEEN: Mutex<Seen> = Mutex::new(Seen {
+    config: ptr::null_mut(),
+    generation: 0,
+});
+
+/// The last generation handed out, to the current configuration or to that of a handle.
+static LAST: AtomicU64 = AtomicU64::new(0);
+
+/// Start a new generation, e.g. for a configuration that has just been created or changed.
+pub(crate) fn next_generation() -> Generation {
+    Generation(LAST.fetch_add(1, Ordering::Relaxed) + 1)
+}
+
+/// Returns the generation of the current configuration, starting a new one if the
+/// configuration has been replaced, whether by [`Fontconfig::reload`] or by one of
+/// Fontconfig's automatic rescans.
+pub(crate) fn current_generation() -> Generation {
+    let config = unsafe { ffi_dispatch!(LIB, FcConfigReference, ptr::null_mut()) };
+    let mut seen = SEEN.lock().unwrap_or_else(PoisonError::into_inner);
+    if config == seen.config {
+        unsafe { ffi_dispatch!(LIB, FcConfigDestroy, config) };
+    } else {
+        if !seen.config.is_null() {
+            unsafe { ffi_dispatch!(LIB, FcConfigDestroy, seen.config) };
+        }
+        seen.config = config;
+        seen.generation = next_generation().0;
+    }
+    Generation(seen.generation)
+}
+
+impl Fontconfig {
+    /// Reload the configuration if any configuration file or font directory of this handle has
+    /// changed.
+    ///
+    /// The files are checked straight away, and the current configuration is then brought up to
+    /// date as Fontconfig's automatic rescans do, once its rescan interval has elapsed since the
+    /// last rescan. Returns `true` if the configuration was reloaded, after which matching and
+    /// listing through the handle from [`Fontconfig::global`] see the new set of fonts.
+    ///
+    /// Only the current configuration can be reloaded. A handle from [`Fontconfig::new`] or
+    /// [`Fontconfig::with_cached_config`] keeps using the configuration it was created with: for
+    /// such a handle this returns `false` while that configuration is up to date, and
+    /// [`Error::ConfigOutOfDate`] once it is not. [`Fontconfig::build_fonts`] then picks up the
+    /// changes, as it needs exclusive access to the handle.
+    ///
+    /// Snapshots such as [`FontIndex`] and [`FontDatabase`] are not updated in place; use their
+    /// `refresh` methods to rebuild them when the configuration has been reloaded.
+    ///
+    /// Returns [`Error::ReloadFailed`] if the new configuration could not be loaded, in which
+    /// case the previous one stays in use.
+    pub fn reload(&self) -> Result<bool> {
+        if self.is_up_to_date() {
+            return Ok(false);
+        }
+        if !self.config.is_null() {
+            return Err(Error::ConfigOutOfDate);
+        }
+        let before = current_generation();
+        if unsafe { ffi_dispatch!(LIB, FcInitBringUptoDate,) } != FcTrue {
+            return Err(Error::ReloadFailed);
+        }
+        Ok(current_generation() != before)
+    }
+
+    /// Returns `true` if no configuration file or font directory of this handle has changed
+    /// since its fonts were scanned.
+    ///
+    /// A long-running application can poll this, e.g. when its window regains focus, to
+    /// notice fonts installed while it runs.
+    pub fn is_up_to_date(&self) -> bool {
+        unsafe { ffi_dispatch!(LIB, FcConfigUptoDate, self.raw_config()) == FcTrue }
+    }
+
+    /// Bring the configuration of this handle up to date, as Fontconfig's automatic rescans do.
+    ///
+    /// This is [`Fontconfig::reload`] for callers that do not need to know whether the
+    /// configuration was reloaded, e.g. before each batch of matches. It follows the same rules,
+    /// so a handle with a configuration of its own that is out of date gets
+    /// [`Error::ConfigOutOfDate`], just as [`Fontconfig::is_up_to_date`] reports it as out of date.
+    pub fn bring_up_to_date(&self) -> Result<()> {
+        self.reload().map(|_| ())
+    }
+
+    /// Returns the generation of the configuration of this handle.
+    ///
+    /// For a handle using the current configuration, the generation changes when the
+    /// configuration is reloaded, whether by [`Fontconfig::reload`] or by one of Fontconfig's
+    /// automatic rescans. For a handle with a configuration of its own, it changes whenever the
+    /// handle changes its configuration. Fonts, indexes and databases record the generation they
+    /// were created in.
+    pub fn generation(&self) -> Generation {
+        if self.config.is_null() {
+            current_generation()
+        } else {
+            self.generation
+        }
+    }
+}
+
+impl FontIndex {
+    /// Reload the configuration if it has changed and rebuild this index if the configuration
+    /// has been reloaded since it was built, by this or any other means.
+    ///
+    /// Returns `true` if the index was rebuilt.
+    pub fn refresh(&mut self, fc: &Fontconfig) -> Result<bool> {
+        fc.reload()?;
+        if self.generation == fc.generation() {
+            return Ok(false);
+        }
+        *self = FontIndex::new(fc);
+        Ok(true)
+    }
+}
+
+impl FontDatabase {
+    /// Reload the configuration if it has changed and rebuild this database if the
+    /// configuration has been reloaded since it was built, by this or any other means.
+    ///
+    /// Returns `true` if the database was rebuilt.
+    pub fn refresh(&mut self, fc: &Fontconfig) -> Result<bool> {
+        fc.reload()?;
+        if self.generation == fc.generation() {
+            return Ok(false);
+        }
+        *self = FontDatabase::new(fc);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_when_unchanged() {
+        let fc = Fontconfig::global().unwrap();
+        let mut index = FontIndex::new(fc);
+        let mut db = FontDatabase::new(fc);
+        assert!(fc.is_up_to_date());
+        assert!(!fc.reload().unwrap());
+        fc.bring_up_to_date().unwrap();
+        assert!(!index.refresh(fc).unwrap());
+        assert!(!db.refresh(fc).unwrap());
+
+        // A snapshot built before a reload is rebuilt.
+        let mut stale = index.clone();
+        stale.generation = Generation::default();
+        assert!(stale.refresh(fc).unwrap());
+        assert_eq!(stale.generation, fc.generation());
+        assert_eq!(stale.len(), index.len());
+
+        let font = fc.find("dejavu sans", None).unwrap();
+        assert!(font.is_current(fc));
+        assert_eq!(fc.generation(), fc.generation());
+    }
+
+    #[test]
+    fn reload_own_config() {
+        use std::time::Duration;
+        use std::{env, fs};
+
+        let font = Fontconfig::new()
+            .unwrap()
+            .find("DejaVu Sans", None)
+            .unwrap();
+        let tmp = env::temp_dir().join(format!("fontconfig-rs-reload-{}", std::process::id()));
+        let dir = tmp.join("fonts");
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(&font.path, dir.join("DejaVuSans.ttf")).unwrap();
+        let fc = crate::SandboxFonts {
+            host_dirs: vec![crate::HostFontDir {
+                path: dir.clone(),
+                host_path: None,
+            }],
+            cache_dir: Some(tmp.join("cache")),
+            ignore_system_config: true,
+            ..crate::SandboxFonts::default()
+        }
+        .build()
+        .unwrap();
+        assert!(!fc.reload().unwrap());
+
+        // The font directory changes after the handle last checked it. Fontconfig compares
+        // times in seconds.
+        std::thread::sleep(Duration::from_millis(1100));
+        fs::write(dir.join("README"), "").unwrap();
+        assert!(!fc.is_up_to_date());
+        assert!(matches!(fc.reload(), Err(Error::ConfigOutOfDate)));
+        assert!(matches!(fc.bring_up_to_date(), Err(Error::ConfigOutOfDate)));
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn generation_of_own_config() {
+        let mut fc = Fontconfig::empty().unwrap();
+        let index = FontIndex::new(&fc);
+        assert_eq!(index.generation, fc.generation());
+        assert_ne!(fc.generation(), Fontconfig::new().unwrap().generation());
+
+        let font = Fontconfig::new()
+            .unwrap()
+            .find("DejaVu Sans", None)
+            .unwrap();
+        fc.app_font_add_file(&font.path).unwrap();
+        assert_ne!(index.generation, fc.generation());
+        assert!(!font.is_current(&fc));
+        assert!(fc.find("DejaVu Sans", None).unwrap().is_current(&fc));
+    }
+}
@@ -0,0 +1,295 @@
+//! The settings a rasterizer needs to render a matched font.
+
+use std::ffi::CStr;
+use std::path::PathBuf;
+use std::ptr;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, sys, Fontconfig, Pattern};
+use crate::{
+    FC_ANTIALIAS, FC_AUTOHINT, FC_EMBEDDED_BITMAP, FC_EMBOLDEN, FC_FAMILY, FC_HINTING,
+    FC_HINT_FULL, FC_HINT_MEDIUM, FC_HINT_NONE, FC_HINT_SLIGHT, FC_HINT_STYLE, FC_LCD_DEFAULT,
+    FC_LCD_FILTER, FC_LCD_LEGACY, FC_LCD_LIGHT, FC_LCD_NONE, FC_MATRIX, FC_RGBA, FC_RGBA_BGR,
+    FC_RGBA_NONE, FC_RGBA_RGB, FC_RGBA_VBGR, FC_RGBA_VRGB,
+};
+
+/// A 2x2 transformation matrix, as held by the "matrix" element of a pattern.
+///
+/// A point `(x, y)` is transformed to `(xx * x + xy * y, yx * x + yy * y)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub struct Matrix {
+    pub xx: f64,
+    pub xy: f64,
+    pub yx: f64,
+    pub yy: f64,
+}
+
+impl Matrix {
+    /// The identity matrix.
+    pub const IDENTITY: Matrix = Matrix {
+        xx: 1.0,
+        xy: 0.0,
+        yx: 0.0,
+        yy: 1.0,
+    };
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::IDENTITY
+    }
+}
+
+/// The amount of hinting to apply, from the "hintstyle" element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum HintStyle {
+    None,
+    Slight,
+    Medium,
+    Full,
+}
+
+/// The subpixel order of the display, from the "rgba" element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Rgba {
+    Unknown,
+    Rgb,
+    Bgr,
+    Vrgb,
+    Vbgr,
+    None,
+}
+
+/// The filter applied to subpixel-rendered glyphs, from the "lcdfilter" element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum LcdFilter {
+    None,
+    Default,
+    Light,
+    Legacy,
+}
+
+/// The rendering settings of a matched font, gathered into one struct for setting up a
+/// rasterizer such as FreeType or Skia.
+///
+/// Elements missing from the pattern take the defaults Xft and cairo use: antialiasing,
+/// hinting and embedded bitmaps on, full hinting, autohinting and emboldening off, an unknown
+/// subpixel order, the default LCD filter and the identity matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderSettings {
+    /// Whether glyphs should be antialiased.
+    pub antialias: bool,
+    /// Whether the font's hinting should be used.
+    pub hinting: bool,
+    /// The amount of hinting to apply.
+    pub hint_style: HintStyle,
+    /// Whether the autohinter should be used in place of the font's own hinting.
+    pub autohint: bool,
+    /// The subpixel order of the display.
+    pub rgba: Rgba,
+    /// The filter applied to subpixel-rendered glyphs.
+    pub lcd_filter: LcdFilter,
+    /// Whether bitmaps embedded in scalable fonts should be used.
+    pub embedded_bitmap: bool,
+    /// Whether glyphs should be emboldened synthetically.
+    pub embolden: bool,
+    /// The transformation to apply to glyphs, e.g. to slant them synthetically.
+    pub matrix: Matrix,
+}
+
+impl RenderSettings {
+    /// Gather the rendering settings from a pattern.
+    ///
+    /// The pattern should be a prepared match, such as one returned by
+    /// [`Pattern::font_match`], so that the configuration has been applied to it.
+    pub fn from_pattern(pattern: &Pattern) -> RenderSettings {
+        let hint_style = match pattern.get_int(FC_HINT_STYLE) {
+            Some(FC_HINT_NONE) => HintStyle::None,
+            Some(FC_HINT_SLIGHT) => HintStyle::Slight,
+            Some(FC_HINT_MEDIUM) => HintStyle::Medium,
+            Some(FC_HINT_FULL) | Some(_) | None => HintStyle::Full,
+        };
+        let rgba = match pattern.get_int(FC_RGBA) {
+            Some(FC_RGBA_RGB) => Rgba::Rgb,
+            Some(FC_RGBA_BGR) => Rgba::Bgr,
+            Some(FC_RGBA_VRGB) => Rgba::Vrgb,
+            Some(FC_RGBA_VBGR) => Rgba::Vbgr,
+            Some(FC_RGBA_NONE) => Rgba::None,
+            Some(_) | None => Rgba::Unknown,
+        };
+        let lcd_filter = match pattern.get_int(FC_LCD_FILTER) {
+            Some(FC_LCD_NONE) => LcdFilter::None,
+            Some(FC_LCD_LIGHT) => LcdFilter::Light,
+            Some(FC_LCD_LEGACY) => LcdFilter::Legacy,
+            Some(FC_LCD_DEFAULT) | Some(_) | None => LcdFilter::Default,
+        };
+
+        RenderSettings {
+            antialias: pattern.get_bool(FC_ANTIALIAS).unwrap_or(true),
+            hinting: pattern.get_bool(FC_HINTING).unwrap_or(true),
+            hint_style,
+            autohint: pattern.get_bool(FC_AUTOHINT).unwrap_or(false),
+            rgba,
+            lcd_filter,
+            embedded_bitmap: pattern.get_bool(FC_EMBEDDED_BITMAP).unwrap_or(true),
+            embolden: pattern.get_bool(FC_EMBOLDEN).unwrap_or(false),
+            matrix: pattern.get_matrix(FC_MATRIX).unwrap_or_default(),
+        }
+    }
+}
+
+/// A font resolved for rendering by [`Fontconfig::query_renderer_settings`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RendererFont {
+    /// The location of the font file on the filesystem.
+    pub path: PathBuf,
+    /// The index of the face within the file.
+    pub index: i32,
+    /// The size to render the font at, in device pixels.
+    pub pixel_size: f64,
+    /// The settings to render the font with.
+    pub settings: RenderSettings,
+}
+
+impl Fontconfig {
+    /// Resolve `family` at `size_pt` points on a display of `dpi` to everything needed to
+    /// render it.
+    ///
+    /// This runs the pipeline Xft and other toolkits run: the configuration and default
+    /// substitutions are applied to the request, the best font is matched and the match is
+    /// prepared for rendering. Returns `None` if no font matched or the matched font has no file.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let font = fc.query_renderer_settings("monospace", 11.0, 96.0).unwrap();
+    /// println!(
+    ///     "{} at {}px, antialias: {}",
+    ///     font.path.display(),
+    ///     font.pixel_size,
+    ///     font.settings.antialias
+    /// );
+    /// ```
+    pub fn query_renderer_settings(
+        &self,
+        family: &str,
+        size_pt: f64,
+        dpi: f64,
+    ) -> Option<RendererFont> {
+        let mut pat = Pattern::new(self);
+        pat.add_string(FC_FAMILY, family);
+        let (font, pixel_size) = pat.try_font_match_sized(size_pt, dpi, 1.0).ok()?;
+
+        Some(RendererFont {
+            path: font.file_path()?,
+            index: font.face_index().unwrap_or(0),
+            pixel_size,
+            settings: RenderSettings::from_pattern(&font),
+        })
+    }
+}
+
+impl Pattern<'_> {
+    /// Add a key-value pair of type `Matrix` to this pattern
+    ///
+    /// See useful keys in the [fontconfig reference][1], and [Adding values](Pattern#adding-values)
+    /// for when the value is left out.
+    ///
+    /// [1]: http://www.freedesktop.org/software/fontconfig/fontconfig-devel/x19.html
+    pub fn add_matrix(&mut self, name: &CStr, val: Matrix) {
+        let matrix = sys::FcMatrix {
+            xx: val.xx,
+            xy: val.xy,
+            yx: val.yx,
+            yy: val.yy,
+        };
+        unsafe {
+            ffi_dispatch!(LIB, FcPatternAddMatrix, self.pat, name.as_ptr(), &matrix);
+        }
+    }
+
+    /// Get the matrix value for a key from this pattern.
+    pub fn get_matrix(&self, name: &CStr) -> Option<Matrix> {
+        unsafe {
+            let mut ret: *mut sys::FcMatrix = ptr::null_mut();
+            if ffi_dispatch!(
+                LIB,
+                FcPatternGetMatrix,
+                self.pat,
+                name.as_ptr(),
+                0,
+                &mut ret
+            ) == sys::FcResultMatch
+            {
+                let matrix = *ret;
+                Some(Matrix {
+                    xx: matrix.xx,
+                    xy: matrix.xy,
+                    yx: matrix.yx,
+                    yy: matrix.yy,
+                })
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fontconfig;
+
+    #[test]
+    fn settings_from_pattern() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        assert_eq!(
+            RenderSettings::from_pattern(&pattern),
+            RenderSettings {
+                antialias: true,
+                hinting: true,
+                hint_style: HintStyle::Full,
+                autohint: false,
+                rgba: Rgba::Unknown,
+                lcd_filter: LcdFilter::Default,
+                embedded_bitmap: true,
+                embolden: false,
+                matrix: Matrix::IDENTITY,
+            }
+        );
+
+        let slant = Matrix {
+            xy: 0.2,
+            ..Matrix::IDENTITY
+        };
+        pattern.add_bool(FC_ANTIALIAS, false);
+        pattern.add_integer(FC_HINT_STYLE, FC_HINT_SLIGHT);
+        pattern.add_integer(FC_RGBA, FC_RGBA_BGR);
+        pattern.add_matrix(FC_MATRIX, slant);
+        let settings = RenderSettings::from_pattern(&pattern);
+        assert!(!settings.antialias);
+        assert_eq!(settings.hint_style, HintStyle::Slight);
+        assert_eq!(settings.rgba, Rgba::Bgr);
+        assert_eq!(settings.matrix, slant);
+    }
+
+    #[test]
+    fn query_settings() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc
+            .query_renderer_settings("DejaVu Sans", 12.0, 96.0)
+            .unwrap();
+        assert!(font.path.ends_with("DejaVuSans.ttf"));
+        assert_eq!(font.index, 0);
+        assert_eq!(font.pixel_size, 16.0);
+    }
+}
@@ -0,0 +1,263 @@
+//! Configurations for applications running in a sandbox such as Flatpak or Snap.
+
+use std::env;
+use std::ffi::CString;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use crate::reload::next_generation;
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+use crate::{ffi_dispatch, ffi_dispatch_optional, sys, Error, FcTrue, Fontconfig, Result};
+#[cfg(feature = "dlopen")]
+use crate::{LIB, LIB_RESULT};
+
+/// The font directories of an application running in a sandbox, from which a configuration
+/// is built.
+///
+/// Sandboxes mount the font directories of the host at other paths, so the caches the host
+/// built for them are only found if Fontconfig is told where they came from, and the
+/// configuration inside the sandbox cannot write to the usual cache directories. Building the
+/// configuration from this description takes care of both, without environment variables or
+/// configuration files.
+///
+/// [`SandboxFonts::detect`] fills this in for Flatpak and Snap:
+///
+/// ```
+/// use fontconfig::{Pattern, SandboxFonts, FC_FAMILY};
+///
+/// let fc = SandboxFonts::detect().build().unwrap();
+/// let mut pat = Pattern::new(&fc);
+/// pat.add(FC_FAMILY, "sans-serif");
+/// println!("{:?}", pat.font_match().filename());
+/// ```
+///
+/// Directories that do not exist are skipped by Fontconfig. Paths must be valid UTF-8, as they
+/// are passed to Fontconfig in an XML configuration.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SandboxFonts {
+    /// Font directories bundled with the application.
+    ///
+    /// Their fonts are matched like any other, so a host font of the same family may be
+    /// chosen over them. Call [`Fontconfig::add_app_fonts`] on the built handle to decide
+    /// which wins.
+    pub app_dirs: Vec<PathBuf>,
+    /// Font directories of the host mounted into the sandbox.
+    pub host_dirs: Vec<HostFontDir>,
+    /// Cache directories of the host mounted into the sandbox, which are only read.
+    pub host_cache_dirs: Vec<PathBuf>,
+    /// A writable directory for the caches of fonts the host has no cache for, such as the
+    /// fonts of the application.
+    pub cache_dir: Option<PathBuf>,
+    /// Leave out the default configuration file of the sandbox, and with it its font
+    /// directories, aliases and rendering settings.
+    pub ignore_system_config: bool,
+}
+
+/// A font directory of the host mounted into a sandbox.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostFontDir {
+    /// The path of the directory in the sandbox, e.g. `/run/host/fonts`.
+    pub path: PathBuf,
+    /// The path of the directory on the host, e.g. `/usr/share/fonts`, which lets the caches of
+    /// the host be used for it.
+    pub host_path: Option<PathBuf>,
+}
+
+impl SandboxFonts {
+    /// Describe the font directories of the sandbox this process runs in.
+    ///
+    /// Under Flatpak these are the host directories mounted under `/run/host` and the fonts in
+    /// `/app/share/fonts`, and under Snap the host directories in `/var/lib/snapd/hostfs` and the
+    /// fonts in `$SNAP/usr/share/fonts`. Outside a sandbox no directories are added. In all
+    /// cases the cache directory is `fontconfig` in the XDG cache directory, which sandboxes
+    /// make private to the application.
+    pub fn detect() -> SandboxFonts {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let mut fonts = SandboxFonts::default();
+        if Path::new("/.flatpak-info").exists() {
+            let user_fonts = home.as_ref().map(|home| home.join(".local/share/fonts"));
+            fonts.add_host_dir("/run/host/fonts", Some("/usr/share/fonts".into()));
+            fonts.add_host_dir(
+                "/run/host/local-fonts",
+                Some("/usr/local/share/fonts".into()),
+            );
+            fonts.add_host_dir("/run/host/user-fonts", user_fonts);
+            fonts.add_host_cache_dir("/run/host/fonts-cache");
+            fonts.add_host_cache_dir("/run/host/user-fonts-cache");
+            fonts.add_app_dir("/app/share/fonts".into());
+        } else if let Some(snap) = env::var_os("SNAP") {
+            let hostfs = Path::new("/var/lib/snapd/hostfs");
+            for dir in ["/usr/share/fonts", "/usr/local/share/fonts"] {
+                fonts.add_host_dir(hostfs.join(&dir[1..]), Some(dir.into()));
+            }
+            fonts.add_host_cache_dir(hostfs.join("var/cache/fontconfig"));
+            fonts.add_app_dir(Path::new(&snap).join("usr/share/fonts"));
+        }
+        fonts.cache_dir = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| Some(home?.join(".cache")))
+            .map(|dir| dir.join("fontconfig"));
+        fonts
+    }
+
+    fn add_host_dir(&mut self, path: impl Into<PathBuf>, host_path: Option<PathBuf>) {
+        let path = path.into();
+        if path.is_dir() {
+            self.host_dirs.push(HostFontDir { path, host_path });
+        }
+    }
+
+    fn add_host_cache_dir(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if path.is_dir() {
+            self.host_cache_dirs.push(path);
+        }
+    }
+
+    fn add_app_dir(&mut self, path: PathBuf) {
+        if path.is_dir() {
+            self.app_dirs.push(path);
+        }
+    }
+
+    /// Build a configuration with these font directories and scan them, returning a handle
+    /// that uses it.
+    ///
+    /// Returns [`Error::ConfigFailed`] if a path is not valid UTF-8, the configuration could not
+    /// be loaded, or the loaded Fontconfig is too old to load a configuration from memory.
+    pub fn build(&self) -> Result<Fontconfig> {
+        #[cfg(feature = "dlopen")]
+        if LIB_RESULT.is_err() {
+            return Err(Error::ConfigFailed);
+        }
+        if !sys::available::FcConfigParseAndLoadFromMemory() {
+            return Err(Error::ConfigFailed);
+        }
+        let xml = self.to_xml().ok_or(Error::ConfigFailed)?;
+        let xml = CString::new(xml).map_err(|_| Error::ConfigFailed)?;
+        unsafe {
+            let config = ffi_dispatch!(LIB, FcConfigCreate,);
+            if config.is_null() {
+                return Err(Error::OutOfMemory);
+            }
+            // The handle releases the configuration if it cannot be built.
+            let fc = Fontconfig {
+                config,
+                owns_config: true,
+                generation: next_generation(),
+            };
+            let loaded = ffi_dispatch_optional!(
+                FcConfigParseAndLoadFromMemory,
+                config,
+                xml.as_ptr() as *const u8,
+                FcTrue
+            ) == FcTrue
+                && (self.ignore_system_config
+                    || ffi_dispatch!(LIB, FcConfigParseAndLoad, config, ptr::null(), FcTrue)
+                        == FcTrue)
+                && ffi_dispatch!(LIB, FcConfigBuildFonts, config) == FcTrue;
+            if loaded {
+                Ok(fc)
+            } else {
+                Err(Error::ConfigFailed)
+            }
+        }
+    }
+
+    /// The configuration file for these directories, or `None` if a path is not valid UTF-8.
+    ///
+    /// The cache directories come before those of the default configuration, so that the
+    /// writable one is used for new caches.
+    fn to_xml(&self) -> Option<String> {
+        let mut xml = String::from("<?xml version=\"1.0\"?>\n<fontconfig>\n");
+        for dir in self.cache_dir.iter().chain(&self.host_cache_dirs) {
+            writeln!(xml, "  <cachedir>{}</cachedir>", escape(dir.to_str()?)).unwrap();
+        }
+        for dir in &self.app_dirs {
+            writeln!(xml, "  <dir>{}</dir>", escape(dir.to_str()?)).unwrap();
+        }
+        for dir in &self.host_dirs {
+            match &dir.host_path {
+                Some(host_path) => writeln!(
+                    xml,
+                    "  <remap-dir as-path=\"{}\">{}</remap-dir>",
+                    escape(host_path.to_str()?),
+                    escape(dir.path.to_str()?)
+                ),
+                None => writeln!(xml, "  <dir>{}</dir>", escape(dir.path.to_str()?)),
+            }
+            .unwrap();
+        }
+        xml.push_str("</fontconfig>\n");
+        Some(xml)
+    }
+}
+
+/// Escape `s` for use as the text of an XML element or attribute.
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::dejavu_dir;
+    use crate::{list_fonts, Pattern, FC_FAMILY};
+
+    #[test]
+    fn xml() {
+        let fonts = SandboxFonts {
+            app_dirs: vec!["/app/share/fonts".into()],
+            host_dirs: vec![HostFontDir {
+                path: "/run/host/fonts".into(),
+                host_path: Some("/usr/share/fonts".into()),
+            }],
+            cache_dir: Some("/cache/<a&b>".into()),
+            ..SandboxFonts::default()
+        };
+        assert_eq!(
+            fonts.to_xml().unwrap(),
+            "<?xml version=\"1.0\"?>\n<fontconfig>\n  \
+             <cachedir>/cache/&lt;a&amp;b&gt;</cachedir>\n  \
+             <dir>/app/share/fonts</dir>\n  \
+             <remap-dir as-path=\"/usr/share/fonts\">/run/host/fonts</remap-dir>\n\
+             </fontconfig>\n"
+        );
+    }
+
+    #[test]
+    fn build_isolated() {
+        let fonts = SandboxFonts {
+            host_dirs: vec![HostFontDir {
+                path: dejavu_dir(),
+                host_path: None,
+            }],
+            cache_dir: Some(env::temp_dir().join("fontconfig-rs-sandbox-test")),
+            ignore_system_config: true,
+            ..SandboxFonts::default()
+        };
+        let fc = fonts.build().unwrap();
+        let fonts = list_fonts(&Pattern::new(&fc), None);
+        assert!(fonts.iter().count() > 0);
+        assert!(fonts
+            .iter()
+            .all(|font| font.name().unwrap().starts_with("DejaVu")));
+
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "DejaVu Serif");
+        assert_eq!(pat.font_match().name(), Some("DejaVu Serif"));
+    }
+}
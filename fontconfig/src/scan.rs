@@ -0,0 +1,104 @@
+//! Walking the installed fonts without listing them.
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, font_set_patterns, sys, Fontconfig, Pattern};
+
+/// A reference to a configuration, released when dropped.
+struct ConfigRef(*mut sys::FcConfig);
+
+impl Drop for ConfigRef {
+    fn drop(&mut self) {
+        unsafe { ffi_dispatch!(LIB, FcConfigDestroy, self.0) }
+    }
+}
+
+impl Fontconfig {
+    /// Call `f` with each installed font in turn, until it returns `Some`.
+    ///
+    /// The fonts are read directly from the system and application font sets of the current
+    /// configuration, or the cached one of a handle from [`Fontconfig::with_cached_config`], so
+    /// unlike [`list_fonts`](crate::list_fonts) no patterns are copied, and the walk stops as soon
+    /// as `f` finds what it is looking for. The patterns passed to `f` are references to those of
+    /// the configuration and hold every element of the font.
+    ///
+    /// The fonts are those of the configuration when the walk starts: fonts added by `f`, or by
+    /// another thread during the walk, are not visited.
+    ///
+    /// Returns the first value `f` returns, or `None` if it never returns one.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let path = fc.scan_fonts(|font| {
+    ///     if font.get_string(FC_FAMILY) == Some("DejaVu Sans Mono") {
+    ///         font.filename().map(str::to_owned)
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    /// println!("{:?}", path);
+    /// ```
+    pub fn scan_fonts<T>(&self, f: impl FnMut(&Pattern<'_>) -> Option<T>) -> Option<T> {
+        // Hold a reference so that the configuration is not destroyed by a rescan while its
+        // fonts are read.
+        let config = unsafe { ffi_dispatch!(LIB, FcConfigReference, self.config) };
+        if config.is_null() {
+            return None;
+        }
+        let config = ConfigRef(config);
+
+        // Reference the patterns before calling `f`, which may add fonts to the configuration
+        // and so reallocate or free its font sets.
+        let mut patterns = Vec::new();
+        for set_name in [sys::FcSetSystem, sys::FcSetApplication] {
+            let set = unsafe { ffi_dispatch!(LIB, FcConfigGetFonts, config.0, set_name) };
+            if set.is_null() {
+                continue;
+            }
+            let set = unsafe { font_set_patterns(set) };
+            patterns.extend(
+                set.iter()
+                    .map(|&pat| unsafe { Pattern::from_pattern(self, pat) }),
+            );
+        }
+        drop(config);
+
+        patterns.iter().find_map(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Fontconfig, FC_FAMILY};
+
+    #[test]
+    fn scan_stops_early() {
+        let fc = Fontconfig::new().unwrap();
+        let found =
+            fc.scan_fonts(|font| (font.get_string(FC_FAMILY)? == "DejaVu Sans").then_some(()));
+        assert_eq!(found, Some(()));
+
+        let mut total = 0;
+        assert_eq!(
+            fc.scan_fonts(|_| {
+                total += 1;
+                None::<()>
+            }),
+            None
+        );
+        assert!(total > 2);
+
+        // The scan stops at the first font `f` accepts, without visiting the rest.
+        let mut seen = 0;
+        let found = fc.scan_fonts(|_| {
+            seen += 1;
+            (seen == 2).then_some(seen)
+        });
+        assert_eq!(found, Some(2));
+        assert_eq!(seen, 2);
+    }
+}
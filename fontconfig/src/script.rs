@@ -0,0 +1,284 @@
+//! Default fonts for a script or language.
+
+use std::ffi::CStr;
+use std::str::FromStr;
+
+use crate::{sort_fonts, Font, Fontconfig, Pattern};
+use crate::{FC_FAMILY, FC_LANG};
+
+/// A generic font family, as resolved by the Fontconfig configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GenericFamily {
+    /// The "serif" family.
+    Serif,
+    /// The "sans-serif" family.
+    SansSerif,
+    /// The "monospace" family.
+    Monospace,
+}
+
+impl GenericFamily {
+    /// The family name Fontconfig knows this family by.
+    pub fn name(self) -> &'static CStr {
+        match self {
+            GenericFamily::Serif => c"serif",
+            GenericFamily::SansSerif => c"sans-serif",
+            GenericFamily::Monospace => c"monospace",
+        }
+    }
+}
+
+/// A writing system, identified by its ISO 15924 code when parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Script {
+    Arabic,
+    Armenian,
+    Bengali,
+    Cyrillic,
+    Devanagari,
+    Ethiopic,
+    Georgian,
+    Greek,
+    Gujarati,
+    Gurmukhi,
+    HanSimplified,
+    HanTraditional,
+    Hangul,
+    Hebrew,
+    Japanese,
+    Kannada,
+    Khmer,
+    Lao,
+    Latin,
+    Malayalam,
+    Sinhala,
+    Tamil,
+    Telugu,
+    Thai,
+    Tibetan,
+}
+
+/// Error type returned when parsing a `Script`.
+///
+/// The error holds the code that could not be parsed.
+#[derive(Debug)]
+pub struct UnknownScript(pub String);
+
+impl Script {
+    /// A language whose orthography Fontconfig uses to decide if a font covers this script.
+    pub fn lang(self) -> &'static str {
+        match self {
+            Script::Arabic => "ar",
+            Script::Armenian => "hy",
+            Script::Bengali => "bn",
+            Script::Cyrillic => "ru",
+            Script::Devanagari => "hi",
+            Script::Ethiopic => "am",
+            Script::Georgian => "ka",
+            Script::Greek => "el",
+            Script::Gujarati => "gu",
+            Script::Gurmukhi => "pa",
+            Script::HanSimplified => "zh-cn",
+            Script::HanTraditional => "zh-tw",
+            Script::Hangul => "ko",
+            Script::Hebrew => "he",
+            Script::Japanese => "ja",
+            Script::Kannada => "kn",
+            Script::Khmer => "km",
+            Script::Lao => "lo",
+            Script::Latin => "en",
+            Script::Malayalam => "ml",
+            Script::Sinhala => "si",
+            Script::Tamil => "ta",
+            Script::Telugu => "te",
+            Script::Thai => "th",
+            Script::Tibetan => "bo",
+        }
+    }
+
+    /// The script `c` is written in, or `None` for characters shared between scripts, such as
+    /// spaces, punctuation, digits and combining marks, and for those of scripts not listed.
+    ///
+    /// Han ideographs are reported as [`Script::HanSimplified`], as the script alone does not
+    /// tell the Chinese and Japanese uses apart.
+    ///
+    /// ```
+    /// use fontconfig::Script;
+    ///
+    /// assert_eq!(Script::of('ж'), Some(Script::Cyrillic));
+    /// assert_eq!(Script::of('か'), Some(Script::Japanese));
+    /// assert_eq!(Script::of(' '), None);
+    /// ```
+    pub fn of(c: char) -> Option<Script> {
+        let script = match c {
+            'A'..='Z' | 'a'..='z' => Script::Latin,
+            '\u{00AA}' | '\u{00BA}' => Script::Latin,
+            '\u{00C0}'..='\u{024F}' if c != '\u{00D7}' && c != '\u{00F7}' => Script::Latin,
+            '\u{0250}'..='\u{02AF}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+            '\u{2C60}'..='\u{2C7F}' | '\u{A720}'..='\u{A7FF}' | '\u{AB30}'..='\u{AB6F}' => {
+                Script::Latin
+            }
+            '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+            '\u{0400}'..='\u{052F}' | '\u{1C80}'..='\u{1C8F}' => Script::Cyrillic,
+            '\u{2DE0}'..='\u{2DFF}' | '\u{A640}'..='\u{A69F}' => Script::Cyrillic,
+            '\u{0530}'..='\u{058F}' => Script::Armenian,
+            '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+            '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' | '\u{08A0}'..='\u{08FF}' => {
+                Script::Arabic
+            }
+            '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => Script::Arabic,
+            '\u{0900}'..='\u{097F}' => Script::Devanagari,
+            '\u{0980}'..='\u{09FF}' => Script::Bengali,
+            '\u{0A00}'..='\u{0A7F}' => Script::Gurmukhi,
+            '\u{0A80}'..='\u{0AFF}' => Script::Gujarati,
+            '\u{0B80}'..='\u{0BFF}' => Script::Tamil,
+            '\u{0C00}'..='\u{0C7F}' => Script::Telugu,
+            '\u{0C80}'..='\u{0CFF}' => Script::Kannada,
+            '\u{0D00}'..='\u{0D7F}' => Script::Malayalam,
+            '\u{0D80}'..='\u{0DFF}' => Script::Sinhala,
+            '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+            '\u{0E80}'..='\u{0EFF}' => Script::Lao,
+            '\u{0F00}'..='\u{0FFF}' => Script::Tibetan,
+            '\u{10A0}'..='\u{10FF}' | '\u{1C90}'..='\u{1CBF}' | '\u{2D00}'..='\u{2D2F}' => {
+                Script::Georgian
+            }
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Script::Hangul
+            }
+            '\u{1200}'..='\u{139F}' | '\u{2D80}'..='\u{2DDF}' => Script::Ethiopic,
+            '\u{1780}'..='\u{17FF}' | '\u{19E0}'..='\u{19FF}' => Script::Khmer,
+            '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => Script::Japanese,
+            '\u{FF66}'..='\u{FF9F}' => Script::Japanese,
+            '\u{2E80}'..='\u{2FDF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => {
+                Script::HanSimplified
+            }
+            '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{3FFFF}' => Script::HanSimplified,
+            _ => return None,
+        };
+        Some(script)
+    }
+}
+
+impl FromStr for Script {
+    type Err = UnknownScript;
+
+    /// Parse an ISO 15924 script code, such as `Cyrl`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let script = match s.to_ascii_lowercase().as_str() {
+            "arab" => Script::Arabic,
+            "armn" => Script::Armenian,
+            "beng" => Script::Bengali,
+            "cyrl" => Script::Cyrillic,
+            "deva" => Script::Devanagari,
+            "ethi" => Script::Ethiopic,
+            "geor" => Script::Georgian,
+            "grek" => Script::Greek,
+            "gujr" => Script::Gujarati,
+            "guru" => Script::Gurmukhi,
+            "hans" | "hani" => Script::HanSimplified,
+            "hant" => Script::HanTraditional,
+            "hang" | "kore" => Script::Hangul,
+            "hebr" => Script::Hebrew,
+            "jpan" | "hira" | "kana" => Script::Japanese,
+            "knda" => Script::Kannada,
+            "khmr" => Script::Khmer,
+            "laoo" => Script::Lao,
+            "latn" => Script::Latin,
+            "mlym" => Script::Malayalam,
+            "sinh" => Script::Sinhala,
+            "taml" => Script::Tamil,
+            "telu" => Script::Telugu,
+            "thai" => Script::Thai,
+            "tibt" => Script::Tibetan,
+            _ => return Err(UnknownScript(s.to_string())),
+        };
+        Ok(script)
+    }
+}
+
+impl Fontconfig {
+    /// Resolve the generic family to a font that covers `script`.
+    ///
+    /// See [`Fontconfig::default_for_lang`].
+    pub fn default_for_script(&self, generic: GenericFamily, script: Script) -> Option<Font> {
+        self.default_for_lang(generic, script.lang())
+    }
+
+    /// Resolve the generic family to a font that covers the orthography of `lang`, e.g. `ru` or
+    /// `zh-tw`.
+    ///
+    /// Fonts are considered in the order the configuration prefers them for the language, and
+    /// the first one Fontconfig lists as supporting the language is returned. This is useful
+    /// when substituting for a font that is not installed. Returns `None` if no font of the
+    /// family covers the language.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, GenericFamily};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// if let Some(font) = fc.default_for_lang(GenericFamily::Serif, "ru") {
+    ///     println!("{}", font.name);
+    /// }
+    /// ```
+    pub fn default_for_lang(&self, generic: GenericFamily, lang: &str) -> Option<Font> {
+        let lang = lang.to_ascii_lowercase();
+        let mut pat = Pattern::new(self);
+        pat.add_string(FC_FAMILY, generic.name());
+        pat.add_string(FC_LANG, &lang);
+        pat.substitute();
+
+        let fonts = sort_fonts(&pat, false);
+        let font = fonts
+            .iter()
+            .find(|font| {
+                font.lang_set().is_some_and(|langs| {
+                    langs.iter().any(|l| {
+                        l == lang
+                            || l.strip_prefix(lang.as_str())
+                                .is_some_and(|t| t.starts_with('-'))
+                    })
+                })
+            })
+            .and_then(|font| Font::from_pattern(&font));
+        font
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_of_char() {
+        assert_eq!(Script::of('é'), Some(Script::Latin));
+        assert_eq!(Script::of('×'), None);
+        assert_eq!(Script::of('λ'), Some(Script::Greek));
+        assert_eq!(Script::of('ש'), Some(Script::Hebrew));
+        assert_eq!(Script::of('ก'), Some(Script::Thai));
+        assert_eq!(Script::of('한'), Some(Script::Hangul));
+        assert_eq!(Script::of('中'), Some(Script::HanSimplified));
+        assert_eq!(Script::of('カ'), Some(Script::Japanese));
+        for c in ['1', ',', '\u{0301}', '∑', '😀'] {
+            assert_eq!(Script::of(c), None);
+        }
+    }
+
+    #[test]
+    fn parse_script() {
+        assert_eq!("Cyrl".parse::<Script>().unwrap(), Script::Cyrillic);
+        assert_eq!("latn".parse::<Script>().unwrap(), Script::Latin);
+        assert!("Zzzz".parse::<Script>().is_err());
+    }
+
+    #[test]
+    fn default_fonts() {
+        let fc = Fontconfig::new().unwrap();
+        assert!(fc
+            .default_for_script(GenericFamily::SansSerif, Script::Cyrillic)
+            .is_some());
+        assert!(fc
+            .default_for_lang(GenericFamily::Monospace, "EN")
+            .is_some());
+    }
+}
@@ -0,0 +1,135 @@
+//! Reference-counted sharing of patterns.
+
+use std::ops::Deref;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{ffi_dispatch, Pattern, FC_FT_FACE};
+
+/// A read-only pattern that can be cloned cheaply.
+///
+/// Cloning a `Pattern` copies it with `FcPatternDuplicate`. Cloning a `SharedPattern` only takes
+/// another reference to the same `FcPattern` with `FcPatternReference`, which is safe because
+/// a shared pattern can no longer be modified. This suits match results that are handed to
+/// several parts of an application.
+///
+/// A `SharedPattern` is `Send` and `Sync`, so fully resolved match results can be kept in a
+/// global cache and read from render threads.
+///
+/// ```
+/// use fontconfig::{Fontconfig, Pattern, FC_FAMILY};
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let mut pat = Pattern::new(&fc);
+/// pat.add_string(FC_FAMILY, c"sans-serif");
+/// let font = pat.font_match().share();
+/// let for_layout = font.clone();
+/// assert_eq!(for_layout.as_ptr(), font.as_ptr());
+/// ```
+pub struct SharedPattern<'fc> {
+    pattern: Pattern<'fc>,
+}
+
+impl<'fc> Pattern<'fc> {
+    /// Convert this pattern into a read-only pattern that can be cloned cheaply.
+    ///
+    /// The pattern is copied once, as other references to it, such as those of the font set it
+    /// came from or of [`Pattern::from_pattern`], could still modify it. A FreeType face stored
+    /// in the pattern is left out of the copy, as a face cannot be used from several threads.
+    pub fn share(self) -> SharedPattern<'fc> {
+        let pat = unsafe { ffi_dispatch!(LIB, FcPatternDuplicate, self.pat) };
+        assert!(!pat.is_null());
+        unsafe { ffi_dispatch!(LIB, FcPatternDel, pat, FC_FT_FACE.as_ptr()) };
+        SharedPattern {
+            pattern: Pattern { pat, fc: self.fc },
+        }
+    }
+}
+
+// SAFETY: Since version 2.10.91, Fontconfig counts references to patterns atomically and can be
+// called from multiple threads. Its functions that read a pattern do not modify it. `share`
+// copies the pattern, so the only references to the shared `FcPattern` are those of
+// `SharedPattern`s, which only give out `&Pattern`: no thread can modify it after it has been
+// shared. The only mutable state a pattern can point to is an FT_Face added with
+// `FcPatternAddFTFace`, which `share` removes from the copy.
+unsafe impl Send for SharedPattern<'_> {}
+unsafe impl Sync for SharedPattern<'_> {}
+
+impl<'fc> Deref for SharedPattern<'fc> {
+    type Target = Pattern<'fc>;
+
+    fn deref(&self) -> &Pattern<'fc> {
+        &self.pattern
+    }
+}
+
+impl<'fc> Clone for SharedPattern<'fc> {
+    fn clone(&self) -> Self {
+        unsafe {
+            ffi_dispatch!(LIB, FcPatternReference, self.pattern.pat);
+        }
+        SharedPattern {
+            pattern: Pattern {
+                pat: self.pattern.pat,
+                fc: self.pattern.fc,
+            },
+        }
+    }
+}
+
+impl<'fc> std::fmt::Debug for SharedPattern<'fc> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.pattern.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Fontconfig, Pattern, SharedPattern, FC_FAMILY};
+
+    #[test]
+    fn share_pattern() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        let shared = pat.font_match().share();
+
+        let clones: Vec<_> = (0..3).map(|_| shared.clone()).collect();
+        assert!(clones.iter().all(|clone| clone.as_ptr() == shared.as_ptr()));
+        drop(clones);
+        assert_eq!(shared.name(), Some("DejaVu Sans"));
+
+        // A pattern with another reference to it is copied, so the other reference cannot
+        // modify the shared pattern.
+        let mut other = unsafe { Pattern::from_pattern(&fc, pat.as_mut_ptr()) };
+        let shared = pat.share();
+        assert_ne!(shared.as_ptr(), other.as_mut_ptr() as *const _);
+        other.add_string(FC_FAMILY, c"Serif");
+        assert!(!(0..)
+            .map_while(|n| shared.get_string_at(FC_FAMILY, n))
+            .any(|family| family == "Serif"));
+    }
+
+    #[test]
+    fn share_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedPattern<'static>>();
+
+        let fc = Fontconfig::global().unwrap();
+        let mut pat = Pattern::new(fc);
+        pat.add_string(FC_FAMILY, c"DejaVu Sans");
+        let shared = pat.font_match().share();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || shared.name().map(str::to_owned))
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap().as_deref(), Some("DejaVu Sans"));
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Fontconfig's helpers for file names, so that paths are handled exactly as Fontconfig does.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{cstr_to_path, ffi_dispatch, path_to_cstring, sys, FcTrue, Fontconfig};
+
+/// Expand a file name the way Fontconfig expands a `<dir>` or `<cachedir>` element.
+///
+/// A leading `~` is replaced by the home directory, a relative path is made absolute from the
+/// current directory, and `.` and `..` components are resolved without touching the file system.
+///
+/// Returns `None` if the path contains a NUL byte, or if it starts with `~` and the home
+/// directory is unknown.
+///
+/// ```
+/// use fontconfig::strings::expand_filename;
+/// use fontconfig::Fontconfig;
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let fonts = expand_filename(&fc, "~/fonts/../.fonts").unwrap();
+/// assert!(fonts.is_absolute());
+/// assert!(fonts.ends_with(".fonts"));
+/// ```
+pub fn expand_filename(_: &Fontconfig, path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path_to_cstring(path.as_ref())?;
+    unsafe {
+        take_path(ffi_dispatch!(
+            LIB,
+            FcStrCopyFilename,
+            path.as_ptr() as *const u8
+        ))
+    }
+}
+
+/// Returns `true` if `path` names a directory, following symbolic links.
+pub fn is_dir(_: &Fontconfig, path: impl AsRef<Path>) -> bool {
+    path_to_cstring(path.as_ref()).is_some_and(|path| unsafe {
+        ffi_dispatch!(LIB, FcFileIsDir, path.as_ptr() as *const u8) == FcTrue
+    })
+}
+
+/// Returns everything before the last `/` of `path`, or `.` if it has none.
+///
+/// Unlike [`Path::parent`], this only looks at the separators, so `a/b/` gives `a/b`.
+///
+/// ```
+/// use std::path::Path;
+/// use fontconfig::strings::dirname;
+/// use fontconfig::Fontconfig;
+///
+/// let fc = Fontconfig::new().unwrap();
+/// let dir = dirname(&fc, "/usr/share/fonts/DejaVuSans.ttf").unwrap();
+/// assert_eq!(dir, Path::new("/usr/share/fonts"));
+/// assert_eq!(dirname(&fc, "DejaVuSans.ttf").unwrap(), Path::new("."));
+/// ```
+pub fn dirname(_: &Fontconfig, path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path_to_cstring(path.as_ref())?;
+    unsafe { take_path(ffi_dispatch!(LIB, FcStrDirname, path.as_ptr() as *const u8)) }
+}
+
+/// Returns everything after the last `/` of `path`, or all of it if it has none.
+///
+/// Unlike [`Path::file_name`], this only looks at the separators, so `a/b/` gives an empty name.
+pub fn basename(_: &Fontconfig, path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path_to_cstring(path.as_ref())?;
+    unsafe {
+        take_path(ffi_dispatch!(
+            LIB,
+            FcStrBasename,
+            path.as_ptr() as *const u8
+        ))
+    }
+}
+
+/// Convert a string allocated by Fontconfig to a path, freeing the string.
+unsafe fn take_path(s: *mut sys::FcChar8) -> Option<PathBuf> {
+    if s.is_null() {
+        return None;
+    }
+    let path = cstr_to_path(CStr::from_ptr(s as *const c_char));
+    ffi_dispatch!(LIB, FcStrFree, s);
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand() {
+        let fc = Fontconfig::new().unwrap();
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        if let Some(home) = home.filter(|home| home.is_absolute()) {
+            assert_eq!(expand_filename(&fc, "~/fonts"), Some(home.join("fonts")));
+        }
+        assert_eq!(
+            expand_filename(&fc, "/usr/share/./fonts/../fonts/"),
+            Some(PathBuf::from("/usr/share/fonts"))
+        );
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(expand_filename(&fc, "fonts"), Some(cwd.join("fonts")));
+    }
+
+    #[test]
+    fn path_components() {
+        let fc = Fontconfig::new().unwrap();
+        assert_eq!(dirname(&fc, "/a/b/c.ttf"), Some(PathBuf::from("/a/b")));
+        assert_eq!(dirname(&fc, "a/b/"), Some(PathBuf::from("a/b")));
+        assert_eq!(dirname(&fc, "c.ttf"), Some(PathBuf::from(".")));
+        assert_eq!(basename(&fc, "/a/b/c.ttf"), Some(PathBuf::from("c.ttf")));
+        assert_eq!(basename(&fc, "c.ttf"), Some(PathBuf::from("c.ttf")));
+        assert!(is_dir(&fc, "/"));
+        assert!(!is_dir(&fc, "/nonexistent"));
+        assert!(!is_dir(&fc, std::env::current_exe().unwrap()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let fc = Fontconfig::new().unwrap();
+        let path = Path::new(OsStr::from_bytes(b"/fonts/caf\xe9/a.ttf"));
+        assert_eq!(
+            basename(&fc, path),
+            Some(PathBuf::from(OsStr::from_bytes(b"a.ttf")))
+        );
+        assert_eq!(
+            dirname(&fc, path),
+            Some(PathBuf::from(OsStr::from_bytes(b"/fonts/caf\xe9")))
+        );
+    }
+}
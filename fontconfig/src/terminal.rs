@@ -0,0 +1,120 @@
+//! Fallback fonts for terminal emulators.
+
+use crate::{sort_fonts, CharSet, Font, Fontconfig, Pattern, Spacing};
+use crate::{FC_FAMILY, FC_MONO, FC_SPACING};
+
+/// The box-drawing characters, U+2500 to U+257F.
+const BOX_DRAWING: std::ops::RangeInclusive<char> = '\u{2500}'..='\u{257F}';
+
+/// The private-use characters patched into fonts by Powerline for prompt segments and symbols.
+const POWERLINE: [char; 7] = [
+    '\u{E0A0}', '\u{E0A1}', '\u{E0A2}', '\u{E0B0}', '\u{E0B1}', '\u{E0B2}', '\u{E0B3}',
+];
+
+/// A font in the fallback chain of a terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalFont {
+    /// The font.
+    pub font: Font,
+    /// The "spacing" element of the font, if it declares one.
+    pub spacing: Option<Spacing>,
+    /// Whether the glyphs of the font fit the terminal's cells: `true` for monospace,
+    /// dual-width and character cell fonts, and `false` for proportional fonts, whose glyphs
+    /// will need to be scaled or clipped.
+    pub cell_safe: bool,
+    /// Whether the font covers all box-drawing characters.
+    pub box_drawing: bool,
+    /// Whether the font covers the Powerline symbols.
+    pub powerline: bool,
+}
+
+impl Fontconfig {
+    /// Build a fallback chain for a terminal emulator whose primary font is `family`.
+    ///
+    /// The chain holds the fonts Fontconfig sorts as closest to a monospace `family`. Monospace,
+    /// dual-width and character cell fonts come before proportional fonts, which are kept, as they
+    /// may be the only fonts covering some characters, but are marked as breaking the cell width.
+    /// Fonts that add no coverage to those before them in this order are then trimmed. Each font
+    /// also records whether it covers the box-drawing characters and the Powerline symbols, which
+    /// terminal user interfaces rely on.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// for entry in fc.terminal_fallbacks("monospace") {
+    ///     println!("{} cell-safe: {}", entry.font.name, entry.cell_safe);
+    /// }
+    /// ```
+    pub fn terminal_fallbacks(&self, family: &str) -> Vec<TerminalFont> {
+        let mut pat = Pattern::new(self);
+        pat.add_string(FC_FAMILY, family);
+        pat.add_integer(FC_SPACING, FC_MONO);
+        pat.substitute();
+
+        let mut fonts: Vec<(TerminalFont, Option<CharSet>)> = sort_fonts(&pat, false)
+            .iter()
+            .filter_map(|pattern| {
+                let spacing = pattern.spacing();
+                let char_set = pattern.char_set();
+                let entry = TerminalFont {
+                    font: Font::from_pattern(&pattern)?,
+                    spacing,
+                    cell_safe: matches!(
+                        spacing,
+                        Some(Spacing::Mono | Spacing::Dual | Spacing::CharCell)
+                    ),
+                    box_drawing: covers_all(char_set.as_ref(), BOX_DRAWING),
+                    powerline: covers_all(char_set.as_ref(), POWERLINE),
+                };
+                Some((entry, char_set))
+            })
+            .collect();
+        // The sort is stable, so each group keeps Fontconfig's order. It comes before trimming,
+        // so that a cell-safe font is not trimmed for a proportional font sorted ahead of it.
+        fonts.sort_by_key(|(entry, _)| !entry.cell_safe);
+
+        // Like Fontconfig, keep the first font and then each font that adds coverage.
+        let mut covered = CharSet::new(self);
+        let mut chain = Vec::new();
+        for (entry, char_set) in fonts {
+            let adds = char_set.is_some_and(|char_set| covered.merge(&char_set));
+            if adds || chain.is_empty() {
+                chain.push(entry);
+            }
+        }
+        chain
+    }
+}
+
+fn covers_all(char_set: Option<&CharSet>, chars: impl IntoIterator<Item = char>) -> bool {
+    char_set.is_some_and(|set| chars.into_iter().all(|c| set.contains(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monospace_chain() {
+        let fc = Fontconfig::new().unwrap();
+        let chain = fc.terminal_fallbacks("DejaVu Sans Mono");
+        let first = &chain[0];
+        assert_eq!(first.font.name, "DejaVu Sans Mono");
+        assert_eq!(first.spacing, Some(Spacing::Mono));
+        assert!(first.cell_safe);
+        assert!(first.box_drawing);
+
+        let safe = chain.iter().take_while(|entry| entry.cell_safe).count();
+        assert!(chain[safe..].iter().all(|entry| !entry.cell_safe));
+
+        // A font is only kept for the coverage it adds, so of the styles of a family, which
+        // cover the same characters, only the first is kept.
+        let names: Vec<&str> = chain.iter().map(|entry| entry.font.name.as_str()).collect();
+        let mut unique = names.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert!(chain.len() < sort_fonts(&Pattern::new(&fc), false).iter().count());
+        assert_eq!(unique.len(), names.len(), "{:?}", names);
+    }
+}
@@ -0,0 +1,110 @@
+//! Conversions between `Matrix` and the transform types of other crates.
+//!
+//! Fontconfig matrices are linear transforms with no translation. Converting a transform that
+//! has a translation into a `Matrix` drops the translation.
+
+use crate::Matrix;
+
+#[cfg(feature = "mint")]
+impl From<Matrix> for mint::ColumnMatrix2<f64> {
+    fn from(matrix: Matrix) -> Self {
+        mint::ColumnMatrix2 {
+            x: mint::Vector2 {
+                x: matrix.xx,
+                y: matrix.yx,
+            },
+            y: mint::Vector2 {
+                x: matrix.xy,
+                y: matrix.yy,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::ColumnMatrix2<f64>> for Matrix {
+    fn from(matrix: mint::ColumnMatrix2<f64>) -> Self {
+        Matrix {
+            xx: matrix.x.x,
+            xy: matrix.y.x,
+            yx: matrix.x.y,
+            yy: matrix.y.y,
+        }
+    }
+}
+
+// euclid transforms row vectors, so its matrices are the transpose of Fontconfig's.
+#[cfg(feature = "euclid")]
+impl<Src, Dst> From<Matrix> for euclid::Transform2D<f64, Src, Dst> {
+    fn from(matrix: Matrix) -> Self {
+        euclid::Transform2D::new(matrix.xx, matrix.yx, matrix.xy, matrix.yy, 0.0, 0.0)
+    }
+}
+
+#[cfg(feature = "euclid")]
+impl<Src, Dst> From<euclid::Transform2D<f64, Src, Dst>> for Matrix {
+    fn from(transform: euclid::Transform2D<f64, Src, Dst>) -> Self {
+        Matrix {
+            xx: transform.m11,
+            xy: transform.m21,
+            yx: transform.m12,
+            yy: transform.m22,
+        }
+    }
+}
+
+#[cfg(feature = "kurbo")]
+impl From<Matrix> for kurbo::Affine {
+    fn from(matrix: Matrix) -> Self {
+        kurbo::Affine::new([matrix.xx, matrix.yx, matrix.xy, matrix.yy, 0.0, 0.0])
+    }
+}
+
+#[cfg(feature = "kurbo")]
+impl From<kurbo::Affine> for Matrix {
+    fn from(affine: kurbo::Affine) -> Self {
+        let [xx, yx, xy, yy, _, _] = affine.as_coeffs();
+        Matrix { xx, xy, yx, yy }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Matrix;
+
+    /// A shear that moves `(1, 0)` to `(1, 2)` and `(0, 1)` to `(3, 1)`.
+    const SHEAR: Matrix = Matrix {
+        xx: 1.0,
+        xy: 3.0,
+        yx: 2.0,
+        yy: 1.0,
+    };
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint_round_trip() {
+        let matrix = mint::ColumnMatrix2::from(SHEAR);
+        assert_eq!(matrix.x, mint::Vector2 { x: 1.0, y: 2.0 });
+        assert_eq!(Matrix::from(matrix), SHEAR);
+    }
+
+    #[cfg(feature = "euclid")]
+    #[test]
+    fn euclid_round_trip() {
+        let transform: euclid::default::Transform2D<f64> = SHEAR.into();
+        let point = transform.transform_point(euclid::point2(0.0, 1.0));
+        assert_eq!(point, euclid::point2(3.0, 1.0));
+        assert_eq!(Matrix::from(transform), SHEAR);
+    }
+
+    #[cfg(feature = "kurbo")]
+    #[test]
+    fn kurbo_round_trip() {
+        let affine = kurbo::Affine::from(SHEAR);
+        assert_eq!(
+            affine * kurbo::Point::new(0.0, 1.0),
+            kurbo::Point::new(3.0, 1.0)
+        );
+        assert_eq!(Matrix::from(affine), SHEAR);
+    }
+}
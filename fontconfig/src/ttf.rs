@@ -0,0 +1,103 @@
+//! Integration with the `ttf-parser` crate.
+
+use std::io;
+
+use crate::{Font, MappedFont, Pattern, Tag};
+
+/// A memory-mapped font file, from which a `ttf_parser::Face` can be parsed.
+///
+/// The file is mapped rather than read, so opening large fonts or collections is cheap. As with
+/// any memory map, the contents may change if the file is modified while it is mapped.
+pub struct MappedFace {
+    font: MappedFont,
+}
+
+impl MappedFace {
+    /// Parse the matched face.
+    pub fn face(&self) -> Result<ttf_parser::Face<'_>, ttf_parser::FaceParsingError> {
+        ttf_parser::Face::parse(self.font.data(), self.index())
+    }
+
+    /// The contents of the font file.
+    pub fn data(&self) -> &[u8] {
+        self.font.data()
+    }
+
+    /// The index of the matched face within the font file.
+    pub fn index(&self) -> u32 {
+        // The upper 16 bits hold the named instance of a variable font.
+        self.font.index() as u32 & 0xFFFF
+    }
+}
+
+impl Font {
+    /// Memory-map the file containing this font so that it can be parsed with `ttf-parser`.
+    ///
+    /// Returns an error of kind `InvalidData` if the file has no face at the index of this font.
+    ///
+    /// ```
+    /// use fontconfig::Fontconfig;
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let font = fc.find("sans-serif", None).unwrap();
+    /// let mapped = font.open_face().unwrap();
+    /// let face = mapped.face().unwrap();
+    /// println!("{} glyphs", face.number_of_glyphs());
+    /// ```
+    pub fn open_face(&self) -> io::Result<MappedFace> {
+        self.map().map(|font| MappedFace { font })
+    }
+}
+
+impl Pattern<'_> {
+    /// Memory-map the file of this font pattern so that it can be parsed with `ttf-parser`.
+    ///
+    /// Returns an error of kind `NotFound` if the pattern has no file, and of kind
+    /// `InvalidData` if the file has no face at the index of the pattern.
+    pub fn open_face(&self) -> io::Result<MappedFace> {
+        self.map_file().map(|font| MappedFace { font })
+    }
+}
+
+/// Returns `true` if the GSUB or GPOS table of the font defines `feature`.
+pub(crate) fn supports_feature(pattern: &Pattern, feature: Tag) -> bool {
+    let tag = ttf_parser::Tag::from_bytes(&feature.to_bytes());
+    let Ok(mapped) = pattern.open_face() else {
+        return false;
+    };
+    mapped.face().is_ok_and(|face| {
+        let tables = face.tables();
+        [tables.gsub, tables.gpos]
+            .into_iter()
+            .flatten()
+            .any(|table| table.features.find(tag).is_some())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fontconfig, FC_INDEX};
+
+    #[test]
+    fn open_matched_face() {
+        let fc = Fontconfig::new().unwrap();
+        let font = fc.find("dejavu sans", None).unwrap();
+        let mapped = font.open_face().unwrap();
+        let face = mapped.face().unwrap();
+        assert!(face.number_of_glyphs() > 0);
+        assert!(face.names().into_iter().any(|name| {
+            name.name_id == ttf_parser::name_id::FAMILY
+                && name.to_string().as_deref() == Some("DejaVu Sans")
+        }));
+
+        // The index is checked against the faces in the file.
+        let mut pattern = Pattern::from_file(&fc, &font.path, 0).unwrap();
+        pattern.del(FC_INDEX);
+        pattern.add_integer(FC_INDEX, 5);
+        let err = pattern.open_face().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Pattern::new(&fc).open_face().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
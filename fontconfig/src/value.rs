@@ -0,0 +1,489 @@
+//! Reading and adding values of any supported type.
+
+use std::ffi::{c_int, c_void, CStr};
+use std::mem::MaybeUninit;
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+#[cfg(feature = "dlopen")]
+use crate::LIB;
+use crate::{
+    ffi_dispatch, ffi_dispatch_optional, sys, CharSet, Error, FcFalse, FcResultExt, FcTrue,
+    FromPatternValue, IntoFcStr, LangSet, Matrix, Pattern, Result, Spacing, ValueType,
+};
+use crate::{FC_CHARCELL, FC_DUAL, FC_MONO, FC_PROPORTIONAL};
+
+/// A value that can be added to a pattern with [`Pattern::add`].
+///
+/// This is implemented for strings, integers, doubles, booleans, matrices, spacings and
+/// references to character and language sets. Strings that are already C strings are not copied
+/// before being handed to Fontconfig. Sets are copied, so changing a set after adding it does
+/// not change the pattern.
+///
+/// Adding is infallible for the reasons given under [Adding values](Pattern#adding-values): a
+/// value the element does not accept is left out and shows up as missing when read back.
+pub trait IntoPatternValue {
+    /// Add this value to the element `name` of `pattern`.
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr);
+}
+
+impl<'a, T: IntoFcStr<'a>> IntoPatternValue for T {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_string(name, self);
+    }
+}
+
+impl IntoPatternValue for c_int {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_integer(name, self);
+    }
+}
+
+impl IntoPatternValue for f64 {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_double(name, self);
+    }
+}
+
+impl IntoPatternValue for bool {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_bool(name, self);
+    }
+}
+
+impl IntoPatternValue for Matrix {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_matrix(name, self);
+    }
+}
+
+impl IntoPatternValue for Spacing {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        let spacing = match self {
+            Spacing::Proportional => FC_PROPORTIONAL,
+            Spacing::Dual => FC_DUAL,
+            Spacing::Mono => FC_MONO,
+            Spacing::CharCell => FC_CHARCELL,
+        };
+        pattern.add_integer(name, spacing);
+    }
+}
+
+impl IntoPatternValue for &CharSet {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        pattern.add_char_set_to(name, self);
+    }
+}
+
+impl IntoPatternValue for &LangSet {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        unsafe {
+            ffi_dispatch!(
+                LIB,
+                FcPatternAddLangSet,
+                pattern.as_mut_ptr(),
+                name.as_ptr(),
+                self.as_ptr()
+            );
+        }
+    }
+}
+
+/// A value of a pattern element, of whichever type it holds.
+///
+/// Accessors such as [`Pattern::try_get_int`] and [`Attribute`](crate::Attribute) need the type
+/// of an element to be known when the code is written. A `Value` serves elements whose type is
+/// only known at runtime, e.g. names read from a configuration or a user.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// An integer, often one of a set of constants such as `FC_SLANT_ITALIC`.
+    Integer(i32),
+    /// A floating-point number.
+    Double(f64),
+    /// A string.
+    String(String),
+    /// A boolean.
+    Bool(bool),
+    /// A 2x2 transformation matrix.
+    Matrix(Matrix),
+    /// A set of characters.
+    CharSet(CharSet),
+    /// A set of languages.
+    LangSet(LangSet),
+    /// A range of numbers from the first to the second, such as the weights of a variable font.
+    Range(f64, f64),
+    /// A FreeType face (`FT_Face`), which the pattern does not own.
+    FtFace(*mut c_void),
+}
+
+impl Value {
+    /// The type of this value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::Integer(_) => ValueType::Integer,
+            Value::Double(_) => ValueType::Double,
+            Value::String(_) => ValueType::String,
+            Value::Bool(_) => ValueType::Bool,
+            Value::Matrix(_) => ValueType::Matrix,
+            Value::CharSet(_) => ValueType::CharSet,
+            Value::LangSet(_) => ValueType::LangSet,
+            Value::Range(..) => ValueType::Range,
+            Value::FtFace(_) => ValueType::FtFace,
+        }
+    }
+
+    /// Copy a value held by a pattern.
+    unsafe fn from_raw(value: sys::FcValue) -> Result<Value> {
+        let u = value.u;
+        Ok(match value._type {
+            sys::FcTypeInteger => Value::Integer(u.i),
+            sys::FcTypeDouble => Value::Double(u.d),
+            sys::FcTypeString => Value::String(CStr::from_ptr(u.s.cast()).to_str()?.to_owned()),
+            sys::FcTypeBool => Value::Bool(u.b != 0),
+            sys::FcTypeMatrix => {
+                let matrix = *u.m;
+                Value::Matrix(Matrix {
+                    xx: matrix.xx,
+                    xy: matrix.xy,
+                    yx: matrix.yx,
+                    yy: matrix.yy,
+                })
+            }
+            sys::FcTypeCharSet => Value::CharSet(CharSet::copy_raw(u.c)),
+            sys::FcTypeLangSet => Value::LangSet(LangSet::copy_raw(u.l)),
+            // Ranges only exist if the loaded Fontconfig has the functions for them.
+            sys::FcTypeRange if sys::available::FcRangeGetDouble() => {
+                let (mut begin, mut end) = (0.0, 0.0);
+                ffi_dispatch_optional!(FcRangeGetDouble, u.r, &mut begin, &mut end);
+                Value::Range(begin, end)
+            }
+            sys::FcTypeFTFace => Value::FtFace(u.f),
+            _ => return Err(Error::TypeMismatch),
+        })
+    }
+}
+
+impl FromPatternValue for Value {
+    fn from_pattern_value(pattern: &Pattern<'_>, name: &CStr, n: c_int) -> Result<Self> {
+        pattern.get_value(name, n)
+    }
+}
+
+impl IntoPatternValue for &Value {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        match self {
+            Value::Integer(i) => pattern.add_integer(name, *i),
+            Value::Double(d) => pattern.add_double(name, *d),
+            Value::String(s) => pattern.add_string(name, s.as_str()),
+            Value::Bool(b) => pattern.add_bool(name, *b),
+            Value::Matrix(matrix) => pattern.add_matrix(name, *matrix),
+            Value::CharSet(set) => set.add_to(pattern, name),
+            Value::LangSet(set) => set.add_to(pattern, name),
+            Value::Range(begin, end) => {
+                if sys::available::FcRangeCreateDouble() {
+                    unsafe {
+                        let range = ffi_dispatch_optional!(FcRangeCreateDouble, *begin, *end);
+                        if !range.is_null() {
+                            ffi_dispatch_optional!(
+                                FcPatternAddRange,
+                                pattern.as_mut_ptr(),
+                                name.as_ptr(),
+                                range
+                            );
+                            ffi_dispatch_optional!(FcRangeDestroy, range);
+                        }
+                    }
+                }
+            }
+            // The pattern would not keep the face alive, so adding one is left to unsafe code.
+            Value::FtFace(_) => {}
+        }
+    }
+}
+
+impl IntoPatternValue for Value {
+    fn add_to(self, pattern: &mut Pattern<'_>, name: &CStr) {
+        (&self).add_to(pattern, name);
+    }
+}
+
+impl Pattern<'_> {
+    /// Get the value at position `n` of the element `name`, whatever its type.
+    ///
+    /// Returns [`Error::NoMatch`] if the element is not present and [`Error::NoId`] if it has
+    /// fewer values.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, Value};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add_value(c"customelement", &Value::Double(0.5));
+    /// assert_eq!(pat.get_value(c"customelement", 0).unwrap(), Value::Double(0.5));
+    /// ```
+    pub fn get_value<'a>(&self, name: impl IntoFcStr<'a>, n: c_int) -> Result<Value> {
+        let name = name.into_fc_str().ok_or(Error::NoMatch)?;
+        unsafe {
+            let mut value = MaybeUninit::<sys::FcValue>::uninit();
+            ffi_dispatch!(
+                LIB,
+                FcPatternGet,
+                self.pat,
+                name.as_ptr(),
+                n,
+                value.as_mut_ptr()
+            )
+            .to_result()?;
+            Value::from_raw(value.assume_init())
+        }
+    }
+
+    /// Add `value` to the element `name` of this pattern, like [`Pattern::add`].
+    ///
+    /// A range is left out if the loaded Fontconfig is older than 2.12, which added ranges. A
+    /// FreeType face is always left out, as nothing would keep the face alive for as long as the
+    /// pattern refers to it; add one with `FcPatternAddFTFace` through
+    /// [`as_mut_ptr`](Pattern::as_mut_ptr) instead.
+    pub fn add_value<'a>(&mut self, name: impl IntoFcStr<'a>, value: &Value) {
+        if let Some(name) = name.into_fc_str() {
+            value.add_to(self, &name);
+        }
+    }
+
+    /// Add a value of any supported type to the element `name` of this pattern.
+    ///
+    /// A value the element does not accept is left out; see [Adding values](Pattern#adding-values).
+    ///
+    /// ```
+    /// use fontconfig::{CharSet, Fontconfig, Pattern, FC_CHARSET, FC_FAMILY, FC_SPACING};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut charset = CharSet::new(&fc);
+    /// charset.add_char('ŋ');
+    ///
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add(FC_FAMILY, "monospace");
+    /// pat.add(FC_SPACING, fontconfig::FC_MONO);
+    /// pat.add(FC_CHARSET, &charset);
+    /// ```
+    pub fn add<'a>(&mut self, name: impl IntoFcStr<'a>, value: impl IntoPatternValue) {
+        if let Some(name) = name.into_fc_str() {
+            value.add_to(self, &name);
+        }
+    }
+
+    /// Append a value to the element `name` of this pattern with weak binding.
+    ///
+    /// Values added with [`Pattern::add`] are strongly bound. Weakly bound values, like the
+    /// families that aliases in configuration files append, count for less when matching: a
+    /// weakly bound family ranks below the languages of the pattern rather than above them, so
+    /// it acts as a fallback for fonts that do not cover the languages.
+    ///
+    /// ```
+    /// use fontconfig::{Fontconfig, Pattern, FC_FAMILY, FC_LANG};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add(FC_LANG, "ja");
+    /// pat.add_weak(FC_FAMILY, "sans-serif");
+    /// ```
+    pub fn add_weak<'a>(&mut self, name: impl IntoFcStr<'a>, value: impl IntoPatternValue) {
+        self.add_with(
+            name,
+            value,
+            AddOptions {
+                weak: true,
+                prepend: false,
+            },
+        );
+    }
+
+    /// Add a value to the element `name` of this pattern, choosing its binding and whether it
+    /// goes before or after the values already there.
+    ///
+    /// Prepending makes the value the preferred one, as a configuration rule with
+    /// `mode="prepend"` would, while a weak binding lets the languages of the pattern and rules
+    /// of the configuration outweigh it.
+    ///
+    /// ```
+    /// use fontconfig::{AddOptions, Fontconfig, Pattern, FC_FAMILY};
+    ///
+    /// let fc = Fontconfig::new().unwrap();
+    /// let mut pat = Pattern::new(&fc);
+    /// pat.add(FC_FAMILY, "sans-serif");
+    /// pat.add_with(FC_FAMILY, "DejaVu Sans", AddOptions { prepend: true, ..AddOptions::default() });
+    /// assert_eq!(pat.get_string(FC_FAMILY), Some("DejaVu Sans"));
+    /// ```
+    pub fn add_with<'a>(
+        &mut self,
+        name: impl IntoFcStr<'a>,
+        value: impl IntoPatternValue,
+        options: AddOptions,
+    ) {
+        let Some(name) = name.into_fc_str() else {
+            return;
+        };
+        // Fontconfig only adds values from an `FcValue` this way, so build it in a scratch pattern.
+        let mut scratch = Pattern::new(self.fc);
+        value.add_to(&mut scratch, &name);
+        let append = if options.prepend { FcFalse } else { FcTrue };
+        unsafe {
+            let mut value = MaybeUninit::<sys::FcValue>::uninit();
+            if ffi_dispatch!(
+                LIB,
+                FcPatternGet,
+                scratch.pat,
+                name.as_ptr(),
+                0,
+                value.as_mut_ptr()
+            ) == sys::FcResultMatch
+            {
+                // The value is copied, so it may be freed with the scratch pattern.
+                let value = value.assume_init();
+                if options.weak {
+                    ffi_dispatch!(
+                        LIB,
+                        FcPatternAddWeak,
+                        self.pat,
+                        name.as_ptr(),
+                        value,
+                        append
+                    );
+                } else {
+                    ffi_dispatch!(LIB, FcPatternAdd, self.pat, name.as_ptr(), value, append);
+                }
+            }
+        }
+    }
+}
+
+/// How [`Pattern::add_with`] adds a value.
+///
+/// The default appends a strongly bound value, as [`Pattern::add`] does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AddOptions {
+    /// Bind the value weakly, as [`Pattern::add_weak`] does.
+    pub weak: bool,
+    /// Put the value before the values already there, rather than after them.
+    pub prepend: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn dynamic_values() {
+        let fc = Fontconfig::new().unwrap();
+        let mut charset = CharSet::new(&fc);
+        charset.add_char('a');
+        let values = [
+            Value::Integer(3),
+            Value::Double(1.5),
+            Value::String("Example".into()),
+            Value::Bool(true),
+            Value::Matrix(Matrix::IDENTITY),
+            Value::CharSet(charset),
+            Value::Range(100.0, 900.0),
+        ];
+
+        let mut pat = Pattern::new(&fc);
+        for value in &values {
+            pat.add_value(c"dynamic", value);
+        }
+        for (n, value) in values.iter().enumerate() {
+            let got = pat.get_value(c"dynamic", n as i32).unwrap();
+            assert_eq!(got.value_type(), value.value_type());
+            assert_eq!(&got, value);
+        }
+        assert!(matches!(pat.get_value(c"dynamic", 7), Err(Error::NoId)));
+        assert!(matches!(pat.get_value(c"missing", 0), Err(Error::NoMatch)));
+
+        // A variable font holds its weights as a range.
+        let mut variable = Pattern::new(&fc);
+        variable.add(FC_WEIGHT, Value::Range(FC_WEIGHT_LIGHT as f64, 200.0));
+        assert_eq!(
+            variable
+                .get_attribute(Attribute::<Value>::new(FC_WEIGHT))
+                .unwrap(),
+            Value::Range(FC_WEIGHT_LIGHT as f64, 200.0)
+        );
+    }
+
+    #[test]
+    fn add_values() {
+        let fc = Fontconfig::new().unwrap();
+        let mut charset = CharSet::new(&fc);
+        charset.add_char('a');
+        let mut langset = LangSet::new(&fc);
+        langset.add("fr");
+
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "Example");
+        pat.add(FC_FAMILY, String::from("Second"));
+        // Strings with a NUL byte cannot reach Fontconfig and are skipped.
+        pat.add(FC_FAMILY, "Bad\0Family");
+        pat.add("bad\0element", "Example");
+        pat.add(FC_WEIGHT, FC_WEIGHT_BOLD);
+        pat.add(FC_PIXEL_SIZE, 12.5);
+        pat.add(FC_VARIABLE, true);
+        pat.add(FC_CHARSET, &charset);
+        pat.add(FC_LANG, &langset);
+
+        assert_eq!(pat.get_string_at(FC_FAMILY, 1), Some("Second"));
+        assert!(pat.get_string_at(FC_FAMILY, 2).is_none());
+        assert_eq!(pat.weight(), Some(FC_WEIGHT_BOLD));
+        assert_eq!(pat.get_double(FC_PIXEL_SIZE), Some(12.5));
+        assert_eq!(pat.get_bool(FC_VARIABLE), Some(true));
+        assert!(pat.char_set().unwrap().contains('a'));
+        assert!(pat.get_lang_set().unwrap().contains("fr"));
+
+        // The pattern holds a copy of the set.
+        charset.add_char('b');
+        assert!(!pat.char_set().unwrap().contains('b'));
+    }
+
+    #[test]
+    fn add_options() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pat = Pattern::new(&fc);
+        pat.add(FC_FAMILY, "b");
+        pat.add_with(FC_FAMILY, "c", AddOptions::default());
+        let weak_first = AddOptions {
+            weak: true,
+            prepend: true,
+        };
+        pat.add_with(FC_FAMILY, "a", weak_first);
+        let families: Vec<_> = pat
+            .bound_values()
+            .map(|value| (pat.get_string_at(FC_FAMILY, value.index), value.binding))
+            .collect();
+        assert_eq!(
+            families,
+            [
+                (Some("a"), Binding::Weak),
+                (Some("b"), Binding::Strong),
+                (Some("c"), Binding::Strong),
+            ]
+        );
+    }
+
+    #[test]
+    fn weak_binding() {
+        let fc = Fontconfig::new().unwrap();
+        // The math font does not cover Hebrew, which only matters once the family is weak.
+        let mut strong = Pattern::new(&fc);
+        strong.add(FC_LANG, "he");
+        strong.add(FC_FAMILY, "DejaVu Math TeX Gyre");
+        let mut weak = Pattern::new(&fc);
+        weak.add(FC_LANG, "he");
+        weak.add_weak(FC_FAMILY, "DejaVu Math TeX Gyre");
+        assert_eq!(weak.get_string(FC_FAMILY), Some("DejaVu Math TeX Gyre"));
+
+        let strong = strong.font_match();
+        let weak = weak.font_match();
+        assert_eq!(strong.get_string(FC_FAMILY), Some("DejaVu Math TeX Gyre"));
+        assert_ne!(weak.get_string(FC_FAMILY), Some("DejaVu Math TeX Gyre"));
+        assert!(weak.get_lang_set().unwrap().contains("he"));
+    }
+}
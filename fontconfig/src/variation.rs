@@ -0,0 +1,302 @@
+//! OpenType tags and variation axis settings.
+
+use std::ffi::CStr;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Pattern, FC_FONT_VARIATIONS, FC_VARIABLE};
+
+/// An OpenType tag, such as the name of a variation axis or feature.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag([u8; 4]);
+
+/// Error type returned when parsing a `Tag`.
+///
+/// The error holds the string that could not be parsed.
+#[derive(Debug)]
+pub struct InvalidTag(pub String);
+
+impl Tag {
+    /// Create a tag from its four bytes, e.g. `Tag::new(b"wght")`.
+    pub const fn new(tag: &[u8; 4]) -> Tag {
+        Tag(*tag)
+    }
+
+    /// Returns the four bytes of this tag.
+    pub const fn to_bytes(self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Returns this tag as a big-endian integer, as used by HarfBuzz and FreeType.
+    pub const fn to_u32(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+}
+
+impl FromStr for Tag {
+    type Err = InvalidTag;
+
+    /// Parse a tag of one to four ASCII characters, padding it with spaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > 4 || !s.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+            return Err(InvalidTag(s.to_string()));
+        }
+
+        let mut bytes = [b' '; 4];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Tag(bytes))
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+impl fmt::Debug for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tag({:?})", self.to_string())
+    }
+}
+
+/// The value of a single variation axis, e.g. `wght=650`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisSetting {
+    /// The axis tag.
+    pub tag: Tag,
+    /// The value of the axis in user space.
+    pub value: f32,
+}
+
+impl FromStr for AxisSetting {
+    type Err = InvalidTag;
+
+    /// Parse an axis setting in the `tag=value` form used by the "fontvariations" element.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidTag(s.to_string());
+        let (tag, value) = s.split_once('=').ok_or_else(invalid)?;
+        Ok(AxisSetting {
+            tag: tag.trim().parse()?,
+            value: value.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// An OpenType feature setting in the syntax of `hb_feature_from_string`, such as `smcp`,
+/// `-liga`, `aalt=2` or `kern[3:5]=0`, as held by the "fontfeatures" element.
+///
+/// This is the one parser of that syntax, shared by the `harfbuzz` and `ttf-parser` features.
+#[cfg_attr(
+    not(any(feature = "harfbuzz", feature = "ttf-parser")),
+    allow(dead_code)
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FeatureSetting {
+    pub(crate) tag: Tag,
+    /// 0 disables the feature, 1 enables it, and larger values select alternates.
+    pub(crate) value: u32,
+    /// The first cluster the setting applies to.
+    pub(crate) start: u32,
+    /// The cluster the setting stops applying at, `u32::MAX` for the end of the text.
+    pub(crate) end: u32,
+}
+
+impl FeatureSetting {
+    /// Parse a feature setting, returning `None` if it is not valid.
+    pub(crate) fn parse(s: &str) -> Option<FeatureSetting> {
+        let s = s.trim();
+        let (default_value, s) = match s.as_bytes().first()? {
+            b'-' => (0, &s[1..]),
+            b'+' => (1, &s[1..]),
+            _ => (1, s),
+        };
+
+        let (s, value) = match s.split_once('=') {
+            Some((s, value)) => (s.trim(), Some(parse_feature_value(value.trim())?)),
+            None => (s, None),
+        };
+
+        let (tag, start, end) = match s.split_once('[') {
+            Some((tag, range)) => {
+                let range = range.trim_end().strip_suffix(']')?;
+                let (start, end) = match range.split_once(':') {
+                    Some((start, end)) => (parse_bound(start, 0)?, parse_bound(end, u32::MAX)?),
+                    None => {
+                        let start = range.trim().parse::<u32>().ok()?;
+                        (start, start.checked_add(1)?)
+                    }
+                };
+                (tag, start, end)
+            }
+            None => (s, 0, u32::MAX),
+        };
+
+        Some(FeatureSetting {
+            tag: parse_quoted_tag(tag)?,
+            value: value.unwrap_or(default_value),
+            start,
+            end,
+        })
+    }
+}
+
+/// Parse a tag that may be enclosed in single or double quotes.
+fn parse_quoted_tag(tag: &str) -> Option<Tag> {
+    let tag = tag.trim();
+    let tag = tag
+        .strip_prefix('\'')
+        .and_then(|t| t.strip_suffix('\''))
+        .or_else(|| tag.strip_prefix('"').and_then(|t| t.strip_suffix('"')))
+        .unwrap_or(tag);
+    tag.parse().ok()
+}
+
+fn parse_feature_value(value: &str) -> Option<u32> {
+    match value {
+        "on" | "true" => Some(1),
+        "off" | "false" => Some(0),
+        _ => value.parse().ok(),
+    }
+}
+
+fn parse_bound(bound: &str, default: u32) -> Option<u32> {
+    let bound = bound.trim();
+    if bound.is_empty() {
+        Some(default)
+    } else {
+        bound.parse().ok()
+    }
+}
+
+impl Pattern<'_> {
+    /// Pin a variation axis of the matched font to `value`, e.g. `add_axis(Tag::new(b"wght"), 650.0)`.
+    ///
+    /// The setting is added to the "fontvariations" element, and the "variable" element is set
+    /// so that the variable face of a font is preferred over its named instances and static
+    /// faces. Use [`Pattern::axis_values`] on the matched pattern to get the resulting values.
+    pub fn add_axis(&mut self, tag: Tag, value: f32) {
+        self.add_string(FC_FONT_VARIATIONS, format!("{}={}", tag, value));
+        self.del(FC_VARIABLE);
+        self.add_bool(FC_VARIABLE, true);
+    }
+
+    /// Get the effective value of each axis set by the "fontvariations" element of this pattern.
+    ///
+    /// When a variable font is matched, Fontconfig appends settings derived from the weight,
+    /// width and other elements to those requested, and the last setting of an axis wins. The
+    /// result has one setting per axis, in the order each axis was first set. Use
+    /// `axis_settings` with the `skrifa` feature to also check the values against the font.
+    pub fn axis_values(&self) -> Vec<AxisSetting> {
+        let mut values: Vec<AxisSetting> = Vec::new();
+        for setting in self.font_variations() {
+            match values.iter_mut().find(|value| value.tag == setting.tag) {
+                Some(value) => value.value = setting.value,
+                None => values.push(setting),
+            }
+        }
+        values
+    }
+
+    /// Get the variation axis settings held by the "fontvariations" element of this pattern.
+    ///
+    /// These are the settings requested by configuration or by the application; they are not
+    /// checked against the axes of the font. Settings that cannot be parsed are skipped.
+    pub fn font_variations(&self) -> Vec<AxisSetting> {
+        self.setting_strings(FC_FONT_VARIATIONS)
+            .filter_map(|setting| setting.parse().ok())
+            .collect()
+    }
+
+    /// Iterate the comma-separated settings in every value of the string element `name`.
+    pub(crate) fn setting_strings<'a>(&'a self, name: &'a CStr) -> impl Iterator<Item = &'a str> {
+        (0..)
+            .map_while(move |n| self.get_string_at(name, n))
+            .flat_map(|value| value.split(','))
+            .filter(|setting| !setting.trim().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fontconfig;
+
+    #[test]
+    fn parse_tags() {
+        assert_eq!("wght".parse::<Tag>().unwrap(), Tag::new(b"wght"));
+        assert_eq!("cv".parse::<Tag>().unwrap(), Tag::new(b"cv  "));
+        assert_eq!(Tag::new(b"wght").to_u32(), 0x77676874);
+        assert_eq!(Tag::new(b"wght").to_string(), "wght");
+        assert!("toolong".parse::<Tag>().is_err());
+        assert!("".parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn parse_feature_settings() {
+        let setting = |tag, value, start, end| FeatureSetting {
+            tag: Tag::new(tag),
+            value,
+            start,
+            end,
+        };
+        assert_eq!(
+            FeatureSetting::parse(" +smcp "),
+            Some(setting(b"smcp", 1, 0, u32::MAX))
+        );
+        assert_eq!(
+            FeatureSetting::parse("'smcp'[3:5]=2"),
+            Some(setting(b"smcp", 2, 3, 5))
+        );
+        assert_eq!(
+            FeatureSetting::parse("-kern[:5]"),
+            Some(setting(b"kern", 0, 0, 5))
+        );
+        assert_eq!(FeatureSetting::parse("smcp[3:5"), None);
+        assert_eq!(FeatureSetting::parse("'smcp"), None);
+        assert_eq!(FeatureSetting::parse("smcp=x"), None);
+    }
+
+    #[test]
+    fn pattern_variations() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_string(FC_FONT_VARIATIONS, c"wght=650, wdth=75,bogus");
+        assert_eq!(
+            pattern.font_variations(),
+            [
+                AxisSetting {
+                    tag: Tag::new(b"wght"),
+                    value: 650.0
+                },
+                AxisSetting {
+                    tag: Tag::new(b"wdth"),
+                    value: 75.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pin_axes() {
+        let fc = Fontconfig::new().unwrap();
+        let mut pattern = Pattern::new(&fc);
+        pattern.add_axis(Tag::new(b"wght"), 650.0);
+        pattern.add_axis(Tag::new(b"wdth"), 75.0);
+        pattern.add_axis(Tag::new(b"wght"), 700.0);
+        assert_eq!(pattern.get_bool(FC_VARIABLE), Some(true));
+        assert_eq!(
+            pattern.axis_values(),
+            [
+                AxisSetting {
+                    tag: Tag::new(b"wght"),
+                    value: 700.0
+                },
+                AxisSetting {
+                    tag: Tag::new(b"wdth"),
+                    value: 75.0
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Converting weights between Fontconfig's scale and the OpenType one used by CSS.
+
+#[cfg(not(feature = "dlopen"))]
+use crate::sys::*;
+use crate::{ffi_dispatch_optional, sys};
+
+/// A weight on Fontconfig's scale, as held by the "weight" element of a pattern.
+///
+/// Fontconfig's scale runs from [`FC_WEIGHT_THIN`](crate::FC_WEIGHT_THIN) (0) to
+/// [`FC_WEIGHT_EXTRABLACK`](crate::FC_WEIGHT_EXTRABLACK) (215), while CSS and the `usWeightClass`
+/// of the `OS/2` table use the OpenType scale from 1 to 1000.
+///
+/// ```
+/// use fontconfig::{Weight, FC_WEIGHT_BOLD};
+///
+/// let bold = Weight::from_opentype(700.0).unwrap();
+/// assert_eq!(bold, Weight(FC_WEIGHT_BOLD as f64));
+/// assert_eq!(bold.to_opentype(), Some(700.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Weight(pub f64);
+
+impl Weight {
+    /// Convert an OpenType weight to Fontconfig's scale, e.g. 700 to
+    /// [`FC_WEIGHT_BOLD`](crate::FC_WEIGHT_BOLD).
+    ///
+    /// Weights between the named ones are interpolated and weights above 1000 are clamped.
+    /// Returns `None` if `weight` is negative or the loaded Fontconfig is too old to convert it.
+    pub fn from_opentype(weight: f64) -> Option<Weight> {
+        if !sys::available::FcWeightFromOpenTypeDouble() {
+            return None;
+        }
+        let weight = unsafe { ffi_dispatch_optional!(FcWeightFromOpenTypeDouble, weight) };
+        (weight >= 0.0).then_some(Weight(weight))
+    }
+
+    /// Convert this weight to the OpenType scale.
+    ///
+    /// Returns `None` if the weight is outside Fontconfig's scale or the loaded Fontconfig is too
+    /// old to convert it.
+    pub fn to_opentype(self) -> Option<f64> {
+        if !sys::available::FcWeightToOpenTypeDouble() {
+            return None;
+        }
+        let weight = unsafe { ffi_dispatch_optional!(FcWeightToOpenTypeDouble, self.0) };
+        (weight >= 0.0).then_some(weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FC_WEIGHT_BOLD, FC_WEIGHT_EXTRABLACK, FC_WEIGHT_REGULAR, FC_WEIGHT_THIN};
+
+    #[test]
+    fn round_trip() {
+        for (fc, ot) in [
+            (FC_WEIGHT_THIN, 100.0),
+            (FC_WEIGHT_REGULAR, 400.0),
+            (FC_WEIGHT_BOLD, 700.0),
+            (FC_WEIGHT_EXTRABLACK, 1000.0),
+        ] {
+            assert_eq!(Weight::from_opentype(ot), Some(Weight(fc as f64)));
+            assert_eq!(Weight(fc as f64).to_opentype(), Some(ot));
+        }
+        // Between the named weights the scales are interpolated.
+        let semibold = Weight::from_opentype(650.0).unwrap();
+        assert!(semibold > Weight(180.0) && semibold < Weight(FC_WEIGHT_BOLD as f64));
+        assert_eq!(
+            Weight::from_opentype(5000.0),
+            Some(Weight(FC_WEIGHT_EXTRABLACK as f64))
+        );
+        assert_eq!(Weight::from_opentype(-1.0), None);
+        assert_eq!(Weight(1000.0).to_opentype(), None);
+    }
+}